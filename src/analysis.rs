@@ -0,0 +1,328 @@
+// データ解析
+
+// 区間の分類
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Region {
+    // 0x00 または 0xFF で埋められている
+    Fill,
+    // 表示可能な文字が大半
+    Text,
+    // エントロピーが高い(圧縮・暗号化)
+    HighEntropy,
+    // その他
+    Binary,
+}
+
+// 区間を分類する
+pub(crate) fn classify(buf: &[u8]) -> Region {
+    // テキストとみなす表示可能文字の割合(%)
+    const TEXT_RATIO: usize = 90;
+    // 高エントロピーとみなす閾値(bit/byte)
+    const HIGH_ENTROPY: f64 = 7.0;
+
+    if buf.is_empty() || buf.iter().all(|&x| x == 0x00) || buf.iter().all(|&x| x == 0xff) {
+        return Region::Fill;
+    }
+
+    let printable = buf
+        .iter()
+        .filter(|&&x| matches!(x, 0x20..=0x7e | b'\t' | b'\n' | b'\r'))
+        .count();
+    if printable * 100 >= buf.len() * TEXT_RATIO {
+        return Region::Text;
+    }
+
+    if entropy(buf) >= HIGH_ENTROPY {
+        Region::HighEntropy
+    } else {
+        Region::Binary
+    }
+}
+
+// シャノンエントロピー(bit/byte)
+pub(crate) fn entropy(buf: &[u8]) -> f64 {
+    if buf.is_empty() {
+        return 0.0;
+    }
+
+    let counts = histogram(buf);
+    let len = buf.len() as f64;
+    counts
+        .iter()
+        .filter(|&&x| x > 0)
+        .map(|&x| {
+            let p = x as f64 / len;
+            p * (1.0 / p).log2()
+        })
+        .sum()
+}
+
+// ファイル全体を count 個の区間に分けて分類する
+// 大きな区間は先頭から SAMPLE バイトだけを見る
+pub(crate) fn overview(buf: &[u8], count: usize) -> Vec<Region> {
+    const SAMPLE: usize = 4096;
+
+    if count == 0 {
+        return Vec::new();
+    }
+
+    (0..count)
+        .map(|i| {
+            let start = buf.len() * i / count;
+            let end = (buf.len() * (i + 1) / count).min(start + SAMPLE);
+            classify(&buf[start..end])
+        })
+        .collect()
+}
+
+// ファイル全体に count 点を取り、各点から window バイトのエントロピーを求める
+pub(crate) fn entropy_profile(buf: &[u8], count: usize, window: usize) -> Vec<f64> {
+    if buf.is_empty() {
+        return Vec::new();
+    }
+
+    (0..count)
+        .map(|i| {
+            let start = buf.len() * i / count;
+            let end = (start + window).min(buf.len());
+            entropy(&buf[start..end])
+        })
+        .collect()
+}
+
+// バイト値ごとの出現回数
+pub(crate) fn histogram(buf: &[u8]) -> [usize; 256] {
+    let mut counts = [0usize; 256];
+    buf.iter().for_each(|&x| counts[x as usize] += 1);
+    counts
+}
+
+// 文字列の種類
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum StringKind {
+    Ascii,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl StringKind {
+    pub(crate) const ALL: [StringKind; 4] = [
+        StringKind::Ascii,
+        StringKind::Utf8,
+        StringKind::Utf16Le,
+        StringKind::Utf16Be,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            StringKind::Ascii => "ascii",
+            StringKind::Utf8 => "utf8",
+            StringKind::Utf16Le => "utf16le",
+            StringKind::Utf16Be => "utf16be",
+        }
+    }
+}
+
+// 見つかった文字列
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct FoundString {
+    pub(crate) offset: usize,
+    pub(crate) kind: StringKind,
+    pub(crate) text: String,
+}
+
+// 表示可能な文字の連続(min_len 文字以上)を探す
+// UTF-16 は strings(1) と同じく ASCII の範囲の文字だけを対象にする
+pub(crate) fn find_strings(buf: &[u8], min_len: usize, kinds: &[StringKind]) -> Vec<FoundString> {
+    let mut found = Vec::new();
+    if kinds.contains(&StringKind::Ascii) || kinds.contains(&StringKind::Utf8) {
+        find_utf8_strings(buf, min_len, kinds, &mut found);
+    }
+    for (kind, little) in [(StringKind::Utf16Le, true), (StringKind::Utf16Be, false)] {
+        if kinds.contains(&kind) {
+            find_utf16_strings(buf, min_len, kind, little, &mut found);
+        }
+    }
+    found.sort_by_key(|x| x.offset);
+
+    // UTF-16 の LE と BE が1バイトずれて同じ文字列になる場合は偶数位置の方を残す
+    let twin = |x: &FoundString, y: &FoundString| {
+        x.kind != y.kind && x.offset.abs_diff(y.offset) == 1 && x.text == y.text
+    };
+    let duplicated: Vec<bool> = found
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            matches!(x.kind, StringKind::Utf16Le | StringKind::Utf16Be)
+                && !x.offset.is_multiple_of(2)
+                && found[i.saturating_sub(2)..(i + 3).min(found.len())]
+                    .iter()
+                    .any(|y| twin(x, y))
+        })
+        .collect();
+    let mut duplicated = duplicated.into_iter();
+    found.retain(|_| !duplicated.next().unwrap_or_default());
+    found
+}
+
+// 表示可能な ASCII 文字
+fn is_printable(byte: u8) -> bool {
+    matches!(byte, 0x20..=0x7e | b'\t')
+}
+
+// ASCII と UTF-8 の文字列
+// 非 ASCII 文字を含む連続を UTF-8、含まない連続を ASCII とする
+fn find_utf8_strings(
+    buf: &[u8],
+    min_len: usize,
+    kinds: &[StringKind],
+    found: &mut Vec<FoundString>,
+) {
+    let utf8 = kinds.contains(&StringKind::Utf8);
+    let mut push = |offset: usize, text: &mut String| {
+        let kind = if text.is_ascii() {
+            StringKind::Ascii
+        } else {
+            StringKind::Utf8
+        };
+        if text.chars().count() >= min_len && kinds.contains(&kind) {
+            found.push(FoundString {
+                offset,
+                kind,
+                text: std::mem::take(text),
+            });
+        }
+        text.clear();
+    };
+
+    let mut start = 0;
+    let mut text = String::new();
+    let mut i = 0;
+    while i < buf.len() {
+        // 1文字を読む
+        let ch = if is_printable(buf[i]) {
+            Some((buf[i] as char, 1))
+        } else if utf8 {
+            let width = match buf[i] {
+                0xc2..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf4 => 4,
+                _ => 0,
+            };
+            buf.get(i..i + width)
+                .and_then(|x| std::str::from_utf8(x).ok())
+                .and_then(|x| x.chars().next())
+                .filter(|x| !x.is_control())
+                .map(|x| (x, width))
+        } else {
+            None
+        };
+
+        match ch {
+            Some((ch, width)) => {
+                if text.is_empty() {
+                    start = i;
+                }
+                text.push(ch);
+                i += width;
+            }
+            None => {
+                push(start, &mut text);
+                i += 1;
+            }
+        }
+    }
+    push(start, &mut text);
+}
+
+// UTF-16 の文字列(2バイト単位、任意の位置から)
+fn find_utf16_strings(
+    buf: &[u8],
+    min_len: usize,
+    kind: StringKind,
+    little: bool,
+    found: &mut Vec<FoundString>,
+) {
+    let char_at = |i: usize| {
+        let (low, high) = match buf.get(i..i + 2)? {
+            [a, b] if little => (*a, *b),
+            [a, b] => (*b, *a),
+            _ => return None,
+        };
+        (high == 0 && is_printable(low)).then_some(low as char)
+    };
+
+    let mut i = 0;
+    while i < buf.len() {
+        if char_at(i).is_none() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut text = String::new();
+        while let Some(ch) = char_at(i) {
+            text.push(ch);
+            i += 2;
+        }
+        if text.len() >= min_len {
+            found.push(FoundString {
+                offset: start,
+                kind,
+                text,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_entropy_and_histogram() {
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(entropy(&all), 8.0);
+        assert_eq!(entropy(&[0x00; 16]), 0.0);
+
+        // 前半が埋め草、後半が全バイト値
+        let mut buf = vec![0x00; 256];
+        buf.extend_from_slice(&all);
+        assert_eq!(entropy_profile(&buf, 2, 256), vec![0.0, 8.0]);
+        assert!(entropy_profile(&[], 4, 256).is_empty());
+
+        let counts = histogram(&buf);
+        assert_eq!((counts[0x00], counts[0x01], counts[0xff]), (257, 1, 1));
+    }
+
+    #[test]
+    fn test_find_strings() {
+        let mut buf = b"\x00abc\x01hello\x00".to_vec();
+        buf.extend_from_slice("日本語テキスト\x00".as_bytes());
+        buf.extend_from_slice(b"\xffw\x00i\x00d\x00e\x00\x00\x00");
+        let found = find_strings(&buf, 4, &StringKind::ALL);
+        let summary: Vec<_> = found
+            .iter()
+            .map(|x| (x.offset, x.kind, x.text.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (5, StringKind::Ascii, "hello"),
+                (11, StringKind::Utf8, "日本語テキスト"),
+                (34, StringKind::Utf16Le, "wide"),
+            ]
+        );
+
+        // 種類の指定
+        let found = find_strings(&buf, 3, &[StringKind::Ascii]);
+        let texts: Vec<_> = found.iter().map(|x| x.text.as_str()).collect();
+        assert_eq!(texts, vec!["abc", "hello"]);
+        let found = find_strings(b"\x00A\x00B\x00C\x00D", 4, &[StringKind::Utf16Be]);
+        assert_eq!(found[0].text, "ABCD");
+        // 1バイトずれの LE と BE は偶数位置の方だけ
+        let found = find_strings(b"\x00\x00A\x00B\x00C\x00D\x00", 4, &StringKind::ALL);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].offset, found[0].kind), (2, StringKind::Utf16Le));
+    }
+}
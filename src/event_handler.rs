@@ -1,9 +1,24 @@
 // イベントハンドラ
 
 // crosstermクレート
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
+// キー割り当て
+use crate::keymap::{self, Action, Category};
 // 状態管理
-use crate::message::Message;
+use crate::message::{
+    MainView, Message, Prompt, PromptKind, Search, Sectors, TextEncoding, WriteMode,
+};
+// 文字テーブル
+use crate::table::{relative_search, relative_table, CharTable};
+// ファイル形式の解析
+use crate::format::{Codec, Format};
+// 構造テンプレート
+use crate::template::Template;
+// 変換処理系
+use crate::tui::{encode_text, entropy_index};
 
 // 入力用ミニバッファ
 #[derive(Debug)]
@@ -80,11 +95,15 @@ impl EventHandler {
             Ok(Event::Key(key_event)) if key_event.kind == KeyEventKind::Press => {
                 self.handle_key_events(&key_event, message);
             }
+            // マウス入力処理
+            Ok(Event::Mouse(mouse_event)) => {
+                self.handle_mouse_events(&mouse_event, message);
+            }
             // エラーの場合
             Err(err) => {
                 println!("Error: {}", err);
             }
-            // その他入力（マウス等）
+            // その他入力（リサイズ等）
             _ => {
                 // todo!()
             }
@@ -92,55 +111,85 @@ impl EventHandler {
     }
     // キー入力処理
     fn handle_key_events(&mut self, key_event: &KeyEvent, message: &mut Message) {
+        // ヘルプ表示中はヘルプの操作のみ
+        if message.help().is_visible() {
+            self.handle_help_keys(key_event, message);
+            return;
+        }
+        // 文字列一覧の表示中は一覧の操作のみ
+        if message.strings().is_visible() {
+            self.handle_strings_keys(key_event, message);
+            return;
+        }
+
+        // 構造パネルに入力中は木の操作
+        if message.structure().is_focused() && message.prompt().is_none() {
+            self.handle_structure_keys(key_event, message);
+            return;
+        }
+
+        // 入力欄の表示中は入力欄の操作のみ
+        if message.prompt().is_some() {
+            self.handle_prompt_keys(key_event, message);
+            return;
+        }
+
+        // キー割り当て表から操作を探す
+        let Some(action) = keymap::lookup(key_event) else {
+            return;
+        };
+
         let len = message.bin_data().buf().len();
+        let step = message.cursor_step();
+        let row_step = message.cursor_row_step();
+        let typed = *message.main_view() == MainView::Typed;
         let cursor = message.cursor_mut();
 
-        // Ctrl や SHIFT等のコンビネーションキー処理
-        match key_event.modifiers {
-            // Ctrlが押されている場合
-            KeyModifiers::CONTROL => {
-                // 対のキーの処理
-                match key_event.code {
-                    // Ctrl + qが入力されたら
-                    KeyCode::Char('q') | KeyCode::Char('Q') => {
-                        // イベントループ終了
-                        self.looping = false;
-                    }
-                    _ => {}
-                }
+        match action {
+            // イベントループ終了
+            Action::Quit => {
+                self.looping = false;
             }
-            // KeyModifiers::SHIFT 等
-            _ => {
-                // todo!()
+
+            // ヘルプ表示
+            Action::Help => {
+                message.help_mut().show();
             }
-        }
 
-        // 通常のキー入力処理
-        match key_event.code {
-            // 文字関連
             // カーソル左移動
-            KeyCode::Char('h') | KeyCode::Char('H') => {
-                cursor.move_to_left();
+            Action::MoveLeft => {
+                cursor.move_to_left_by(step);
                 self.reset_input_buf(message);
             }
             // カーソル右移動
-            KeyCode::Char('l') | KeyCode::Char('L') => {
-                cursor.move_to_right(len);
+            Action::MoveRight => {
+                cursor.move_to_right_by(step, len);
                 self.reset_input_buf(message);
             }
             // カーソル下移動
-            KeyCode::Char('j') | KeyCode::Char('J') => {
-                cursor.move_to_down(len);
+            Action::MoveDown => {
+                cursor.move_to_down_by(row_step, len);
                 self.reset_input_buf(message);
             }
             // カーソル上移動
-            KeyCode::Char('k') | KeyCode::Char('K') => {
-                cursor.move_to_up();
+            Action::MoveUp => {
+                cursor.move_to_up_by(row_step);
                 self.reset_input_buf(message);
             }
 
+            // 範囲選択の開始・解除
+            Action::ToggleSelection => {
+                let index = cursor.index();
+                message.selection_mut().toggle(index);
+            }
+
+            // 範囲選択の解除
+            Action::ClearSelection => {
+                message.selection_mut().clear();
+            }
+
             // 削除
-            KeyCode::Delete | KeyCode::Char('x') | KeyCode::Char('X') => {
+            Action::Delete => {
                 let index = cursor.index();
 
                 // 最後尾の場合は、カーソルを移動
@@ -159,14 +208,19 @@ impl EventHandler {
             }
 
             // 書き込みモード変更
-            KeyCode::Char('i') | KeyCode::Char('I') => {
+            Action::ToggleMode => {
                 message.toggle_mode();
             }
 
+            // エンディアン切り替え
+            Action::ToggleEndian => {
+                message.toggle_endian();
+            }
+
             // ファイルへ保存
-            KeyCode::Char('w') | KeyCode::Char('W') => {
-                if let Some(path) = message.current_file().path() {
-                    if let Err(e) = message.bin_data().export_to(path) {
+            Action::Save => {
+                if let Some(path) = message.current_file().path().cloned() {
+                    if let Err(e) = message.bin_data_mut().export_to(&path) {
                         message.notice_mut().add(e.to_string());
                     } else {
                         let success_msg = String::from("Saved!");
@@ -178,53 +232,727 @@ impl EventHandler {
                 }
             }
 
+            // コマンド入力
+            Action::Command => {
+                *message.prompt_mut() = Some(Prompt::new(PromptKind::Command));
+            }
+
+            // テキスト入力
+            Action::TypeText => {
+                *message.prompt_mut() = Some(Prompt::new(PromptKind::Text));
+            }
+
+            // テキスト検索
+            Action::Search => {
+                *message.prompt_mut() = Some(Prompt::new(PromptKind::Search));
+            }
+
+            // 次を検索
+            Action::SearchNext => {
+                let start = cursor.index() + 1;
+                self.search_from(start, message);
+            }
+
+            // レイアウト切り替え
+            Action::NextLayout => {
+                let layout = message.settings_mut().next_layout();
+                message.notice_mut().add(format!("Layout: {layout}"));
+                message.save_settings();
+            }
+
+            // インスペクタ表示切り替え
+            Action::ToggleInspector => {
+                message.settings_mut().toggle_inspector();
+                message.save_settings();
+            }
+
+            // 全体俯瞰ストリップ表示切り替え
+            Action::ToggleOverview => {
+                message.settings_mut().toggle_overview();
+                message.save_settings();
+            }
+
+            // メインパネル縮小
+            Action::ShrinkMain => {
+                message.settings_mut().shrink_main();
+                message.save_settings();
+            }
+
+            // メインパネル拡大
+            Action::GrowMain => {
+                message.settings_mut().grow_main();
+                message.save_settings();
+            }
+
+            // テーマ切り替え
+            Action::NextTheme => {
+                message.next_theme();
+            }
+
+            // テキストパネルの文字コード切り替え
+            Action::NextEncoding => {
+                let encoding = message.next_text_encoding().to_string();
+                message.notice_mut().add(format!("Encoding: {encoding}"));
+            }
+            Action::PrevEncoding => {
+                let encoding = message.prev_text_encoding().to_string();
+                message.notice_mut().add(format!("Encoding: {encoding}"));
+            }
+
+            // 制御文字の表示方法切り替え
+            Action::NextControlDisplay => {
+                let control = message.next_control_display().to_string();
+                message
+                    .notice_mut()
+                    .add(format!("Control chars: {control}"));
+            }
+
             // 数値データ入力
-            KeyCode::Char(char_code @ ('0'..='9' | 'a'..='f' | 'A'..='F')) => {
-                // 入力データをミニバッファへ書き込み
-                self.input_buf.add(char_code);
+            // 型付き表示では値の入力欄を開く
+            Action::HexInput => {
+                if let KeyCode::Char(char_code) = key_event.code {
+                    if typed {
+                        let mut prompt = Prompt::new(PromptKind::Value);
+                        prompt.input_mut().push(char_code);
+                        *message.prompt_mut() = Some(prompt);
+                    } else {
+                        self.input_hex(char_code, message);
+                    }
+                }
+            }
+
+            // 型付き表示の値の編集(現在の値から)
+            Action::EditValue => {
+                if typed {
+                    let index = cursor.index();
+                    let value = message
+                        .value_type()
+                        .format(&message.bin_data().buf()[index..], *message.endian())
+                        .unwrap_or_default();
+                    let mut prompt = Prompt::new(PromptKind::Value);
+                    *prompt.input_mut() = value;
+                    *message.prompt_mut() = Some(prompt);
+                }
+            }
+
+            // メインパネルの表示切り替え
+            Action::NextView => {
+                let view = message.next_main_view().to_string();
+                message.notice_mut().add(format!("View: {view}"));
+                self.reset_input_buf(message);
+            }
+
+            // 型付き表示の型切り替え
+            Action::NextValueType => {
+                let value_type = message.next_value_type().to_string();
+                message.notice_mut().add(format!("Type: {value_type}"));
+                self.reset_input_buf(message);
+            }
+
+            // 画素表示の設定
+            Action::NextPixelFormat => {
+                let format = message.bitmap_mut().next_format();
+                message.notice_mut().add(format!("Pixel: {format}"));
+            }
+            Action::NextPalette => {
+                let palette = message.bitmap_mut().next_palette();
+                message.notice_mut().add(format!("Palette: {palette}"));
+            }
+            Action::NarrowBitmap => message.bitmap_mut().resize(-1),
+            Action::WidenBitmap => message.bitmap_mut().resize(1),
+
+            // 波形表示の設定
+            Action::NextSampleType => {
+                let sample = message.plot_mut().next_sample();
+                message.align_cursor();
+                message.notice_mut().add(format!("Sample: {sample}"));
+                self.reset_input_buf(message);
+            }
+            Action::TogglePlotStyle => {
+                let style = message.plot_mut().toggle_style();
+                message.notice_mut().add(format!("Plot: {style}"));
+            }
+            Action::NextChannel => {
+                let channel = message.plot_mut().next_channel();
+                let channels = message.plot().channels();
+                let notice = format!("Channel: {}/{channels}", channel + 1);
+                message.notice_mut().add(notice);
+            }
+            Action::ZoomIn => message.plot_mut().zoom_in(),
+            Action::ZoomOut => message.plot_mut().zoom_out(),
+
+            // 文字列一覧
+            Action::Strings => message.show_strings(),
+
+            // 構造パネル
+            Action::ToggleStructure => {
+                if message.structure().root().is_none() {
+                    message.notice_mut().add(String::from(
+                        "No structure (:parse or :template <file> [offset])",
+                    ));
+                } else {
+                    message.structure_mut().toggle_visible();
+                }
+            }
+            Action::FocusStructure => {
+                if message.structure().is_visible() {
+                    message.structure_mut().set_focused(true);
+                }
+            }
+            // 一覧やパネルの操作は lookup では返らない
+            Action::SelectNext
+            | Action::SelectPrev
+            | Action::PageDown
+            | Action::PageUp
+            | Action::Filter
+            | Action::DeleteFilter
+            | Action::Jump
+            | Action::Close
+            | Action::Expand
+            | Action::Collapse
+            | Action::ToggleFold
+            | Action::FollowLink
+            | Action::Unfocus => {}
+        }
+    }
+
+    // ヘルプ表示中のキー入力処理
+    fn handle_help_keys(&mut self, key_event: &KeyEvent, message: &mut Message) {
+        match keymap::lookup(key_event) {
+            // スクロール
+            Some(Action::MoveDown) => message.help_mut().scroll_down(),
+            Some(Action::MoveUp) => message.help_mut().scroll_up(),
+            // 終了はそのまま受け付ける
+            Some(Action::Quit) => self.looping = false,
+            // その他のキーで閉じる
+            _ => message.help_mut().hide(),
+        }
+    }
 
-                // 16進数へ変換
-                let res = self.input_buf.to_hex();
+    // 文字列一覧のキー入力処理
+    // 文字の入力は絞り込みに使う
+    fn handle_strings_keys(&mut self, key_event: &KeyEvent, message: &mut Message) {
+        const PAGE: isize = 10;
+        let strings = message.strings_mut();
+        match keymap::lookup_in(key_event, Category::Strings) {
+            Some(Action::Close) => strings.hide(),
+            Some(Action::SelectNext) => strings.select_by(1),
+            Some(Action::SelectPrev) => strings.select_by(-1),
+            Some(Action::PageDown) => strings.select_by(PAGE),
+            Some(Action::PageUp) => strings.select_by(-PAGE),
+            Some(Action::DeleteFilter) => strings.pop_filter(),
+            Some(Action::Filter) => {
+                if let KeyCode::Char(c) = key_event.code {
+                    strings.push_filter(c);
+                }
+            }
+            // 選択中の文字列へ移動して閉じる
+            Some(Action::Jump) => {
+                if let Some(offset) = strings.selected_item().map(|x| x.offset) {
+                    strings.hide();
+                    let len = message.bin_data().buf().len();
+                    message.cursor_mut().jump_to(offset, len);
+                    self.reset_input_buf(message);
+                }
+            }
+            // 終了とヘルプはそのまま受け付ける
+            _ => match keymap::lookup(key_event) {
+                Some(Action::Quit) => self.looping = false,
+                Some(Action::Help) => message.help_mut().show(),
+                _ => {}
+            },
+        }
+    }
+
+    // 構造パネルのキー入力処理
+    // 項目を選ぶとその先頭へカーソルを移動する
+    fn handle_structure_keys(&mut self, key_event: &KeyEvent, message: &mut Message) {
+        const PAGE: isize = 10;
+        let structure = message.structure_mut();
+        let action =
+            keymap::lookup_in(key_event, Category::Structure).or_else(|| keymap::lookup(key_event));
+        match action {
+            Some(Action::SelectNext) => structure.select_by(1),
+            Some(Action::SelectPrev) => structure.select_by(-1),
+            Some(Action::PageDown) => structure.select_by(PAGE),
+            Some(Action::PageUp) => structure.select_by(-PAGE),
+            Some(Action::Expand) => structure.set_collapsed(false),
+            Some(Action::Collapse) => structure.set_collapsed(true),
+            Some(Action::ToggleFold) => structure.toggle_collapsed(),
+            // 参照先(セクションの中身など)へ移動
+            Some(Action::FollowLink) => {
+                if message.jump_to_link() {
+                    self.reset_input_buf(message);
+                } else {
+                    let notice = String::from("No link on this field");
+                    message.notice_mut().add(notice);
+                }
+                return;
+            }
+            // 16進数パネルへ戻る
+            Some(Action::Unfocus) => {
+                structure.set_focused(false);
+                return;
+            }
+            Some(Action::ToggleStructure) => {
+                structure.toggle_visible();
+                return;
+            }
+            Some(Action::Quit) => {
+                self.looping = false;
+                return;
+            }
+            // コマンドとヘルプはそのまま受け付ける
+            Some(Action::Command) => {
+                *message.prompt_mut() = Some(Prompt::new(PromptKind::Command));
+                return;
+            }
+            Some(Action::Help) => {
+                message.help_mut().show();
+                return;
+            }
+            _ => return,
+        }
+        message.jump_to_field();
+        self.reset_input_buf(message);
+    }
 
-                // 16進数へ変換が成功なら
-                if let Ok(val) = res {
-                    use crate::message::WriteMode::*;
+    // 入力欄のキー入力処理
+    fn handle_prompt_keys(&mut self, key_event: &KeyEvent, message: &mut Message) {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            // 取り消し
+            KeyCode::Esc => {
+                *message.prompt_mut() = None;
+            }
+            // 確定
+            KeyCode::Enter => {
+                if let Some(prompt) = message.prompt_mut().take() {
+                    self.submit_prompt(prompt.kind(), prompt.input(), message);
+                }
+            }
+            // 1文字削除
+            KeyCode::Backspace => {
+                if let Some(prompt) = message.prompt_mut() {
+                    prompt.input_mut().pop();
+                }
+            }
+            // 1文字追加
+            KeyCode::Char(c) if !ctrl => {
+                if let Some(prompt) = message.prompt_mut() {
+                    prompt.input_mut().push(c);
+                }
+            }
+            _ => {}
+        }
+    }
 
+    // 入力欄の内容を実行
+    fn submit_prompt(&mut self, kind: PromptKind, input: &str, message: &mut Message) {
+        if input.is_empty() {
+            return;
+        }
+        match kind {
+            PromptKind::Command => self.run_command(input, message),
+            PromptKind::Search => match self.encode(input, message) {
+                Ok(bytes) => {
+                    *message.search_mut() = Some(Search::Bytes(bytes));
+                    let start = message.cursor().index() + 1;
+                    self.search_from(start, message);
+                }
+                Err(e) => message.notice_mut().add(e),
+            },
+            PromptKind::Text => match self.encode(input, message) {
+                Ok(bytes) => self.write_bytes(&bytes, message),
+                Err(e) => message.notice_mut().add(e),
+            },
+            // 型付き表示の値はカーソル位置の要素を上書きする
+            PromptKind::Value => match message.value_type().parse(input, *message.endian()) {
+                Ok(bytes) => {
                     let index = message.cursor().index();
-                    match message.write_mode() {
-                        // 上書き処理
-                        OverWrite => {
-                            message.bin_data_mut().update(index, val);
-                        }
-                        // 挿入処理
-                        Insert => {
-                            // 最初の桁に入力あり
-                            if self.input_buf.index() != 0 {
-                                // 下の桁を0にする
-                                self.input_buf.set_value(0);
-                                self.input_buf.add(char_code);
-
-                                // 16進数へ変換
-                                let res = self.input_buf.to_hex();
-                                if let Ok(val) = res {
-                                    message.bin_data_mut().insert(index, val);
-                                }
-                            }
-
-                            // 最後の桁に入力あり
-                            if self.input_buf.index() == 0 {
-                                message.bin_data_mut().update(index, val);
-                            }
+                    message.bin_data_mut().overwrite(index, &bytes);
+                    self.reset_input_buf(message);
+                }
+                Err(e) => message.notice_mut().add(e),
+            },
+        }
+    }
+
+    // コマンドの実行
+    fn run_command(&mut self, input: &str, message: &mut Message) {
+        let (command, arg) = match input.trim().split_once(char::is_whitespace) {
+            Some((command, arg)) => (command, arg.trim()),
+            None => (input.trim(), ""),
+        };
+
+        match (command, arg) {
+            // 読み込み済みの文字テーブルへ切り替え
+            ("tbl", "") => match message.char_table() {
+                Some(_) => *message.text_encoding_mut() = TextEncoding::Table,
+                None => message.notice_mut().add(String::from("No table loaded")),
+            },
+            // 文字テーブルの読み込み
+            ("tbl", path) => match CharTable::load(path) {
+                Ok(table) => {
+                    let notice = format!("Table: {} ({} entries)", table.name(), table.len());
+                    message.set_char_table(table);
+                    message.notice_mut().add(notice);
+                }
+                Err(e) => message.notice_mut().add(e),
+            },
+            // 画素表示の設定(画素表示へ切り替える)
+            ("bitmap", args) => match message.bitmap_mut().apply(args) {
+                Ok(()) => {
+                    message.set_main_view(MainView::Bitmap);
+                    let notice = format!("Bitmap: {}", message.bitmap());
+                    message.notice_mut().add(notice);
+                }
+                Err(e) => message.notice_mut().add(e),
+            },
+            // 波形表示の設定(波形表示へ切り替える)
+            ("plot", args) => match message.plot_mut().apply(args) {
+                Ok(()) => {
+                    message.set_main_view(MainView::Plot);
+                    let notice = format!("Plot: {}", message.plot());
+                    message.notice_mut().add(notice);
+                }
+                Err(e) => message.notice_mut().add(e),
+            },
+            // エントロピーの窓の大きさ(解析表示へ切り替える)
+            ("entropy", "") => message.set_main_view(MainView::Analysis),
+            ("entropy", window) => match window.parse::<usize>() {
+                Ok(size) if size > 0 => {
+                    *message.entropy_window_mut() = size;
+                    message.set_main_view(MainView::Analysis);
+                }
+                _ => message
+                    .notice_mut()
+                    .add(format!("Invalid window: {window}")),
+            },
+            // 文字列一覧(最短の文字数と種類を指定できる)
+            ("strings", args) => match message.strings_mut().apply(args) {
+                Ok(()) => message.show_strings(),
+                Err(e) => message.notice_mut().add(e),
+            },
+            // 構造テンプレートの読み込み(開始位置の省略時はカーソル位置)
+            ("template" | "tp", args) => self.apply_template(args, message),
+            // カーソル位置(選択中は選択範囲)から直列化形式を復号
+            ("decode", name) => self.decode(name, message),
+            // セクタ(ページ+OOB)単位の表示
+            ("sector", "off") => {
+                *message.sectors_mut() = None;
+                message.notice_mut().add(String::from("Sectors: off"));
+            }
+            ("sector", size) => match size.parse::<Sectors>() {
+                Ok(sectors) => {
+                    *message.sectors_mut() = Some(sectors);
+                    message.notice_mut().add(format!("Sectors: {sectors}"));
+                }
+                Err(e) => message.notice_mut().add(e),
+            },
+            // 指定位置へ移動(解析済みの形式のアドレスも指定可能)
+            ("goto" | "g", args) => self.goto(args, message),
+            // ファイル形式の解析(形式の省略時は自動判定)
+            ("parse", name) => self.parse_format(name, message),
+            // 制御文字の表示方法の指定
+            ("ctrl", name) => match name.parse() {
+                Ok(control) => {
+                    *message.control_display_mut() = control;
+                    message
+                        .notice_mut()
+                        .add(format!("Control chars: {control}"));
+                }
+                Err(e) => message.notice_mut().add(e),
+            },
+            // 相対検索
+            ("rsearch" | "rs", word) => match relative_search(message.bin_data().buf(), word) {
+                Ok(hits) => {
+                    let notice = match hits.len() {
+                        0 => String::from("Not found"),
+                        n => format!("{n} hits (n: next, :rtbl [file]: make table)"),
+                    };
+                    *message.search_mut() = Some(Search::Relative {
+                        word: word.to_string(),
+                        hits,
+                    });
+                    message.notice_mut().add(notice);
+                    let start = message.cursor().index();
+                    self.search_from(start, message);
+                }
+                Err(e) => message.notice_mut().add(e),
+            },
+            // カーソル位置の相対検索の一致から仮の文字テーブルを作る
+            ("rtbl", path) => self.make_relative_table(path, message),
+            // 文字コードの指定
+            ("enc", name) => match name.parse() {
+                Ok(encoding) => {
+                    *message.text_encoding_mut() = encoding;
+                    message.notice_mut().add(format!("Encoding: {encoding}"));
+                }
+                Err(e) => message.notice_mut().add(e),
+            },
+            _ => message
+                .notice_mut()
+                .add(format!("Unknown command: {command}")),
+        }
+    }
+
+    // 構造テンプレートを読み込んで当てはめる
+    fn apply_template(&mut self, args: &str, message: &mut Message) {
+        let (path, offset) = match args.rsplit_once(char::is_whitespace) {
+            Some((path, offset)) => match parse_offset(offset) {
+                Some(offset) => (path.trim(), offset),
+                None => (args, message.cursor().index()),
+            },
+            None => (args, message.cursor().index()),
+        };
+        match Template::load(path) {
+            Ok(template) => {
+                let endian = *message.endian();
+                let (root, error) = template.apply(message.bin_data().buf(), offset, endian);
+                let notice =
+                    error.unwrap_or_else(|| format!("Template: {} bytes", root.range.len()));
+                message.structure_mut().set_root(root);
+                message.notice_mut().add(notice);
+            }
+            Err(e) => message.notice_mut().add(e),
+        }
+    }
+
+    // 指定位置へ移動
+    // "rva 0x1000" や "cluster 5" のように解析済みの形式のアドレスで指定した場合は変換する
+    fn goto(&mut self, args: &str, message: &mut Message) {
+        let index = match args.split_once(char::is_whitespace) {
+            // セクタ単位の表示中はセクタ(ページ)番号で指定できる
+            Some((unit @ ("sector" | "page"), number)) => {
+                let number = number.trim();
+                let len = message.bin_data().buf().len();
+                match (message.sectors(), parse_offset(number)) {
+                    (Some(sectors), Some(value)) => sectors
+                        .start(value)
+                        .filter(|&x| x < len)
+                        .ok_or_else(|| format!("{unit} {number} is not in the file")),
+                    (Some(_), None) => Err(format!("Invalid {unit}: {number}")),
+                    (None, _) => Err(String::from("No sector size (:sector 512 or 2048+64)")),
+                }
+            }
+            Some((name, address)) => {
+                let address = address.trim();
+                let map = message
+                    .address_maps()
+                    .iter()
+                    .find(|x| x.name().eq_ignore_ascii_case(name));
+                match (map, parse_offset(address)) {
+                    (Some(map), Some(value)) => map
+                        .to_offset(value as u64)
+                        .ok_or_else(|| format!("{} {address} is not in the file", map.name())),
+                    (Some(_), None) => Err(format!("Invalid address: {address}")),
+                    (None, _) => Err(format!(
+                        "No {} mapping (:parse a file that has one)",
+                        name.to_uppercase()
+                    )),
+                }
+            }
+            None => parse_offset(args).ok_or_else(|| format!("Invalid offset: {args}")),
+        };
+        match index {
+            Ok(index) => {
+                let len = message.bin_data().buf().len();
+                message.cursor_mut().jump_to(index, len);
+                self.reset_input_buf(message);
+            }
+            Err(e) => message.notice_mut().add(e),
+        }
+    }
+
+    // 直列化形式を復号して構造パネルに表示
+    fn decode(&mut self, name: &str, message: &mut Message) {
+        let codec = match name.parse::<Codec>() {
+            Ok(codec) => codec,
+            Err(e) => return message.notice_mut().add(e),
+        };
+        let buf = message.bin_data().buf();
+        let range = match message.selected_range() {
+            Some(range) => *range.start()..range.end() + 1,
+            None => message.cursor().index().min(buf.len())..buf.len(),
+        };
+        let (root, failure) = codec.decode(buf, range);
+        let notice = match failure {
+            Some(failure) => format!("{codec}: {failure}"),
+            None => format!("Decoded {} as {codec}", root.value),
+        };
+        message.structure_mut().set_root(root);
+        message.notice_mut().add(notice);
+    }
+
+    // ファイル形式を解析して構造パネルに表示
+    fn parse_format(&mut self, name: &str, message: &mut Message) {
+        let buf = message.bin_data().buf();
+        let format = if name.is_empty() {
+            Format::detect(buf).ok_or_else(|| String::from("Unknown file format"))
+        } else {
+            name.parse::<Format>()
+        };
+        match format.and_then(|x| x.parse(buf).map(|parsed| (x, parsed))) {
+            Ok((format, (root, maps))) => {
+                let notice = match root.error_count() {
+                    0 => format!("Parsed as {format}"),
+                    count => format!("Parsed as {format} ({count} problems)"),
+                };
+                message.structure_mut().set_root(root);
+                *message.address_maps_mut() = maps;
+                message.notice_mut().add(notice);
+            }
+            Err(e) => message.notice_mut().add(e),
+        }
+    }
+
+    // 相対検索の一致から仮の文字テーブルを作って読み込む
+    // path を指定した場合は保存もする
+    fn make_relative_table(&mut self, path: &str, message: &mut Message) {
+        let index = message.cursor().index();
+        let word = match message.search() {
+            Some(Search::Relative { word, hits }) if hits.contains(&index) => word.clone(),
+            Some(Search::Relative { .. }) => {
+                let notice = String::from("Move the cursor to a hit first (n)");
+                message.notice_mut().add(notice);
+                return;
+            }
+            _ => {
+                let notice = String::from("No relative search (:rsearch <word>)");
+                message.notice_mut().add(notice);
+                return;
+            }
+        };
+
+        let table = relative_table(message.bin_data().buf()[index], &word);
+        let mut notice = format!("Table: provisional ({} entries)", table.len());
+        if !path.is_empty() {
+            match table.save(path) {
+                Ok(()) => notice = format!("Saved table: {path}"),
+                Err(e) => notice = e,
+            }
+        }
+        message.set_char_table(table);
+        message.notice_mut().add(notice);
+    }
+
+    // テキストパネルの文字コードでバイト列へ変換
+    fn encode(&self, text: &str, message: &Message) -> Result<Vec<u8>, String> {
+        encode_text(text, *message.text_encoding(), message.char_table())
+    }
+
+    // 直前の検索内容を start から探してカーソルを移動
+    fn search_from(&mut self, start: usize, message: &mut Message) {
+        let Some(search) = message.search() else {
+            message.notice_mut().add(String::from("No previous search"));
+            return;
+        };
+
+        let len = message.bin_data().buf().len();
+        match search.find(message.bin_data(), start) {
+            Some(index) => {
+                if index < start {
+                    message.notice_mut().add(String::from("Search wrapped"));
+                }
+                message.cursor_mut().jump_to(index, len);
+                self.reset_input_buf(message);
+            }
+            None => message.notice_mut().add(String::from("Not found")),
+        }
+    }
+
+    // カーソル位置へバイト列を書き込み、カーソルを書き込んだ後ろへ移動
+    // 上書きモードでも末尾を超える分は追加する
+    fn write_bytes(&mut self, bytes: &[u8], message: &mut Message) {
+        let index = message.cursor().index();
+        let insert = matches!(message.write_mode(), WriteMode::Insert);
+        let bin_data = message.bin_data_mut();
+        if insert {
+            for (k, &x) in bytes.iter().enumerate() {
+                bin_data.insert(index + k, x);
+            }
+        } else {
+            bin_data.overwrite(index, bytes);
+        }
+
+        // 末尾の次(追加位置)まで移動できる
+        let len = message.bin_data().buf().len();
+        message.cursor_mut().jump_to(index + bytes.len(), len + 1);
+        self.reset_input_buf(message);
+    }
+
+    // 16進数の入力
+    fn input_hex(&mut self, char_code: char, message: &mut Message) {
+        // 入力データをミニバッファへ書き込み
+        self.input_buf.add(char_code);
+
+        // 16進数へ変換
+        let res = self.input_buf.to_hex();
+
+        // 16進数へ変換が成功なら
+        if let Ok(val) = res {
+            use crate::message::WriteMode::*;
+
+            let index = message.cursor().index();
+            match message.write_mode() {
+                // 上書き処理
+                OverWrite => {
+                    message.bin_data_mut().update(index, val);
+                }
+                // 挿入処理
+                Insert => {
+                    // 最初の桁に入力あり
+                    if self.input_buf.index() != 0 {
+                        // 下の桁を0にする
+                        self.input_buf.set_value(0);
+                        self.input_buf.add(char_code);
+
+                        // 16進数へ変換
+                        let res = self.input_buf.to_hex();
+                        if let Ok(val) = res {
+                            message.bin_data_mut().insert(index, val);
                         }
                     }
+
+                    // 最後の桁に入力あり
+                    if self.input_buf.index() == 0 {
+                        message.bin_data_mut().update(index, val);
+                    }
                 }
-                // ミニバッファの入力分、カーソルを移動
-                message.cursor_mut().input_buf_x(self.input_buf.index());
             }
+        }
+        // ミニバッファの入力分、カーソルを移動
+        message.cursor_mut().input_buf_x(self.input_buf.index());
+    }
+
+    // マウス入力処理
+    fn handle_mouse_events(&mut self, mouse_event: &MouseEvent, message: &mut Message) {
+        // 左クリックのみ対象
+        if mouse_event.kind != MouseEventKind::Down(MouseButton::Left) {
+            return;
+        }
+        let position = ratatui::layout::Position::new(mouse_event.column, mouse_event.row);
+
+        // 解析表示のエントロピーのグラフ上ならその位置へ移動
+        if let Some(area) = message.layout().entropy {
+            if area.contains(position) {
+                let len = message.bin_data().buf().len();
+                let index = entropy_index(len, position.x - area.x, area.width);
+                message.cursor_mut().jump_to(index, len);
+                self.reset_input_buf(message);
+            }
+        }
 
-            // 矢印キー等制御文字は対象外
-            _ => {
-                // todo!()
+        // 全体俯瞰ストリップ上ならその区間の先頭へ移動
+        if let Some(panel) = message.layout().overview {
+            let area = panel.inner(ratatui::layout::Margin::new(1, 1));
+            if area.contains(position) {
+                let len = message.bin_data().buf().len();
+                let slice = (position.y - area.y) as usize;
+                let index = len * slice / area.height as usize;
+                message.cursor_mut().jump_to(index, len);
+                self.reset_input_buf(message);
             }
         }
     }
@@ -237,3 +965,11 @@ impl EventHandler {
         message.cursor_mut().input_buf_x(self.input_buf.index());
     }
 }
+
+// 10進数または "0x" で始まる16進数の位置
+fn parse_offset(text: &str) -> Option<usize> {
+    match text.strip_prefix("0x").or(text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
@@ -0,0 +1,499 @@
+// ファイル形式の解析(結果は構造の木)
+
+mod archive;
+mod bytecode;
+mod capture;
+mod disk;
+mod elf;
+mod image;
+mod pe;
+mod serial;
+
+// 標準ライブラリ
+use std::fmt;
+use std::ops::Range;
+// 状態管理
+use crate::message::Endian;
+// 構造の木
+use crate::structure::Field;
+
+// 対応する形式
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Format {
+    Elf,
+    Pe,
+    Png,
+    Jpeg,
+    Gif,
+    Bmp,
+    Zip,
+    Gzip,
+    Tar,
+    Fat,
+    Mbr,
+    Pcap,
+    Pcapng,
+    Wasm,
+    Class,
+}
+
+impl Format {
+    const ALL: [Format; 15] = [
+        Format::Elf,
+        Format::Pe,
+        Format::Png,
+        Format::Jpeg,
+        Format::Gif,
+        Format::Bmp,
+        Format::Zip,
+        Format::Gzip,
+        Format::Tar,
+        // FAT のブートセクタも 55 AA で終わるので MBR より先に判定する
+        Format::Fat,
+        Format::Mbr,
+        Format::Pcap,
+        Format::Pcapng,
+        Format::Wasm,
+        Format::Class,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Format::Elf => "elf",
+            Format::Pe => "pe",
+            Format::Png => "png",
+            Format::Jpeg => "jpeg",
+            Format::Gif => "gif",
+            Format::Bmp => "bmp",
+            Format::Zip => "zip",
+            Format::Gzip => "gzip",
+            Format::Tar => "tar",
+            Format::Fat => "fat",
+            Format::Mbr => "mbr",
+            Format::Pcap => "pcap",
+            Format::Pcapng => "pcapng",
+            Format::Wasm => "wasm",
+            Format::Class => "class",
+        }
+    }
+
+    // 先頭のバイト列から形式を判定
+    pub(crate) fn detect(buf: &[u8]) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.matches(buf))
+    }
+
+    fn matches(self, buf: &[u8]) -> bool {
+        match self {
+            Format::Elf => buf.starts_with(b"\x7fELF"),
+            // DOSヘッダの e_lfanew の先に PE シグネチャ
+            Format::Pe => {
+                let lfanew = buf
+                    .get(0x3C..0x40)
+                    .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]) as usize);
+                buf.starts_with(b"MZ")
+                    && lfanew.and_then(|x| buf.get(x..x.checked_add(4)?)) == Some(b"PE\0\0")
+            }
+            Format::Png => buf.starts_with(image::PNG_SIGNATURE),
+            Format::Jpeg => buf.starts_with(&[0xFF, 0xD8, 0xFF]),
+            Format::Gif => buf.starts_with(b"GIF87a") || buf.starts_with(b"GIF89a"),
+            // ファイルヘッダの後ろに情報ヘッダの大きさが続く
+            Format::Bmp => {
+                buf.starts_with(b"BM")
+                    && matches!(
+                        buf.get(14..18),
+                        Some([12 | 40 | 52 | 56 | 108 | 124, 0, 0, 0])
+                    )
+            }
+            Format::Zip => buf.starts_with(b"PK\x03\x04") || buf.starts_with(b"PK\x05\x06"),
+            Format::Gzip => buf.starts_with(&[0x1F, 0x8B]),
+            Format::Tar => buf.get(257..262) == Some(b"ustar"),
+            Format::Fat => disk::is_fat(buf, 0),
+            // パーティションの状態は 0x00 か 0x80
+            Format::Mbr => {
+                buf.get(510..512) == Some(&[0x55, 0xAA])
+                    && (0..4).all(|i| matches!(buf[446 + i * 16], 0x00 | 0x80))
+            }
+            Format::Pcap => capture::is_pcap(buf),
+            Format::Pcapng => capture::is_pcapng(buf),
+            Format::Wasm => buf.starts_with(bytecode::WASM_MAGIC),
+            // Mach-O のユニバーサルバイナリと同じマジックなのでバージョンも見る
+            Format::Class => {
+                buf.starts_with(&[0xCA, 0xFE, 0xBA, 0xBE])
+                    && buf
+                        .get(6..8)
+                        .is_some_and(|x| u16::from_be_bytes([x[0], x[1]]) >= 45)
+            }
+        }
+    }
+
+    // 解析して構造の木にする
+    // 仮想アドレスやクラスタ番号を持つ形式はアドレスの対応表も返す
+    pub(crate) fn parse(self, buf: &[u8]) -> Result<(Field, Vec<AddressMap>), String> {
+        match self {
+            Format::Elf => Ok((elf::parse(buf)?, Vec::new())),
+            Format::Pe => pe::parse(buf).map(|(root, map)| (root, vec![map])),
+            Format::Png => Ok((image::parse_png(buf)?, Vec::new())),
+            Format::Jpeg => Ok((image::parse_jpeg(buf)?, Vec::new())),
+            Format::Gif => Ok((image::parse_gif(buf)?, Vec::new())),
+            Format::Bmp => Ok((image::parse_bmp(buf)?, Vec::new())),
+            Format::Zip => Ok((archive::parse_zip(buf)?, Vec::new())),
+            Format::Gzip => Ok((archive::parse_gzip(buf)?, Vec::new())),
+            Format::Tar => Ok((archive::parse_tar(buf)?, Vec::new())),
+            Format::Fat => disk::parse_fat(buf, 0).map(|(root, map)| (root, vec![map])),
+            Format::Mbr => disk::parse_mbr(buf),
+            Format::Pcap => Ok((capture::parse_pcap(buf)?, Vec::new())),
+            Format::Pcapng => Ok((capture::parse_pcapng(buf)?, Vec::new())),
+            Format::Wasm => Ok((bytecode::parse_wasm(buf)?, Vec::new())),
+            Format::Class => Ok((bytecode::parse_class(buf)?, Vec::new())),
+        }
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|x| x.name() == s.to_ascii_lowercase())
+            .ok_or_else(|| format!("Unknown format: {s}"))
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name().to_uppercase())
+    }
+}
+
+// カーソル位置から復号する直列化形式
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Codec {
+    Protobuf,
+    Cbor,
+    MessagePack,
+    Bson,
+}
+
+impl Codec {
+    const ALL: [Codec; 4] = [
+        Codec::Protobuf,
+        Codec::Cbor,
+        Codec::MessagePack,
+        Codec::Bson,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Codec::Protobuf => "protobuf",
+            Codec::Cbor => "cbor",
+            Codec::MessagePack => "msgpack",
+            Codec::Bson => "bson",
+        }
+    }
+
+    // range の先頭から1つの値(Protobuf は range の終わりまでのフィールド)を復号する
+    // 失敗した場合は読めたところまでの木と、失敗した位置と理由を返す
+    pub(crate) fn decode(self, buf: &[u8], range: Range<usize>) -> (Field, Option<String>) {
+        let mut decoder = serial::Decoder::new(buf, range.start, range.end);
+        let mut children = Vec::new();
+        let name = String::from("value");
+        let result = match self {
+            Codec::Protobuf => decoder.protobuf(&mut children),
+            Codec::Cbor => decoder.cbor(name, &mut children),
+            Codec::MessagePack => decoder.msgpack(name, &mut children),
+            Codec::Bson => decoder.bson(name, &mut children),
+        };
+        let end = decoder.pos();
+        let failure = result.err().map(|x| {
+            let field = Field::new("stopped", x.offset..(x.offset + 1).min(range.end), "")
+                .with_error(x.message.clone());
+            children.push(field);
+            format!("failed at 0x{:X}: {}", x.offset, x.message)
+        });
+        let mut root = Field::new(
+            format!("{self} at 0x{:X}", range.start),
+            range.start..end,
+            format!("{} bytes", end - range.start),
+        );
+        root.children = children;
+        (root, failure)
+    }
+}
+
+impl std::str::FromStr for Codec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = match s.to_ascii_lowercase().as_str() {
+            "pb" | "proto" => String::from("protobuf"),
+            "mp" | "messagepack" => String::from("msgpack"),
+            x => x.to_string(),
+        };
+        Self::ALL
+            .into_iter()
+            .find(|x| x.name() == s)
+            .ok_or_else(|| format!("Unknown codec: {s} (protobuf, cbor, msgpack, bson)"))
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Codec::Protobuf => "Protobuf",
+            Codec::MessagePack => "MessagePack",
+            x => return write!(f, "{}", x.name().to_uppercase()),
+        };
+        write!(f, "{name}")
+    }
+}
+
+// 仮想アドレス(RVA など)やクラスタ番号とファイル上の位置の対応表
+#[derive(Clone, Debug)]
+pub(crate) struct AddressMap {
+    // アドレスの呼び方
+    name: &'static str,
+    // (アドレスの範囲, ファイル上の開始位置, アドレス1つあたりのバイト数)
+    regions: Vec<(Range<u64>, usize, u64)>,
+}
+
+impl AddressMap {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            regions: Vec::new(),
+        }
+    }
+
+    // 1バイト単位のアドレス
+    fn add(&mut self, address: u64, len: u64, offset: usize) {
+        self.add_units(address, len, offset, 1);
+    }
+
+    // unit バイト単位のアドレス(クラスタ番号など)
+    fn add_units(&mut self, address: u64, count: u64, offset: usize, unit: u64) {
+        if count > 0 {
+            let range = address..address.saturating_add(count);
+            self.regions.push((range, offset, unit));
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        self.name
+    }
+
+    // アドレスに対応するファイル上の位置
+    pub(crate) fn to_offset(&self, address: u64) -> Option<usize> {
+        let (range, offset, unit) = self
+            .regions
+            .iter()
+            .find(|(range, ..)| range.contains(&address))?;
+        let delta = (address - range.start).checked_mul(*unit)?;
+        offset.checked_add(usize::try_from(delta).ok()?)
+    }
+}
+
+// 指定のエンディアンで数値を読み、項目を作る
+struct Reader<'a> {
+    buf: &'a [u8],
+    endian: Endian,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8], endian: Endian) -> Self {
+        Self { buf, endian }
+    }
+
+    // offset から len バイト
+    fn bytes(&self, offset: usize, len: usize) -> Result<&'a [u8], String> {
+        self.buf
+            .get(offset..offset.saturating_add(len))
+            .ok_or_else(|| format!("Truncated at 0x{offset:X}"))
+    }
+
+    // size バイトの符号なし整数
+    fn uint(&self, offset: usize, size: usize) -> Result<u64, String> {
+        let bytes = self.bytes(offset, size)?;
+        Ok(bytes.iter().enumerate().fold(0u64, |acc, (i, &x)| {
+            let shift = match self.endian {
+                Endian::Little => i * 8,
+                Endian::Big => (size - 1 - i) * 8,
+            };
+            acc | (x as u64) << shift
+        }))
+    }
+
+    fn u8(&self, offset: usize) -> Result<u64, String> {
+        self.uint(offset, 1)
+    }
+
+    fn u16(&self, offset: usize) -> Result<u64, String> {
+        self.uint(offset, 2)
+    }
+
+    fn u32(&self, offset: usize) -> Result<u64, String> {
+        self.uint(offset, 4)
+    }
+
+    // LEB128 の値とバイト数
+    fn leb(&self, offset: usize) -> Result<(u64, usize), String> {
+        let mut value = 0;
+        for i in 0..10 {
+            let x = self.u8(offset + i)?;
+            value |= (x & 0x7F) << (i * 7);
+            if x & 0x80 == 0 {
+                return Ok((value, i + 1));
+            }
+        }
+        Err(format!("LEB128 is too long at 0x{offset:X}"))
+    }
+
+    // 符号付き LEB128 の値とバイト数
+    fn sleb(&self, offset: usize) -> Result<(i64, usize), String> {
+        let (value, size) = self.leb(offset)?;
+        let bits = size * 7;
+        let value = match bits < 64 && value >> (bits - 1) & 1 != 0 {
+            true => (value | !0 << bits) as i64,
+            false => value as i64,
+        };
+        Ok((value, size))
+    }
+
+    // offset から順に項目を読む
+    fn record(&self, offset: usize) -> Record<'_, 'a> {
+        Record {
+            reader: self,
+            offset,
+            fields: Vec::new(),
+        }
+    }
+
+    // NUL 終端の文字列
+    fn c_str(&self, offset: usize) -> Option<String> {
+        let bytes = self.buf.get(offset..)?;
+        let len = bytes.iter().position(|&x| x == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+}
+
+// 10進数と16進数
+fn number(value: u64) -> String {
+    if value > 9 {
+        format!("{value} (0x{value:X})")
+    } else {
+        value.to_string()
+    }
+}
+
+// 連続した項目の読み取り
+struct Record<'r, 'a> {
+    reader: &'r Reader<'a>,
+    // 次の項目の位置
+    offset: usize,
+    fields: Vec<Field>,
+}
+
+impl Record<'_, '_> {
+    // 数値の項目(10進数と16進数で表示)
+    fn num(&mut self, name: &str, size: usize) -> Result<u64, String> {
+        let value = self.reader.uint(self.offset, size)?;
+        self.push(name, size, number(value));
+        Ok(value)
+    }
+
+    // 数値の項目(16進数で表示)
+    fn hex(&mut self, name: &str, size: usize) -> Result<u64, String> {
+        let value = self.reader.uint(self.offset, size)?;
+        self.push(name, size, format!("0x{value:X}"));
+        Ok(value)
+    }
+
+    // 符号付き整数の項目
+    fn int(&mut self, name: &str, size: usize) -> Result<i64, String> {
+        let value = self.reader.uint(self.offset, size)?;
+        let shift = 64 - size * 8;
+        let value = ((value << shift) as i64) >> shift;
+        self.push(name, size, value.to_string());
+        Ok(value)
+    }
+
+    // LEB128 の項目
+    fn leb(&mut self, name: &str) -> Result<u64, String> {
+        let (value, size) = self.reader.leb(self.offset)?;
+        self.push(name, size, number(value));
+        Ok(value)
+    }
+
+    // 符号付き LEB128 の項目
+    fn sleb(&mut self, name: &str) -> Result<i64, String> {
+        let (value, size) = self.reader.sleb(self.offset)?;
+        self.push(name, size, value.to_string());
+        Ok(value)
+    }
+
+    // 数値の項目(名前付きの値)
+    fn named(
+        &mut self,
+        name: &str,
+        size: usize,
+        names: impl Fn(u64) -> Option<&'static str>,
+    ) -> Result<u64, String> {
+        let value = self.reader.uint(self.offset, size)?;
+        let text = match names(value) {
+            Some(x) => format!("{x} ({value})"),
+            None => format!("0x{value:X}"),
+        };
+        self.push(name, size, text);
+        Ok(value)
+    }
+
+    // バイト列の項目(先頭の数バイトを16進数で表示)
+    fn raw(&mut self, name: &str, size: usize) -> Result<(), String> {
+        let bytes = self.reader.bytes(self.offset, size)?;
+        let mut text: Vec<String> = bytes.iter().take(8).map(|x| format!("{x:02X}")).collect();
+        if size > 8 {
+            text.push(String::from("…"));
+        }
+        self.push(name, size, text.join(" "));
+        Ok(())
+    }
+
+    // 値を指定した項目
+    fn push(&mut self, name: &str, size: usize, value: String) {
+        let range = self.offset..self.offset + size;
+        self.fields.push(Field::new(name, range, value));
+        self.offset += size;
+    }
+
+    // 読んだ項目をまとめる
+    fn finish(self, name: impl Into<String>) -> Field {
+        Field::group(name, self.fields)
+    }
+}
+
+// CRC-32(ISO-HDLC、PNG や ZIP などで使う)
+pub(super) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &x| {
+        CRC32_TABLE[((crc ^ x as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
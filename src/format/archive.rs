@@ -0,0 +1,666 @@
+// アーカイブ形式(ZIP、gzip、tar)
+
+// 親モジュール
+use super::{crc32, Reader};
+// 状態管理
+use crate::message::Endian;
+// 構造の木
+use crate::structure::Field;
+
+// 一度に展開する項目数の上限
+const MAX_ENTRIES: usize = 1 << 16;
+
+const LOCAL_SIGNATURE: &[u8] = b"PK\x03\x04";
+const CENTRAL_SIGNATURE: &[u8] = b"PK\x01\x02";
+const DESCRIPTOR_SIGNATURE: &[u8] = b"PK\x07\x08";
+const EOCD_SIGNATURE: &[u8] = b"PK\x05\x06";
+const LOCATOR_SIGNATURE: &[u8] = b"PK\x06\x07";
+const EOCD64_SIGNATURE: &[u8] = b"PK\x06\x06";
+
+// 中央ディレクトリから分かるエントリの値
+struct Central {
+    name: String,
+    local: usize,
+    compressed: u64,
+    crc: u64,
+    zip64: bool,
+}
+
+pub(super) fn parse_zip(buf: &[u8]) -> Result<Field, String> {
+    let reader = Reader::new(buf, Endian::Little);
+    let mut tail = Vec::new();
+    let mut entries = Vec::new();
+
+    // 終端レコードは後ろから探す(コメントは最大 65535 バイト)
+    let search_start = buf.len().saturating_sub(22 + 0xFFFF);
+    let eocd = (search_start..buf.len().saturating_sub(21))
+        .rev()
+        .find(|&i| buf[i..].starts_with(EOCD_SIGNATURE));
+    if let Some(eocd) = eocd {
+        let mut record = reader.record(eocd);
+        record.push("signature", 4, String::from("PK 05 06"));
+        record.num("disk", 2)?;
+        record.num("directory disk", 2)?;
+        record.num("entries on disk", 2)?;
+        let mut count = record.num("entries", 2)?;
+        record.num("directory size", 4)?;
+        let mut directory = record.hex("directory offset", 4)?;
+        let comment_len = record.num("comment length", 2)? as usize;
+        if comment_len > 0 {
+            let comment = buf.get(record.offset..record.offset + comment_len);
+            let text = comment.map(String::from_utf8_lossy).unwrap_or_default();
+            record.push("comment", comment_len, format!("{text:?}"));
+        }
+        let mut eocd_field = record.finish("end of central directory");
+
+        // ZIP64 の場合は ZIP64 終端レコードの値を使う
+        let mut zip64 = Vec::new();
+        if eocd >= 20 && buf[eocd - 20..].starts_with(LOCATOR_SIGNATURE) {
+            let mut locator = reader.record(eocd - 20);
+            locator.push("signature", 4, String::from("PK 06 07"));
+            locator.num("disk", 4)?;
+            let offset = locator.hex("record offset", 8)? as usize;
+            locator.num("disks", 4)?;
+            let mut locator = locator.finish("zip64 locator").with_link(offset);
+            if reader.bytes(offset, 4).ok() == Some(EOCD64_SIGNATURE) {
+                let mut record = reader.record(offset);
+                record.push("signature", 4, String::from("PK 06 06"));
+                record.num("record size", 8)?;
+                record.num("version made by", 2)?;
+                record.num("version needed", 2)?;
+                record.num("disk", 4)?;
+                record.num("directory disk", 4)?;
+                record.num("entries on disk", 8)?;
+                count = record.num("entries", 8)?;
+                record.num("directory size", 8)?;
+                directory = record.hex("directory offset", 8)?;
+                let field = record.finish("zip64 end of central directory");
+                zip64.push(field.with_link(directory as usize));
+            } else {
+                locator = locator.with_error("zip64 record not found");
+            }
+            zip64.push(locator);
+        }
+        eocd_field = eocd_field.with_link(directory as usize);
+
+        // 中央ディレクトリ
+        let mut fields = Vec::new();
+        let mut offset = directory as usize;
+        for i in 0..(count as usize).min(MAX_ENTRIES) {
+            if reader.bytes(offset, 4).ok() != Some(CENTRAL_SIGNATURE) {
+                let field = Field::new(format!("[{i}]"), offset..(offset + 4).min(buf.len()), "")
+                    .with_error("bad signature");
+                fields.push(field);
+                break;
+            }
+            let (field, central, next) = central_entry(&reader, offset)?;
+            fields.push(field);
+            entries.push(central);
+            offset = next;
+        }
+        let field = Field::group("central directory", fields).with_value(format!("{count}"));
+        tail.push(field);
+        tail.extend(zip64);
+        tail.push(eocd_field);
+    }
+
+    // ローカルヘッダ(中央ディレクトリがあればその指す位置、無ければ先頭から順に)
+    let mut locals = Vec::new();
+    if entries.is_empty() {
+        let mut offset = 0;
+        while locals.len() < MAX_ENTRIES && reader.bytes(offset, 4).ok() == Some(LOCAL_SIGNATURE) {
+            let (field, next) = local_entry(&reader, offset, None)?;
+            let failed = field.error.is_some();
+            locals.push(field);
+            if failed {
+                break;
+            }
+            offset = next;
+        }
+    } else {
+        entries.sort_by_key(|x| x.local);
+        for central in &entries {
+            let field = if reader.bytes(central.local, 4).ok() == Some(LOCAL_SIGNATURE) {
+                local_entry(&reader, central.local, Some(central))?.0
+            } else {
+                let range = central.local..(central.local + 4).min(buf.len());
+                Field::new(central.name.clone(), range, "").with_error("local header not found")
+            };
+            locals.push(field);
+        }
+    }
+    if locals.is_empty() && tail.is_empty() {
+        return Err(String::from("Not a ZIP file"));
+    }
+    let count = locals.len();
+    let mut children = vec![Field::group("local files", locals).with_value(format!("{count}"))];
+    children.extend(tail);
+    Ok(Field::group("ZIP", children).with_value(format!("{} bytes", buf.len())))
+}
+
+// 中央ディレクトリのエントリ
+fn central_entry(reader: &Reader, offset: usize) -> Result<(Field, Central, usize), String> {
+    let mut record = reader.record(offset);
+    record.push("signature", 4, String::from("PK 01 02"));
+    record.num("version made by", 2)?;
+    record.num("version needed", 2)?;
+    record.hex("flags", 2)?;
+    let method = record.named("method", 2, method_name)?;
+    record.hex("time", 2)?;
+    record.hex("date", 2)?;
+    let crc = record.hex("crc-32", 4)?;
+    let mut compressed = record.num("compressed size", 4)?;
+    let mut size = record.num("size", 4)?;
+    let name_len = record.num("name length", 2)? as usize;
+    let extra_len = record.num("extra length", 2)? as usize;
+    let comment_len = record.num("comment length", 2)? as usize;
+    record.num("disk", 2)?;
+    record.hex("internal attributes", 2)?;
+    record.hex("external attributes", 4)?;
+    let mut local = record.hex("local header offset", 4)?;
+    let name = text_field(&mut record, "name", name_len)?;
+    let extra = reader.bytes(record.offset, extra_len)?;
+    // 0xFFFFFFFF の値は ZIP64 拡張フィールドにある
+    let mut values = [size, compressed, local];
+    let zip64 = values.contains(&0xFFFF_FFFF);
+    zip64_extra(extra, &mut values);
+    [size, compressed, local] = values;
+    record.push("extra", extra_len, format!("{extra_len} bytes"));
+    if comment_len > 0 {
+        text_field(&mut record, "comment", comment_len)?;
+    }
+    let next = record.offset;
+    let field = record
+        .finish(name.clone())
+        .with_value(format!(
+            "{} {compressed} → {size} bytes",
+            method_text(method)
+        ))
+        .with_link(local as usize);
+    let central = Central {
+        name,
+        local: local as usize,
+        compressed,
+        crc,
+        zip64,
+    };
+    Ok((field, central, next))
+}
+
+// ローカルヘッダと続くデータ
+fn local_entry(
+    reader: &Reader,
+    offset: usize,
+    central: Option<&Central>,
+) -> Result<(Field, usize), String> {
+    let len = reader.buf.len();
+    let mut record = reader.record(offset);
+    record.push("signature", 4, String::from("PK 03 04"));
+    record.num("version needed", 2)?;
+    let flags = record.hex("flags", 2)?;
+    let method = record.named("method", 2, method_name)?;
+    record.hex("time", 2)?;
+    record.hex("date", 2)?;
+    let mut crc = record.hex("crc-32", 4)?;
+    let mut compressed = record.num("compressed size", 4)?;
+    let mut size = record.num("size", 4)?;
+    let name_len = record.num("name length", 2)? as usize;
+    let extra_len = record.num("extra length", 2)? as usize;
+    let name = text_field(&mut record, "name", name_len)?;
+    let extra = reader.bytes(record.offset, extra_len)?;
+    let mut values = [size, compressed];
+    zip64_extra(extra, &mut values);
+    [size, compressed] = values;
+    if extra_len > 0 {
+        record.push("extra", extra_len, format!("{extra_len} bytes"));
+    }
+
+    // データの大きさは中央ディレクトリの値を優先する(データ記述子の場合に必要)
+    let mut error = None;
+    let descriptor = flags & 8 != 0;
+    let start = record.offset;
+    if let Some(central) = central {
+        compressed = central.compressed;
+        crc = central.crc;
+        if central.name != name {
+            error = Some(format!(
+                "name differs from central directory ({:?})",
+                central.name
+            ));
+        }
+    } else if descriptor && compressed == 0 {
+        // 中央ディレクトリが無ければデータ記述子の署名を探す
+        compressed = reader.buf[start..]
+            .windows(4)
+            .position(|x| x == DESCRIPTOR_SIGNATURE)
+            .map_or((len - start) as u64, |x| x as u64);
+    }
+    let end = start.saturating_add(compressed as usize);
+    if end > len {
+        record.push("data", len - start, format!("{} bytes", len - start));
+        let field = record.finish(name).with_error("data exceeds file");
+        return Ok((field, len));
+    }
+    record.push("data", compressed as usize, format!("{compressed} bytes"));
+    // 格納(無圧縮)で暗号化されていなければ CRC を確かめる
+    if method == 0 && flags & 1 == 0 && (!descriptor || central.is_some()) && error.is_none() {
+        let actual = crc32(&reader.buf[start..end]) as u64;
+        if actual != crc {
+            error = Some(format!("bad CRC (computed 0x{actual:08X})"));
+        }
+    }
+
+    // データ記述子
+    if descriptor {
+        let mut fields = reader.record(end);
+        if reader.bytes(end, 4).ok() == Some(DESCRIPTOR_SIGNATURE) {
+            fields.push("signature", 4, String::from("PK 07 08"));
+        }
+        let word = if central.is_some_and(|x| x.zip64) {
+            8
+        } else {
+            4
+        };
+        fields.hex("crc-32", 4)?;
+        fields.num("compressed size", word)?;
+        fields.num("size", word)?;
+        let next = fields.offset;
+        record.fields.push(fields.finish("data descriptor"));
+        record.offset = next;
+    }
+    let next = record.offset;
+    let field = record
+        .finish(name)
+        .with_value(format!(
+            "{} {compressed} → {size} bytes",
+            method_text(method)
+        ))
+        .with_link(start);
+    Ok(match error {
+        Some(error) => (field.with_error(error), next),
+        None => (field, next),
+    })
+}
+
+// ZIP64 拡張フィールド(ID 1)の値で 0xFFFFFFFF の値を順に置き換える
+fn zip64_extra(extra: &[u8], values: &mut [u64]) {
+    let mut offset = 0;
+    while offset + 4 <= extra.len() {
+        let id = u16::from_le_bytes([extra[offset], extra[offset + 1]]);
+        let size = u16::from_le_bytes([extra[offset + 2], extra[offset + 3]]) as usize;
+        let Some(data) = extra.get(offset + 4..offset + 4 + size) else {
+            return;
+        };
+        if id == 1 {
+            let mut chunks = data.chunks_exact(8);
+            for value in values.iter_mut().filter(|x| **x == 0xFFFF_FFFF) {
+                match chunks.next() {
+                    Some(chunk) => *value = u64::from_le_bytes(chunk.try_into().unwrap()),
+                    None => return,
+                }
+            }
+            return;
+        }
+        offset += 4 + size;
+    }
+}
+
+// 文字列の項目
+fn text_field(record: &mut super::Record, name: &str, len: usize) -> Result<String, String> {
+    let bytes = record.reader.bytes(record.offset, len)?;
+    let text = String::from_utf8_lossy(bytes).into_owned();
+    record.push(name, len, format!("{text:?}"));
+    Ok(text)
+}
+
+fn method_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "stored",
+        8 => "deflate",
+        9 => "deflate64",
+        12 => "bzip2",
+        14 => "lzma",
+        93 => "zstd",
+        95 => "xz",
+        99 => "aes",
+        _ => return None,
+    })
+}
+
+fn method_text(value: u64) -> String {
+    method_name(value).map_or_else(|| format!("method {value}"), String::from)
+}
+
+// gzip(1メンバー、末尾8バイトをトレーラとみなす)
+pub(super) fn parse_gzip(buf: &[u8]) -> Result<Field, String> {
+    if !buf.starts_with(&[0x1F, 0x8B]) {
+        return Err(String::from("Not a gzip file"));
+    }
+    let reader = Reader::new(buf, Endian::Little);
+    let mut header = reader.record(0);
+    header.push("magic", 2, String::from("1F 8B"));
+    // ヘッダが読めない場合は読めた所までを残す
+    let error = match gzip_header(&mut header) {
+        Ok(error) => error,
+        Err(e) => {
+            let header = header.finish("header").with_error(e);
+            return Ok(Field::group("gzip", vec![header]));
+        }
+    };
+    let start = header.offset;
+    let mut header = header.finish("header");
+    if let Some(error) = error {
+        header = header.with_error(error);
+    }
+    if buf.len() < start + 8 {
+        let field = Field::new("trailer", start..buf.len(), "").with_error("missing trailer");
+        return Ok(Field::group("gzip", vec![header, field]));
+    }
+    let end = buf.len() - 8;
+    let data = Field::new("deflate data", start..end, format!("{} bytes", end - start));
+    let mut trailer = reader.record(end);
+    trailer.hex("crc-32", 4)?;
+    let size = trailer.num("size", 4)?;
+    let children = vec![header, data, trailer.finish("trailer")];
+    Ok(Field::group("gzip", children).with_value(format!("{} → {size} bytes", buf.len())))
+}
+
+// gzip ヘッダの magic 以降
+// ヘッダの CRC が合わない場合はその問題点を返す
+fn gzip_header(header: &mut super::Record) -> Result<Option<String>, String> {
+    let reader = header.reader;
+    header.named("method", 1, |x| (x == 8).then_some("deflate"))?;
+    let flags = header.hex("flags", 1)?;
+    header.num("mtime", 4)?;
+    header.hex("extra flags", 1)?;
+    header.named("os", 1, os_name)?;
+    if flags & 4 != 0 {
+        let len = header.num("extra length", 2)? as usize;
+        reader.bytes(header.offset, len)?;
+        header.push("extra", len, format!("{len} bytes"));
+    }
+    for (bit, name) in [(8, "name"), (16, "comment")] {
+        if flags & bit != 0 {
+            let text = reader
+                .c_str(header.offset)
+                .ok_or_else(|| format!("Unterminated {name}"))?;
+            header.push(name, text.len() + 1, format!("{text:?}"));
+        }
+    }
+    if flags & 2 != 0 {
+        let end = header.offset;
+        let stored = header.hex("header crc", 2)?;
+        let actual = (crc32(&reader.buf[..end]) & 0xFFFF) as u64;
+        if stored != actual {
+            return Ok(Some(format!("bad header CRC (computed 0x{actual:04X})")));
+        }
+    }
+    Ok(None)
+}
+
+fn os_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "FAT",
+        3 => "Unix",
+        7 => "Macintosh",
+        10 => "TOPS-20",
+        11 => "NTFS",
+        255 => "unknown",
+        _ => return None,
+    })
+}
+
+// tar の 512 バイトヘッダ
+pub(super) fn parse_tar(buf: &[u8]) -> Result<Field, String> {
+    let mut children = Vec::new();
+    let mut offset = 0;
+    while offset + 512 <= buf.len() && children.len() < MAX_ENTRIES {
+        let block = &buf[offset..offset + 512];
+        // 0 埋めのブロックで終わる
+        if block.iter().all(|&x| x == 0) {
+            let end = if buf[offset..].len() >= 1024
+                && buf[offset + 512..offset + 1024].iter().all(|&x| x == 0)
+            {
+                offset + 1024
+            } else {
+                offset + 512
+            };
+            children.push(Field::new("end of archive", offset..end, ""));
+            offset = end;
+            break;
+        }
+        let (field, size) = tar_header(buf, offset);
+        let failed = field.error.is_some();
+        children.push(field);
+        if failed {
+            break;
+        }
+        let data = offset + 512;
+        let padded = size.div_ceil(512) * 512;
+        if size > 0 {
+            let end = (data + size).min(buf.len());
+            let mut field = Field::new("data", data..end, format!("{size} bytes"));
+            if data + padded > buf.len() {
+                field = field.with_error("data exceeds file");
+            }
+            children.push(field);
+        }
+        offset = data + padded;
+    }
+    if children.is_empty() {
+        return Err(String::from("Not a tar file"));
+    }
+    if offset < buf.len() {
+        let len = buf.len() - offset;
+        children.push(Field::new(
+            "trailing data",
+            offset..buf.len(),
+            format!("{len} bytes"),
+        ));
+    }
+    Ok(Field::group("tar", children).with_value(format!("{} bytes", buf.len())))
+}
+
+// tar のヘッダ1つ(データの大きさも返す)
+fn tar_header(buf: &[u8], offset: usize) -> (Field, usize) {
+    let block = &buf[offset..offset + 512];
+    let layout: [(&str, usize, usize); 17] = [
+        ("name", 0, 100),
+        ("mode", 100, 8),
+        ("uid", 108, 8),
+        ("gid", 116, 8),
+        ("size", 124, 12),
+        ("mtime", 136, 12),
+        ("checksum", 148, 8),
+        ("type", 156, 1),
+        ("link name", 157, 100),
+        ("magic", 257, 6),
+        ("version", 263, 2),
+        ("user", 265, 32),
+        ("group", 297, 32),
+        ("dev major", 329, 8),
+        ("dev minor", 337, 8),
+        ("prefix", 345, 155),
+        ("pad", 500, 12),
+    ];
+    let text = |start: usize, len: usize| {
+        let bytes = &block[start..start + len];
+        let end = bytes.iter().position(|&x| x == 0).unwrap_or(len);
+        String::from_utf8_lossy(&bytes[..end]).into_owned()
+    };
+    let fields = layout
+        .iter()
+        .filter(|(name, ..)| *name != "pad")
+        .map(|&(name, start, len)| {
+            let value = match name {
+                "type" => type_name(block[156]),
+                _ => format!("{:?}", text(start, len).trim_end()),
+            };
+            Field::new(name, offset + start..offset + start + len, value)
+        })
+        .collect();
+
+    // チェックサムは checksum 欄を空白とみなした全バイトの和
+    let sum: u32 = block
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            if (148..156).contains(&i) {
+                32
+            } else {
+                x as u32
+            }
+        })
+        .sum();
+    let stored = parse_octal(&block[148..156]);
+    let size = parse_octal(&block[124..136]).unwrap_or(0) as usize;
+    let mut name = text(0, 100);
+    let prefix = text(345, 155);
+    if !prefix.is_empty() && &block[257..262] == b"ustar" {
+        name = format!("{prefix}/{name}");
+    }
+    let field =
+        Field::group(name, fields).with_value(format!("{} {size} bytes", type_name(block[156])));
+    let field = match stored {
+        Some(stored) if stored == sum as u64 => field,
+        Some(_) => field.with_error(format!("bad checksum (computed {sum:o})")),
+        None => field.with_error("invalid checksum field"),
+    };
+    let field = if size > 0 {
+        field.with_link(offset + 512)
+    } else {
+        field
+    };
+    (field, size)
+}
+
+// 8進数の数値欄(先頭ビットが立っていれば 256 進数)
+fn parse_octal(bytes: &[u8]) -> Option<u64> {
+    if bytes.first().is_some_and(|&x| x & 0x80 != 0) {
+        return Some(
+            bytes[1..]
+                .iter()
+                .fold((bytes[0] & 0x7F) as u64, |acc, &x| acc << 8 | x as u64),
+        );
+    }
+    let text = std::str::from_utf8(bytes).ok()?;
+    let text = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    u64::from_str_radix(text, 8).ok()
+}
+
+fn type_name(value: u8) -> String {
+    let name = match value {
+        b'0' | 0 => "file",
+        b'1' => "hard link",
+        b'2' => "symlink",
+        b'3' => "char device",
+        b'4' => "block device",
+        b'5' => "directory",
+        b'6' => "fifo",
+        b'7' => "contiguous",
+        b'g' => "global header",
+        b'x' => "extended header",
+        b'L' => "long name",
+        b'K' => "long link",
+        _ => return format!("{:?}", value as char),
+    };
+    name.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_zip() {
+        // 格納のみのエントリ1つ
+        let data = b"hello";
+        let crc = crc32(data);
+        let mut buf = Vec::new();
+        buf.extend(LOCAL_SIGNATURE);
+        buf.extend([20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        buf.extend(crc.to_le_bytes());
+        buf.extend(5u32.to_le_bytes());
+        buf.extend(5u32.to_le_bytes());
+        buf.extend(5u16.to_le_bytes());
+        buf.extend(0u16.to_le_bytes());
+        buf.extend(b"a.txt");
+        buf.extend(data);
+        let directory = buf.len();
+        buf.extend(CENTRAL_SIGNATURE);
+        buf.extend([20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        buf.extend(crc.to_le_bytes());
+        buf.extend(5u32.to_le_bytes());
+        buf.extend(5u32.to_le_bytes());
+        buf.extend(5u16.to_le_bytes());
+        buf.extend([0; 12]);
+        buf.extend(0u32.to_le_bytes());
+        buf.extend(b"a.txt");
+        let size = buf.len() - directory;
+        buf.extend(EOCD_SIGNATURE);
+        buf.extend([0, 0, 0, 0, 1, 0, 1, 0]);
+        buf.extend((size as u32).to_le_bytes());
+        buf.extend((directory as u32).to_le_bytes());
+        buf.extend([0, 0]);
+
+        let root = parse_zip(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        let central = &root.children[1].children[0];
+        assert_eq!(central.name, "a.txt");
+        assert_eq!(central.link, Some(0));
+
+        // データを壊すと CRC の不一致になる
+        buf[35] = b'j';
+        let root = parse_zip(&buf).unwrap();
+        assert_eq!(root.error_count(), 1);
+    }
+
+    #[test]
+    fn test_parse_gzip() {
+        // 名前付き、空の deflate データ
+        let mut buf = vec![0x1F, 0x8B, 8, 8, 0, 0, 0, 0, 0, 3];
+        buf.extend(b"a.txt\0");
+        buf.extend([3, 0]);
+        buf.extend([0; 8]);
+        let root = parse_gzip(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[0].children[6].value, "\"a.txt\"");
+        assert_eq!(root.children[1].range, 16..18);
+
+        // 名前が終わらない
+        let root = parse_gzip(&buf[..14]).unwrap();
+        assert_eq!(root.children[0].error.as_deref(), Some("Unterminated name"));
+
+        // 拡張フィールドの長さがファイルを超える
+        let mut broken = buf[..10].to_vec();
+        broken[3] = 4;
+        broken.extend([0xFF, 0xFF, 0, 0]);
+        let root = parse_gzip(&broken).unwrap();
+        assert!(root.children[0].error.is_some());
+
+        // 末尾の CRC と大きさが無い
+        let root = parse_gzip(&buf[..20]).unwrap();
+        assert_eq!(root.children[1].error.as_deref(), Some("missing trailer"));
+    }
+
+    #[test]
+    fn test_parse_tar() {
+        let mut buf = vec![0u8; 512 * 4];
+        buf[..5].copy_from_slice(b"a.txt");
+        buf[124..135].copy_from_slice(b"00000000005");
+        buf[156] = b'0';
+        buf[257..263].copy_from_slice(b"ustar\0");
+        let sum: u32 = buf[..512].iter().map(|&x| x as u32).sum::<u32>() + 8 * 32;
+        buf[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+        buf[512..517].copy_from_slice(b"hello");
+
+        let root = parse_tar(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[0].name, "a.txt");
+        assert_eq!(root.children[0].link, Some(512));
+        assert_eq!(root.children[2].name, "end of archive");
+
+        buf[0] = b'b';
+        assert_eq!(parse_tar(&buf).unwrap().error_count(), 1);
+    }
+}
@@ -0,0 +1,988 @@
+// バイトコード形式(WebAssembly、Java クラスファイル)
+
+// 標準ライブラリ
+use std::ops::Range;
+// 親モジュール
+use super::{Reader, Record};
+// 状態管理
+use crate::message::Endian;
+// 構造の木
+use crate::structure::Field;
+
+// 一度に展開する項目数の上限
+const MAX_ITEMS: usize = 1 << 16;
+// 命令として展開する数の上限(残りはバイト列のまま)
+const MAX_INSTRUCTIONS: usize = 1 << 16;
+
+pub(super) const WASM_MAGIC: &[u8] = b"\0asm";
+
+// WebAssembly モジュール
+pub(super) fn parse_wasm(buf: &[u8]) -> Result<Field, String> {
+    if !buf.starts_with(WASM_MAGIC) {
+        return Err(String::from("WebAssembly magic not found"));
+    }
+    let reader = Reader::new(buf, Endian::Little);
+    let mut header = reader.record(0);
+    header.push("magic", 4, String::from("\\0asm"));
+    header.num("version", 4)?;
+    let mut fields = vec![header.finish("header")];
+
+    let mut module = Module::default();
+    let mut offset = 8;
+    while offset < buf.len() {
+        let index = fields.len() - 1;
+        let mut record = reader.record(offset);
+        let id = record.named("id", 1, section_name)?;
+        let size = record.leb("size")? as usize;
+        let name = format!("[{index}] {}", section_name(id).unwrap_or("unknown"));
+        let start = record.offset;
+        let Some(end) = start.checked_add(size).filter(|&x| x <= buf.len()) else {
+            let field = record
+                .finish(name)
+                .with_error("section runs past the end of the file");
+            fields.push(field);
+            break;
+        };
+        // 中身はセクションの終わりまでに限って読む
+        let section = Reader::new(&buf[..end], Endian::Little);
+        let mut contents = section.record(start);
+        let result = module.section(&mut contents, id);
+        let left = end - contents.offset;
+        record.fields.append(&mut contents.fields);
+        let mut field = Field::group(name, record.fields);
+        field.range.end = end;
+        match result {
+            Err(e) if field.error_count() == 0 => field.error = Some(e),
+            Ok(()) if left > 0 => field.error = Some(format!("{left} bytes left in the section")),
+            _ => {}
+        }
+        fields.push(field);
+        offset = end;
+    }
+    Ok(Field::group("WebAssembly module", fields))
+}
+
+// 関数の番号と名前の対応(輸入した関数が先に並ぶ)
+#[derive(Default)]
+struct Module {
+    imported_functions: u64,
+    exports: Vec<(u64, String)>,
+    instructions: usize,
+}
+
+impl Module {
+    fn section(&mut self, record: &mut Record, id: u64) -> Result<(), String> {
+        let end = record.reader.buf.len();
+        match id {
+            0 => {
+                name(record, "name")?;
+                if record.offset < end {
+                    record.raw("contents", end - record.offset)?;
+                }
+            }
+            1 => vector(record, |record| {
+                let form = record.hex("form", 1)?;
+                if form != 0x60 {
+                    return Err(format!("unsupported type form 0x{form:02X}"));
+                }
+                let params = value_types(record, "param")?;
+                let results = value_types(record, "result")?;
+                Ok(format!("({params}) -> ({results})"))
+            })?,
+            2 => vector(record, |record| {
+                let module = name(record, "module")?;
+                let field = name(record, "name")?;
+                let kind = record.named("kind", 1, external_kind)?;
+                let description = self.external(record, kind)?;
+                Ok(format!("{module}.{field} ({description})"))
+            })?,
+            3 => vector(record, |record| Ok(format!("type {}", record.leb("type")?)))?,
+            4 => vector(record, |record| {
+                let kind = record.named("type", 1, value_type)?;
+                let limits = limits(record)?;
+                Ok(format!("{} {limits}", value_type(kind).unwrap_or("?")))
+            })?,
+            5 => vector(record, limits)?,
+            6 => vector(record, |record| {
+                let kind = record.named("type", 1, value_type)?;
+                let mutable = record.num("mutable", 1)?;
+                self.expression(record, None)?;
+                let kind = value_type(kind).unwrap_or("?");
+                Ok(match mutable {
+                    0 => kind.to_string(),
+                    _ => format!("mut {kind}"),
+                })
+            })?,
+            7 => {
+                let mut exports = Vec::new();
+                vector(record, |record| {
+                    let field = name(record, "name")?;
+                    let kind = record.named("kind", 1, external_kind)?;
+                    let index = record.leb("index")?;
+                    if kind == 0 {
+                        exports.push((index, field.clone()));
+                    }
+                    Ok(format!(
+                        "{field} ({} {index})",
+                        external_kind(kind).unwrap_or("?")
+                    ))
+                })?;
+                self.exports = exports;
+            }
+            8 => {
+                record.leb("function")?;
+            }
+            10 => {
+                let mut index = 0;
+                vector(record, |record| {
+                    let function = self.imported_functions + index;
+                    index += 1;
+                    let size = record.leb("size")? as usize;
+                    let body_end = record.offset.saturating_add(size);
+                    if body_end > end {
+                        return Err(String::from("body runs past the section"));
+                    }
+                    let locals = record.leb("local groups")?;
+                    for _ in 0..locals {
+                        if record.offset >= body_end {
+                            break;
+                        }
+                        record.leb("local count")?;
+                        record.named("local type", 1, value_type)?;
+                    }
+                    if record.offset > body_end {
+                        return Err(String::from("locals run past the body"));
+                    }
+                    self.expression(record, Some(body_end))?;
+                    if record.offset != body_end {
+                        return Err(String::from("body does not end at its size"));
+                    }
+                    let name = self
+                        .exports
+                        .iter()
+                        .find(|x| x.0 == function)
+                        .map_or(String::new(), |x| format!(" {}", x.1));
+                    Ok(format!("function {function}{name}"))
+                })?;
+            }
+            11 => vector(record, |record| {
+                let flags = record.leb("flags")?;
+                if flags == 2 {
+                    record.leb("memory")?;
+                }
+                if flags != 1 {
+                    self.expression(record, None)?;
+                }
+                let size = record.leb("size")? as usize;
+                record.raw("data", size)?;
+                Ok(format!("{size} bytes"))
+            })?,
+            12 => {
+                record.leb("count")?;
+            }
+            // 要素やタグなどは個数のみ
+            _ => {
+                record.leb("count")?;
+                if record.offset < end {
+                    record.raw("contents", end - record.offset)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 輸入の種類ごとの値
+    fn external(&mut self, record: &mut Record, kind: u64) -> Result<String, String> {
+        Ok(match kind {
+            0 => {
+                self.imported_functions += 1;
+                format!("type {}", record.leb("type")?)
+            }
+            1 => {
+                let kind = record.named("type", 1, value_type)?;
+                format!(
+                    "table {} {}",
+                    value_type(kind).unwrap_or("?"),
+                    limits(record)?
+                )
+            }
+            2 => format!("memory {}", limits(record)?),
+            3 => {
+                let kind = record.named("type", 1, value_type)?;
+                record.num("mutable", 1)?;
+                format!("global {}", value_type(kind).unwrap_or("?"))
+            }
+            4 => {
+                record.num("attribute", 1)?;
+                format!("tag type {}", record.leb("type")?)
+            }
+            _ => return Err(format!("unknown import kind {kind}")),
+        })
+    }
+
+    // 命令の並び(入れ子の end まで)
+    // 関数本体で読めない命令があれば残りをバイト列にする
+    fn expression(&mut self, record: &mut Record, body_end: Option<usize>) -> Result<(), String> {
+        let mut depth = 0;
+        loop {
+            if let Some(end) = body_end {
+                if self.instructions >= MAX_INSTRUCTIONS && record.offset < end {
+                    return record.raw("code", end - record.offset);
+                }
+            }
+            let start = record.fields.len();
+            let opcode = record.reader.u8(record.offset)?;
+            let Some(name) = opcode_name(opcode) else {
+                return match body_end {
+                    Some(end) if record.offset < end => {
+                        record.raw("undecoded", end - record.offset)
+                    }
+                    Some(_) => Err(String::from("instructions run past the body")),
+                    None => Err(format!("unsupported opcode 0x{opcode:02X}")),
+                };
+            };
+            self.instructions += 1;
+            record.push(name, 1, String::new());
+            match opcode {
+                0x02..=0x04 => {
+                    depth += 1;
+                    match record.reader.u8(record.offset)? {
+                        0x40 => record.push("block type", 1, String::from("void")),
+                        x if value_type(x).is_some() => {
+                            record.named("block type", 1, value_type)?;
+                        }
+                        _ => {
+                            record.sleb("block type")?;
+                        }
+                    }
+                }
+                0x0B if depth == 0 => return Ok(()),
+                0x0B => depth -= 1,
+                0x0C | 0x0D => {
+                    record.leb("label")?;
+                }
+                0x0E => {
+                    let count = record.leb("count")?;
+                    for _ in 0..count {
+                        record.leb("label")?;
+                    }
+                    record.leb("default")?;
+                }
+                0x10 | 0xD2 => {
+                    record.leb("function")?;
+                }
+                0x11 => {
+                    record.leb("type")?;
+                    record.leb("table")?;
+                }
+                0x1C => {
+                    let count = record.leb("count")?;
+                    for _ in 0..count {
+                        record.named("type", 1, value_type)?;
+                    }
+                }
+                0x20..=0x22 => {
+                    record.leb("local")?;
+                }
+                0x23 | 0x24 => {
+                    record.leb("global")?;
+                }
+                0x25 | 0x26 => {
+                    record.leb("table")?;
+                }
+                0x28..=0x3E => {
+                    record.leb("align")?;
+                    record.leb("offset")?;
+                }
+                0x3F | 0x40 => {
+                    record.num("memory", 1)?;
+                }
+                0x41 | 0x42 => {
+                    record.sleb("value")?;
+                }
+                0x43 => {
+                    let value = record.reader.u32(record.offset)?;
+                    record.push("value", 4, f32::from_bits(value as u32).to_string());
+                }
+                0x44 => {
+                    let value = record.reader.uint(record.offset, 8)?;
+                    record.push("value", 8, f64::from_bits(value).to_string());
+                }
+                0xD0 => {
+                    record.named("type", 1, value_type)?;
+                }
+                0xFC => {
+                    let name = prefixed(record)?;
+                    record.fields[start].name = String::from(name);
+                }
+                _ => {}
+            }
+            // 即値を持つ命令は命令ごとにまとめる
+            let mut children = record.fields.split_off(start);
+            if children.len() > 1 {
+                let name = children[0].name.clone();
+                let value: Vec<&str> = children[1..].iter().map(|x| x.value.as_str()).collect();
+                let value = value.join(" ");
+                children[0].name = String::from("opcode");
+                record
+                    .fields
+                    .push(Field::group(name, children).with_value(value));
+            } else {
+                record.fields.append(&mut children);
+            }
+        }
+    }
+}
+
+// 0xFC で始まる命令(名前を返す)
+fn prefixed(record: &mut Record) -> Result<&'static str, String> {
+    const TRUNC_SAT: [&str; 8] = [
+        "i32.trunc_sat_f32_s",
+        "i32.trunc_sat_f32_u",
+        "i32.trunc_sat_f64_s",
+        "i32.trunc_sat_f64_u",
+        "i64.trunc_sat_f32_s",
+        "i64.trunc_sat_f32_u",
+        "i64.trunc_sat_f64_s",
+        "i64.trunc_sat_f64_u",
+    ];
+    let code = record.leb("code")?;
+    Ok(match code {
+        0..=7 => TRUNC_SAT[code as usize],
+        8 => {
+            record.leb("data")?;
+            record.num("memory", 1)?;
+            "memory.init"
+        }
+        9 => {
+            record.leb("data")?;
+            "data.drop"
+        }
+        10 => {
+            record.num("memory", 1)?;
+            record.num("memory", 1)?;
+            "memory.copy"
+        }
+        11 => {
+            record.num("memory", 1)?;
+            "memory.fill"
+        }
+        12 => {
+            record.leb("element")?;
+            record.leb("table")?;
+            "table.init"
+        }
+        13 => {
+            record.leb("element")?;
+            "elem.drop"
+        }
+        14 => {
+            record.leb("table")?;
+            record.leb("table")?;
+            "table.copy"
+        }
+        15..=17 => {
+            record.leb("table")?;
+            ["table.grow", "table.size", "table.fill"][code as usize - 15]
+        }
+        _ => return Err(format!("unsupported opcode 0xFC {code}")),
+    })
+}
+
+// 個数の後に項目が続く並び
+// 項目ごとにまとめ、読めなかった項目は問題として残す
+fn vector(
+    record: &mut Record,
+    mut item: impl FnMut(&mut Record) -> Result<String, String>,
+) -> Result<(), String> {
+    let count = record.leb("count")? as usize;
+    let leaf = record.fields.len() - 1;
+    for i in 0..count.min(MAX_ITEMS) {
+        let start = record.fields.len();
+        let result = item(record);
+        let children = record.fields.split_off(start);
+        let field = Field::group(format!("[{i}]"), children);
+        match result {
+            Ok(value) => record.fields.push(field.with_value(value)),
+            Err(e) => {
+                record.fields.push(field.with_error(e.clone()));
+                return Err(e);
+            }
+        }
+    }
+    // 上限を超えた分はまとめてバイト列にする
+    if count > MAX_ITEMS {
+        record.fields[leaf].error = Some(format!("only the first {MAX_ITEMS} items are shown"));
+        let end = record.reader.buf.len();
+        if record.offset < end {
+            record.raw("rest", end - record.offset)?;
+        }
+    }
+    Ok(())
+}
+
+// 長さ付きの名前
+fn name(record: &mut Record, label: &str) -> Result<String, String> {
+    let len = record.leb(&format!("{label} length"))? as usize;
+    let bytes = record.reader.bytes(record.offset, len)?;
+    let text = String::from_utf8_lossy(bytes).into_owned();
+    record.push(label, len, format!("{text:?}"));
+    Ok(text)
+}
+
+// 型の並び
+fn value_types(record: &mut Record, label: &str) -> Result<String, String> {
+    let count = record.leb(&format!("{label} count"))?;
+    let mut names = Vec::new();
+    for _ in 0..count {
+        let kind = record.named(label, 1, value_type)?;
+        names.push(value_type(kind).unwrap_or("?"));
+    }
+    Ok(names.join(", "))
+}
+
+// 最小値と(あれば)最大値
+fn limits(record: &mut Record) -> Result<String, String> {
+    let flags = record.hex("flags", 1)?;
+    let min = record.leb("min")?;
+    Ok(match flags & 1 {
+        0 => format!("{min}.."),
+        _ => format!("{min}..{}", record.leb("max")?),
+    })
+}
+
+fn section_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "custom",
+        1 => "type",
+        2 => "import",
+        3 => "function",
+        4 => "table",
+        5 => "memory",
+        6 => "global",
+        7 => "export",
+        8 => "start",
+        9 => "element",
+        10 => "code",
+        11 => "data",
+        12 => "data count",
+        13 => "tag",
+        _ => return None,
+    })
+}
+
+fn external_kind(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "func",
+        1 => "table",
+        2 => "memory",
+        3 => "global",
+        4 => "tag",
+        _ => return None,
+    })
+}
+
+fn value_type(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0x7F => "i32",
+        0x7E => "i64",
+        0x7D => "f32",
+        0x7C => "f64",
+        0x7B => "v128",
+        0x70 => "funcref",
+        0x6F => "externref",
+        _ => return None,
+    })
+}
+
+// 0x45 から 0xC4 までの数値命令
+const NUMERIC: &str = "i32.eqz i32.eq i32.ne i32.lt_s i32.lt_u i32.gt_s i32.gt_u i32.le_s \
+    i32.le_u i32.ge_s i32.ge_u i64.eqz i64.eq i64.ne i64.lt_s i64.lt_u i64.gt_s i64.gt_u \
+    i64.le_s i64.le_u i64.ge_s i64.ge_u f32.eq f32.ne f32.lt f32.gt f32.le f32.ge f64.eq f64.ne \
+    f64.lt f64.gt f64.le f64.ge i32.clz i32.ctz i32.popcnt i32.add i32.sub i32.mul i32.div_s \
+    i32.div_u i32.rem_s i32.rem_u i32.and i32.or i32.xor i32.shl i32.shr_s i32.shr_u i32.rotl \
+    i32.rotr i64.clz i64.ctz i64.popcnt i64.add i64.sub i64.mul i64.div_s i64.div_u i64.rem_s \
+    i64.rem_u i64.and i64.or i64.xor i64.shl i64.shr_s i64.shr_u i64.rotl i64.rotr f32.abs \
+    f32.neg f32.ceil f32.floor f32.trunc f32.nearest f32.sqrt f32.add f32.sub f32.mul f32.div \
+    f32.min f32.max f32.copysign f64.abs f64.neg f64.ceil f64.floor f64.trunc f64.nearest \
+    f64.sqrt f64.add f64.sub f64.mul f64.div f64.min f64.max f64.copysign i32.wrap_i64 \
+    i32.trunc_f32_s i32.trunc_f32_u i32.trunc_f64_s i32.trunc_f64_u i64.extend_i32_s \
+    i64.extend_i32_u i64.trunc_f32_s i64.trunc_f32_u i64.trunc_f64_s i64.trunc_f64_u \
+    f32.convert_i32_s f32.convert_i32_u f32.convert_i64_s f32.convert_i64_u f32.demote_f64 \
+    f64.convert_i32_s f64.convert_i32_u f64.convert_i64_s f64.convert_i64_u f64.promote_f32 \
+    i32.reinterpret_f32 i64.reinterpret_f64 f32.reinterpret_i32 f64.reinterpret_i64 \
+    i32.extend8_s i32.extend16_s i64.extend8_s i64.extend16_s i64.extend32_s";
+
+fn opcode_name(opcode: u64) -> Option<&'static str> {
+    Some(match opcode {
+        0x00 => "unreachable",
+        0x01 => "nop",
+        0x02 => "block",
+        0x03 => "loop",
+        0x04 => "if",
+        0x05 => "else",
+        0x0B => "end",
+        0x0C => "br",
+        0x0D => "br_if",
+        0x0E => "br_table",
+        0x0F => "return",
+        0x10 => "call",
+        0x11 => "call_indirect",
+        0x1A => "drop",
+        0x1B => "select",
+        0x1C => "select t",
+        0x20 => "local.get",
+        0x21 => "local.set",
+        0x22 => "local.tee",
+        0x23 => "global.get",
+        0x24 => "global.set",
+        0x25 => "table.get",
+        0x26 => "table.set",
+        0x28 => "i32.load",
+        0x29 => "i64.load",
+        0x2A => "f32.load",
+        0x2B => "f64.load",
+        0x2C => "i32.load8_s",
+        0x2D => "i32.load8_u",
+        0x2E => "i32.load16_s",
+        0x2F => "i32.load16_u",
+        0x30 => "i64.load8_s",
+        0x31 => "i64.load8_u",
+        0x32 => "i64.load16_s",
+        0x33 => "i64.load16_u",
+        0x34 => "i64.load32_s",
+        0x35 => "i64.load32_u",
+        0x36 => "i32.store",
+        0x37 => "i64.store",
+        0x38 => "f32.store",
+        0x39 => "f64.store",
+        0x3A => "i32.store8",
+        0x3B => "i32.store16",
+        0x3C => "i64.store8",
+        0x3D => "i64.store16",
+        0x3E => "i64.store32",
+        0x3F => "memory.size",
+        0x40 => "memory.grow",
+        0x41 => "i32.const",
+        0x42 => "i64.const",
+        0x43 => "f32.const",
+        0x44 => "f64.const",
+        0x45..=0xC4 => NUMERIC.split_whitespace().nth(opcode as usize - 0x45)?,
+        0xD0 => "ref.null",
+        0xD1 => "ref.is_null",
+        0xD2 => "ref.func",
+        0xFC => "prefixed",
+        _ => return None,
+    })
+}
+
+// Java クラスファイル
+pub(super) fn parse_class(buf: &[u8]) -> Result<Field, String> {
+    let reader = Reader::new(buf, Endian::Big);
+    let mut header = reader.record(0);
+    if header.hex("magic", 4)? != 0xCAFE_BABE {
+        return Err(String::from("Class file magic not found"));
+    }
+    header.num("minor version", 2)?;
+    let major = reader.u16(header.offset)?;
+    let java = match major {
+        45..=48 => format!("Java 1.{}", major - 44),
+        _ => format!("Java {}", major.saturating_sub(44)),
+    };
+    header.push("major version", 2, format!("{major} ({java})"));
+    let count = header.num("constant pool count", 2)?;
+    let header = header.finish("header");
+
+    let (pool, offset) = ConstantPool::read(&reader, 10, count)?;
+    let mut fields = vec![header, pool.field(count)];
+    if let Some(e) = &pool.error {
+        fields.push(Field::new("rest", offset..buf.len(), "").with_error(e.clone()));
+        return Ok(Field::group("class file", fields));
+    }
+
+    let mut record = reader.record(offset);
+    record.push(
+        "access flags",
+        2,
+        access_flags(reader.u16(offset)?, CLASS_FLAGS),
+    );
+    pool.index(&mut record, "this class")?;
+    pool.index(&mut record, "super class")?;
+    let count = record.num("interface count", 2)?;
+    for i in 0..count {
+        pool.index(&mut record, &format!("interface {i}"))?;
+    }
+    let mut offset = record.offset;
+    fields.push(record.finish("class"));
+
+    // フィールドとメソッドは同じ形
+    for (label, flags) in [("fields", FIELD_FLAGS), ("methods", METHOD_FLAGS)] {
+        let mut record = reader.record(offset);
+        let count = record.num("count", 2)?;
+        for i in 0..count as usize {
+            let mut member = reader.record(record.offset);
+            let access = reader.u16(member.offset)?;
+            member.push("access flags", 2, access_flags(access, flags));
+            let name = pool.index(&mut member, "name")?;
+            let descriptor = pool.index(&mut member, "descriptor")?;
+            pool.attributes(&mut member)?;
+            let field = member
+                .finish(format!("[{i}] {name}"))
+                .with_value(descriptor);
+            record.offset = field.range.end;
+            record.fields.push(field);
+        }
+        offset = record.offset;
+        fields.push(record.finish(label));
+    }
+    let mut record = reader.record(offset);
+    pool.attributes(&mut record)?;
+    let end = record.offset;
+    fields.push(record.finish("attributes"));
+    if end < buf.len() {
+        let field = Field::new(
+            "trailing data",
+            end..buf.len(),
+            format!("{} bytes", buf.len() - end),
+        )
+        .with_error("data after the class file");
+        fields.push(field);
+    }
+    Ok(Field::group("class file", fields))
+}
+
+// 定数プールの1項目
+enum Constant {
+    Utf8(String),
+    Value(String),
+    // Class、String など1つの番号を持つもの
+    Index(u64),
+    // Fieldref、NameAndType など2つの番号を持つもの
+    Pair(u64, u64),
+    // Long と Double の後ろの使われない番号
+    Unused,
+}
+
+struct ConstantPool {
+    // 番号ごとの(項目、範囲、タグ名)(0番は使わない)
+    entries: Vec<Option<(Constant, Range<usize>, &'static str)>>,
+    error: Option<String>,
+}
+
+impl ConstantPool {
+    // 読めないタグがあればそこで止める
+    fn read(reader: &Reader, offset: usize, count: u64) -> Result<(Self, usize), String> {
+        let mut entries = vec![None];
+        let mut offset = offset;
+        let mut error = None;
+        while (entries.len() as u64) < count {
+            let start = offset;
+            let tag = reader.u8(offset)?;
+            offset += 1;
+            let (constant, name) = match tag {
+                1 => {
+                    let len = reader.u16(offset)? as usize;
+                    let bytes = reader.bytes(offset + 2, len)?;
+                    offset += 2 + len;
+                    (
+                        Constant::Utf8(String::from_utf8_lossy(bytes).into_owned()),
+                        "Utf8",
+                    )
+                }
+                3 => {
+                    let value = reader.u32(offset)? as i32;
+                    offset += 4;
+                    (Constant::Value(value.to_string()), "Integer")
+                }
+                4 => {
+                    let value = f32::from_bits(reader.u32(offset)? as u32);
+                    offset += 4;
+                    (Constant::Value(value.to_string()), "Float")
+                }
+                5 | 6 => {
+                    let value = reader.uint(offset, 8)?;
+                    offset += 8;
+                    match tag {
+                        5 => (Constant::Value((value as i64).to_string()), "Long"),
+                        _ => (Constant::Value(f64::from_bits(value).to_string()), "Double"),
+                    }
+                }
+                7 | 8 | 16 | 19 | 20 => {
+                    let index = reader.u16(offset)?;
+                    offset += 2;
+                    let name = match tag {
+                        7 => "Class",
+                        8 => "String",
+                        16 => "MethodType",
+                        19 => "Module",
+                        _ => "Package",
+                    };
+                    (Constant::Index(index), name)
+                }
+                9..=12 | 17 | 18 => {
+                    let first = reader.u16(offset)?;
+                    let second = reader.u16(offset + 2)?;
+                    offset += 4;
+                    let name = match tag {
+                        9 => "Fieldref",
+                        10 => "Methodref",
+                        11 => "InterfaceMethodref",
+                        12 => "NameAndType",
+                        17 => "Dynamic",
+                        _ => "InvokeDynamic",
+                    };
+                    (Constant::Pair(first, second), name)
+                }
+                15 => {
+                    let kind = reader.u8(offset)?;
+                    let index = reader.u16(offset + 1)?;
+                    offset += 3;
+                    (Constant::Pair(kind, index), "MethodHandle")
+                }
+                _ => {
+                    error = Some(format!("unknown constant tag {tag} at 0x{start:X}"));
+                    break;
+                }
+            };
+            let wide = matches!(tag, 5 | 6);
+            entries.push(Some((constant, start..offset, name)));
+            if wide {
+                entries.push(Some((Constant::Unused, offset..offset, "")));
+            }
+        }
+        Ok((Self { entries, error }, offset))
+    }
+
+    // 番号の指す内容を文字列にする
+    fn describe(&self, index: u64) -> String {
+        self.describe_nested(index, 0)
+    }
+
+    fn describe_nested(&self, index: u64, depth: usize) -> String {
+        let entry = self.entries.get(index as usize).and_then(|x| x.as_ref());
+        match (entry, depth) {
+            (_, 4..) => String::from("…"),
+            (Some((Constant::Utf8(text), _, _)), _) => text.clone(),
+            (Some((Constant::Value(text), _, _)), _) => text.clone(),
+            (Some((Constant::Index(x), _, _)), _) => self.describe_nested(*x, depth + 1),
+            (Some((Constant::Pair(a, b), _, "NameAndType")), _) => format!(
+                "{} {}",
+                self.describe_nested(*a, depth + 1),
+                self.describe_nested(*b, depth + 1)
+            ),
+            (Some((Constant::Pair(a, b), _, "MethodHandle")), _) => {
+                format!("kind {a} {}", self.describe_nested(*b, depth + 1))
+            }
+            (Some((Constant::Pair(a, b), _, "Dynamic" | "InvokeDynamic")), _) => {
+                format!("#{a} {}", self.describe_nested(*b, depth + 1))
+            }
+            (Some((Constant::Pair(a, b), _, _)), _) => format!(
+                "{}.{}",
+                self.describe_nested(*a, depth + 1),
+                self.describe_nested(*b, depth + 1)
+            ),
+            _ => format!("#{index}?"),
+        }
+    }
+
+    // 定数プールの木(参照先の項目へのリンク付き)
+    fn field(&self, count: u64) -> Field {
+        let mut children = Vec::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let Some((constant, range, name)) = entry else {
+                continue;
+            };
+            let value = match constant {
+                Constant::Unused => continue,
+                Constant::Utf8(text) => format!("{text:?}"),
+                _ => self.describe(i as u64),
+            };
+            let mut field = Field::new(format!("#{i} {name}"), range.clone(), value);
+            let target = match constant {
+                Constant::Index(x) => Some(*x),
+                Constant::Pair(_, x) if *name == "MethodHandle" => Some(*x),
+                Constant::Pair(x, _) if !name.ends_with("Dynamic") => Some(*x),
+                _ => None,
+            };
+            if let Some(target) = target.and_then(|x| self.range(x)) {
+                field = field.with_link(target.start);
+            }
+            children.push(field);
+        }
+        Field::group("constant pool", children)
+            .with_value(format!("{} entries", count.saturating_sub(1)))
+    }
+
+    fn range(&self, index: u64) -> Option<Range<usize>> {
+        let entry = self.entries.get(index as usize)?.as_ref()?;
+        Some(entry.1.clone())
+    }
+
+    // 定数プールの番号の項目(内容を表示し、定数プールへリンクする)
+    fn index(&self, record: &mut Record, name: &str) -> Result<String, String> {
+        let index = record.reader.u16(record.offset)?;
+        let text = match index {
+            0 => String::from("none"),
+            x => self.describe(x),
+        };
+        record.push(name, 2, format!("#{index} {text}"));
+        if let (Some(range), Some(field)) = (self.range(index), record.fields.last_mut()) {
+            field.link = Some(range.start);
+        }
+        Ok(text)
+    }
+
+    // 属性の並び(Code の中の属性も含む)
+    fn attributes(&self, record: &mut Record) -> Result<(), String> {
+        let reader = record.reader;
+        let count = record.num("attribute count", 2)?;
+        for i in 0..count {
+            let mut attribute = reader.record(record.offset);
+            let name = self.index(&mut attribute, "name")?;
+            let len = attribute.num("length", 4)? as usize;
+            let end = attribute.offset.saturating_add(len);
+            if end > reader.buf.len() {
+                let field = attribute
+                    .finish(format!("[{i}] {name}"))
+                    .with_error("attribute runs past the end of the file");
+                record.fields.push(field);
+                return Err(String::from("attribute runs past the end of the file"));
+            }
+            match name.as_str() {
+                "Code" => {
+                    attribute.num("max stack", 2)?;
+                    attribute.num("max locals", 2)?;
+                    let code = attribute.num("code length", 4)? as usize;
+                    attribute.raw("code", code)?;
+                    let handlers = attribute.num("exception table length", 2)?;
+                    for _ in 0..handlers {
+                        attribute.raw("exception handler", 8)?;
+                    }
+                    self.attributes(&mut attribute)?;
+                }
+                "ConstantValue" | "SourceFile" | "Signature" if len == 2 => {
+                    self.index(&mut attribute, "value")?;
+                }
+                _ if len > 0 => attribute.raw("info", len)?,
+                _ => {}
+            }
+            let mut field = attribute.finish(format!("[{i}] {name}"));
+            if field.range.end != end {
+                field.error = Some(format!("length {len} differs from the contents"));
+            }
+            field.range.end = end;
+            record.fields.push(field);
+            record.offset = end;
+        }
+        Ok(())
+    }
+}
+
+const CLASS_FLAGS: &[(u64, &str)] = &[
+    (0x0001, "public"),
+    (0x0010, "final"),
+    (0x0020, "super"),
+    (0x0200, "interface"),
+    (0x0400, "abstract"),
+    (0x1000, "synthetic"),
+    (0x2000, "annotation"),
+    (0x4000, "enum"),
+    (0x8000, "module"),
+];
+
+const FIELD_FLAGS: &[(u64, &str)] = &[
+    (0x0001, "public"),
+    (0x0002, "private"),
+    (0x0004, "protected"),
+    (0x0008, "static"),
+    (0x0010, "final"),
+    (0x0040, "volatile"),
+    (0x0080, "transient"),
+    (0x1000, "synthetic"),
+    (0x4000, "enum"),
+];
+
+const METHOD_FLAGS: &[(u64, &str)] = &[
+    (0x0001, "public"),
+    (0x0002, "private"),
+    (0x0004, "protected"),
+    (0x0008, "static"),
+    (0x0010, "final"),
+    (0x0020, "synchronized"),
+    (0x0040, "bridge"),
+    (0x0080, "varargs"),
+    (0x0100, "native"),
+    (0x0400, "abstract"),
+    (0x0800, "strict"),
+    (0x1000, "synthetic"),
+];
+
+fn access_flags(value: u64, names: &[(u64, &str)]) -> String {
+    let names: Vec<&str> = names
+        .iter()
+        .filter(|x| value & x.0 != 0)
+        .map(|x| x.1)
+        .collect();
+    format!("0x{value:04X} {}", names.join(" "))
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_wasm() {
+        let mut buf = b"\0asm\x01\0\0\0".to_vec();
+        // (i32) -> i32
+        buf.extend([0x01, 0x06, 0x01, 0x60, 0x01, 0x7F, 0x01, 0x7F]);
+        buf.extend([0x03, 0x02, 0x01, 0x00]);
+        buf.extend([0x07, 0x07, 0x01, 0x03, b'i', b'n', b'c', 0x00, 0x00]);
+        // local.get 0, i32.const 300, i32.add
+        buf.extend([
+            0x0A, 0x0A, 0x01, 0x08, 0x00, 0x20, 0x00, 0x41, 0xAC, 0x02, 0x6A, 0x0B,
+        ]);
+        let root = parse_wasm(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[1].children[3].value, "(i32) -> (i32)");
+        let function = &root.children[4].children[3];
+        assert_eq!(function.value, "function 0 inc");
+        let constant = &function.children[3];
+        assert_eq!(constant.name, "i32.const");
+        assert_eq!(constant.children[1].range, 37..39);
+        assert_eq!(constant.children[1].value, "300");
+
+        // 本体の長さやバイトが壊れていても落ちない
+        for (i, size) in (33..buf.len()).flat_map(|i| (0..=8).map(move |x| (i, x))) {
+            let mut buf = buf.clone();
+            buf[i] = 0xFF;
+            buf[32] = size;
+            parse_wasm(&buf).unwrap();
+        }
+        // 局所変数の宣言が本体を越える
+        let mut broken = buf.clone();
+        broken[33] = 0xFF;
+        assert!(parse_wasm(&broken).unwrap().error_count() > 0);
+        // 上限を超える個数
+        let mut buf = b"\0asm\x01\0\0\0\x03\x84\x80\x04\x81\x80\x04".to_vec();
+        buf.extend(vec![0; (1 << 16) + 1]);
+        let root = parse_wasm(&buf).unwrap();
+        assert!(root.children[1].children[2].error.is_some());
+    }
+
+    #[test]
+    fn test_parse_class() {
+        let mut buf = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52, 0, 5];
+        buf.extend([7, 0, 2, 1, 0, 1, b'A', 7, 0, 4, 1, 0, 16]);
+        buf.extend(b"java/lang/Object");
+        buf.extend([0, 0x21, 0, 1, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let root = parse_class(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[0].children[2].value, "52 (Java 8)");
+        let class = &root.children[2];
+        assert_eq!(class.children[1].value, "#1 A");
+        assert_eq!(class.children[1].link, Some(10));
+        assert_eq!(class.children[2].value, "#3 java/lang/Object");
+    }
+}
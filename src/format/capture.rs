@@ -0,0 +1,892 @@
+// パケットキャプチャ(pcap、pcapng)
+
+// 標準ライブラリ
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::ops::Range;
+// 親モジュール
+use super::Reader;
+// 状態管理
+use crate::message::Endian;
+// 構造の木
+use crate::structure::Field;
+
+// 一度に展開するパケット数の上限
+const MAX_PACKETS: usize = 1 << 16;
+
+const PCAPNG_SECTION: &[u8] = &[0x0A, 0x0D, 0x0D, 0x0A];
+
+pub(super) fn is_pcap(buf: &[u8]) -> bool {
+    pcap_magic(buf).is_some()
+}
+
+pub(super) fn is_pcapng(buf: &[u8]) -> bool {
+    buf.starts_with(PCAPNG_SECTION) && section_endian(buf, 0).is_some()
+}
+
+// pcap のマジックからエンディアンと、時刻の端数がナノ秒か
+fn pcap_magic(buf: &[u8]) -> Option<(Endian, bool)> {
+    match buf.get(..4)? {
+        [0xD4, 0xC3, 0xB2, 0xA1] => Some((Endian::Little, false)),
+        [0xA1, 0xB2, 0xC3, 0xD4] => Some((Endian::Big, false)),
+        [0x4D, 0x3C, 0xB2, 0xA1] => Some((Endian::Little, true)),
+        [0xA1, 0xB2, 0x3C, 0x4D] => Some((Endian::Big, true)),
+        _ => None,
+    }
+}
+
+// セクションヘッダのバイト順マジックからエンディアン
+fn section_endian(buf: &[u8], offset: usize) -> Option<Endian> {
+    match buf.get(offset + 8..offset + 12)? {
+        [0x4D, 0x3C, 0x2B, 0x1A] => Some(Endian::Little),
+        [0x1A, 0x2B, 0x3C, 0x4D] => Some(Endian::Big),
+        _ => None,
+    }
+}
+
+pub(super) fn parse_pcap(buf: &[u8]) -> Result<Field, String> {
+    let (endian, nanos) = pcap_magic(buf).ok_or_else(|| String::from("pcap magic not found"))?;
+    let reader = Reader::new(buf, endian);
+    let mut header = reader.record(0);
+    let resolution = if nanos { "nanoseconds" } else { "microseconds" };
+    header.push("magic", 4, format!("{resolution}, {endian}"));
+    header.num("major version", 2)?;
+    header.num("minor version", 2)?;
+    header.int("time zone", 4)?;
+    header.num("sigfigs", 4)?;
+    let snaplen = header.num("snaplen", 4)?;
+    let link = header.named("link type", 4, link_type)?;
+    let header = header.finish("file header");
+
+    let mut packets = Vec::new();
+    let mut offset = 24;
+    while offset < buf.len() && packets.len() < MAX_PACKETS {
+        let index = packets.len();
+        if buf.len() - offset < 16 {
+            let field = Field::new(format!("[{index}]"), offset..buf.len(), "")
+                .with_error("truncated record header");
+            packets.push(field);
+            break;
+        }
+        let seconds = reader.u32(offset)?;
+        let fraction = reader.u32(offset + 4)?;
+        let time = match nanos {
+            true => timestamp(seconds, fraction, 9),
+            false => timestamp(seconds, fraction * 1000, 6),
+        };
+        let mut record = reader.record(offset);
+        record.push("timestamp", 8, time.clone());
+        let captured = record.num("captured length", 4)?;
+        let original = record.num("original length", 4)?;
+        let data = offset + 16;
+        let end = data.saturating_add(captured as usize);
+        let packet = Packet {
+            index,
+            time,
+            data: data..end.min(buf.len()),
+            original,
+            link,
+        };
+        let mut field = packet.field(buf, record.finish("record header"));
+        if end > buf.len() {
+            field.error = Some(String::from("data runs past the end of the file"));
+        } else if captured > original || (snaplen > 0 && captured > snaplen) {
+            field.error = Some(String::from(
+                "captured length exceeds original length or snaplen",
+            ));
+        }
+        packets.push(field);
+        offset = end;
+    }
+    let count = packets.len();
+    let packets = Field::group("packets", packets).with_value(count.to_string());
+    Ok(Field::group("pcap", vec![header, packets]))
+}
+
+// pcapng のインタフェース(リンク種別と時刻の分解能)
+struct Interface {
+    link: u64,
+    resolution: u8,
+}
+
+pub(super) fn parse_pcapng(buf: &[u8]) -> Result<Field, String> {
+    let mut blocks = Vec::new();
+    let mut packets = Vec::new();
+    let mut interfaces: Vec<Interface> = Vec::new();
+    let mut endian = section_endian(buf, 0).ok_or_else(|| String::from("pcapng not found"))?;
+    let mut offset = 0;
+    while offset < buf.len() && packets.len() < MAX_PACKETS {
+        // セクションごとにエンディアンとインタフェースが変わる
+        if buf[offset..].starts_with(PCAPNG_SECTION) {
+            endian = section_endian(buf, offset).unwrap_or(endian);
+            interfaces.clear();
+        }
+        let reader = Reader::new(buf, endian);
+        let mut block = reader.record(offset);
+        let (kind, len) = match (reader.u32(offset), reader.u32(offset + 4)) {
+            (Ok(kind), Ok(len)) => (kind, len as usize),
+            _ => {
+                let field =
+                    Field::new("block", offset..buf.len(), "").with_error("truncated block header");
+                blocks.push(field);
+                break;
+            }
+        };
+        block.named("type", 4, block_type)?;
+        block.num("length", 4)?;
+        let end = offset.saturating_add(len);
+        // 種類ごとの固定部分と末尾の長さを含む最小の長さ
+        let minimum = match kind {
+            0x0A0D_0D0A => 28,
+            1 => 20,
+            2 | 6 => 32,
+            3 => 16,
+            _ => 12,
+        };
+        if len < minimum || len % 4 != 0 || end > buf.len() {
+            let field = block
+                .finish("block")
+                .with_error(format!("invalid block length {len}"));
+            blocks.push(field);
+            break;
+        }
+        let body = end - 4;
+        let name = block_type(kind).unwrap_or("unknown block");
+        let mut packet = None;
+        match kind {
+            0x0A0D_0D0A => {
+                block.hex("byte-order magic", 4)?;
+                block.num("major version", 2)?;
+                block.num("minor version", 2)?;
+                block.int("section length", 8)?;
+                let (options, _) = options(&reader, block.offset, body, section_option)?;
+                block.fields.extend(options);
+            }
+            1 => {
+                let link = block.named("link type", 2, link_type)?;
+                block.num("reserved", 2)?;
+                block.num("snaplen", 4)?;
+                let (options, values) = options(&reader, block.offset, body, interface_option)?;
+                block.fields.extend(options);
+                // if_tsresol の省略時はマイクロ秒
+                let resolution = values
+                    .iter()
+                    .find(|x| x.0 == 9)
+                    .and_then(|x| reader.u8(x.1).ok())
+                    .map_or(6, |x| x as u8);
+                interfaces.push(Interface { link, resolution });
+            }
+            // 拡張パケットと旧形式のパケット
+            2 | 6 => {
+                let id = match kind {
+                    6 => block.num("interface", 4)?,
+                    _ => {
+                        let id = block.num("interface", 2)?;
+                        block.num("drops", 2)?;
+                        id
+                    }
+                };
+                let high = reader.u32(block.offset)?;
+                let low = reader.u32(block.offset + 4)?;
+                let interface = interfaces.get(id as usize);
+                let resolution = interface.map_or(6, |x| x.resolution);
+                let time = units_to_time(high << 32 | low, resolution);
+                block.push("timestamp", 8, time.clone());
+                let captured = block.num("captured length", 4)?;
+                let original = block.num("original length", 4)?;
+                let data = block.offset;
+                let padded = (captured as usize).saturating_add(3) & !3;
+                if data.saturating_add(padded) > body {
+                    let field = block
+                        .finish(name)
+                        .with_error("captured length exceeds the block");
+                    blocks.push(field);
+                    break;
+                }
+                block.offset += padded;
+                let (options, _) = options(&reader, block.offset, body, packet_option)?;
+                block.fields.extend(options);
+                packet = Some(Packet {
+                    index: packets.len(),
+                    time,
+                    data: data..data + captured as usize,
+                    original,
+                    link: interface.map_or(0, |x| x.link),
+                });
+            }
+            // 単純パケット(時刻が無く、インタフェースは先頭のもの)
+            3 => {
+                let original = block.num("original length", 4)?;
+                let data = block.offset;
+                let captured = (body - data).min(original as usize);
+                packet = Some(Packet {
+                    index: packets.len(),
+                    time: String::new(),
+                    data: data..data + captured,
+                    original,
+                    link: interfaces.first().map_or(0, |x| x.link),
+                });
+                block.offset = body;
+            }
+            _ => {}
+        }
+        if block.offset < body {
+            block.raw("body", body - block.offset)?;
+        }
+        block.offset = body;
+        let trailing = block.num("trailing length", 4)?;
+        let mut field = block.finish(name);
+        if trailing as usize != len {
+            field.error = Some(String::from("trailing length differs"));
+        }
+        match packet {
+            Some(packet) => packets.push(packet.field(buf, field)),
+            None => blocks.push(field),
+        }
+        offset = end;
+    }
+    let count = packets.len();
+    let blocks = Field::group("blocks", blocks);
+    let packets = Field::group("packets", packets).with_value(count.to_string());
+    Ok(Field::group("pcapng", vec![blocks, packets]))
+}
+
+// オプションの項目と、コードごとの値の位置
+type Options = (Vec<Field>, Vec<(u64, usize)>);
+
+// オプション(コード、長さ、4バイト境界まで埋めた値)
+fn options(
+    reader: &Reader,
+    offset: usize,
+    end: usize,
+    names: fn(u64) -> Option<&'static str>,
+) -> Result<Options, String> {
+    let mut fields = Vec::new();
+    let mut values = Vec::new();
+    let mut offset = offset;
+    while offset + 4 <= end {
+        let code = reader.u16(offset)?;
+        let len = reader.u16(offset + 2)? as usize;
+        if code == 0 {
+            fields.push(Field::new("end of options", offset..offset + 4, ""));
+            break;
+        }
+        let padded = (len + 3) & !3;
+        if offset + 4 + padded > end {
+            let field = Field::new("option", offset..end, "").with_error("runs past the block");
+            fields.push(field);
+            break;
+        }
+        let value = reader.bytes(offset + 4, len)?;
+        let name = match code {
+            1 => "comment",
+            x => names(x).unwrap_or("option"),
+        };
+        let text = match std::str::from_utf8(value) {
+            Ok(text) if !text.chars().any(char::is_control) => format!("{text:?}"),
+            _ => value
+                .iter()
+                .map(|x| format!("{x:02X}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        fields.push(Field::new(name, offset..offset + 4 + padded, text));
+        values.push((code, offset + 4));
+        offset += 4 + padded;
+    }
+    Ok((fields, values))
+}
+
+// 1つのパケット
+struct Packet {
+    index: usize,
+    time: String,
+    data: Range<usize>,
+    original: u64,
+    link: u64,
+}
+
+impl Packet {
+    // 範囲はキャプチャしたデータで、参照先は最も内側の層のペイロード
+    fn field(self, buf: &[u8], header: Field) -> Field {
+        let mut layers = Layers::default();
+        let reader = Reader::new(&buf[..self.data.end], Endian::Big);
+        let end = self.data.end;
+        if let Err(e) = layers.link(&reader, self.data.start, end, self.link) {
+            layers
+                .fields
+                .push(Field::new("truncated", end..end, "").with_error(e));
+        }
+        let name = match self.time.is_empty() {
+            true => format!("[{}]", self.index),
+            false => format!("[{}] {}", self.index, self.time),
+        };
+        let link = link_type(self.link).unwrap_or("unknown link");
+        let value = format!(
+            "{}/{} bytes {link} {}",
+            self.data.len(),
+            self.original,
+            layers.summary
+        );
+        let mut field = Field::new(name, self.data.clone(), value.trim_end());
+        field.children = vec![header];
+        field.children.append(&mut layers.fields);
+        field.with_link(layers.payload.unwrap_or(self.data.start))
+    }
+}
+
+// 解析した層
+#[derive(Default)]
+struct Layers {
+    fields: Vec<Field>,
+    // 最も内側の層の要約
+    summary: String,
+    // IP の送信元と宛先
+    addresses: (String, String),
+    payload: Option<usize>,
+}
+
+impl Layers {
+    // リンク層
+    fn link(
+        &mut self,
+        reader: &Reader,
+        offset: usize,
+        end: usize,
+        link: u64,
+    ) -> Result<(), String> {
+        match link & 0xFFFF {
+            // BSD のループバック(ホストのバイト順のアドレスファミリ)
+            0 => {
+                let family = reader.u32(offset)? as u32;
+                let family = family.min(family.swap_bytes());
+                let mut record = reader.record(offset);
+                record.push("family", 4, family.to_string());
+                self.fields.push(record.finish("loopback"));
+                let kind = match family {
+                    2 => 0x0800,
+                    24 | 28 | 30 => 0x86DD,
+                    _ => 0,
+                };
+                self.ether(reader, offset + 4, end, kind)
+            }
+            1 => {
+                let mut record = reader.record(offset);
+                let destination = mac(reader.bytes(offset, 6)?);
+                record.push("destination", 6, destination.clone());
+                let source = mac(reader.bytes(offset + 6, 6)?);
+                record.push("source", 6, source.clone());
+                let mut kind = record.named("type", 2, ether_type)?;
+                // VLAN タグ
+                while matches!(kind, 0x8100 | 0x88A8) {
+                    record.num("VLAN tag", 2)?;
+                    kind = record.named("type", 2, ether_type)?;
+                }
+                let next = record.offset;
+                self.fields.push(record.finish("Ethernet"));
+                self.summary = format!("Ethernet {source} → {destination}");
+                self.ether(reader, next, end, kind)
+            }
+            101 | 12 | 14 | 228 | 229 => {
+                let kind = match reader.u8(offset)? >> 4 {
+                    6 => 0x86DD,
+                    _ => 0x0800,
+                };
+                self.ether(reader, offset, end, kind)
+            }
+            // Linux cooked capture
+            113 => {
+                let mut record = reader.record(offset);
+                record.num("packet type", 2)?;
+                record.num("ARPHRD type", 2)?;
+                record.num("address length", 2)?;
+                record.raw("address", 8)?;
+                let kind = record.named("protocol", 2, ether_type)?;
+                self.fields.push(record.finish("Linux cooked"));
+                self.ether(reader, offset + 16, end, kind)
+            }
+            276 => {
+                let mut record = reader.record(offset);
+                let kind = record.named("protocol", 2, ether_type)?;
+                record.num("reserved", 2)?;
+                record.num("interface", 4)?;
+                record.num("ARPHRD type", 2)?;
+                record.num("packet type", 1)?;
+                record.num("address length", 1)?;
+                record.raw("address", 8)?;
+                self.fields.push(record.finish("Linux cooked v2"));
+                self.ether(reader, offset + 20, end, kind)
+            }
+            _ => self.payload(offset, end),
+        }
+    }
+
+    // EtherType ごとのネットワーク層
+    fn ether(
+        &mut self,
+        reader: &Reader,
+        offset: usize,
+        end: usize,
+        kind: u64,
+    ) -> Result<(), String> {
+        match kind {
+            0x0800 => self.ipv4(reader, offset, end),
+            0x86DD => self.ipv6(reader, offset, end),
+            _ => self.payload(offset, end),
+        }
+    }
+
+    fn ipv4(&mut self, reader: &Reader, offset: usize, end: usize) -> Result<(), String> {
+        let mut record = reader.record(offset);
+        let ihl = (reader.u8(offset)? & 0x0F) as usize * 4;
+        record.push("version", 1, format!("4, header {ihl} bytes"));
+        record.hex("DSCP/ECN", 1)?;
+        let total = record.num("total length", 2)? as usize;
+        record.num("identification", 2)?;
+        let fragment = record.hex("flags/fragment", 2)?;
+        record.num("TTL", 1)?;
+        let protocol = record.named("protocol", 1, ip_protocol)?;
+        record.hex("checksum", 2)?;
+        let source = Ipv4Addr::from(reader.u32(record.offset)? as u32);
+        record.push("source", 4, source.to_string());
+        let destination = Ipv4Addr::from(reader.u32(record.offset)? as u32);
+        record.push("destination", 4, destination.to_string());
+        if ihl > 20 {
+            record.raw("options", ihl - 20)?;
+        }
+        let mut field = record.finish("IPv4");
+        self.addresses = (source.to_string(), destination.to_string());
+        self.summary = format!("IPv4 {source} → {destination}");
+        if ihl < 20 || total < ihl {
+            field.error = Some(format!("invalid lengths (header {ihl}, total {total})"));
+            self.fields.push(field);
+            return Ok(());
+        }
+        self.fields.push(field);
+        // Ethernet の埋め草は含めない
+        let end = (offset + total).min(end);
+        match fragment & 0x1FFF {
+            0 => self.transport(reader, offset + ihl, end, protocol),
+            _ => self.payload(offset + ihl, end),
+        }
+    }
+
+    fn ipv6(&mut self, reader: &Reader, offset: usize, end: usize) -> Result<(), String> {
+        let mut record = reader.record(offset);
+        record.hex("version/class/flow", 4)?;
+        let len = record.num("payload length", 2)? as usize;
+        let mut next = record.named("next header", 1, ip_protocol)?;
+        record.num("hop limit", 1)?;
+        let source = ipv6(reader.bytes(record.offset, 16)?);
+        record.push("source", 16, source.to_string());
+        let destination = ipv6(reader.bytes(record.offset, 16)?);
+        record.push("destination", 16, destination.to_string());
+        self.fields.push(record.finish("IPv6"));
+        self.addresses = (format!("[{source}]"), format!("[{destination}]"));
+        self.summary = format!("IPv6 {source} → {destination}");
+        let end = (offset + 40 + len).min(end);
+        let mut offset = offset + 40;
+        // 拡張ヘッダ
+        while matches!(next, 0 | 43 | 44 | 60) {
+            let mut record = reader.record(offset);
+            let following = record.named("next header", 1, ip_protocol)?;
+            let size = match next {
+                44 => {
+                    record.num("reserved", 1)?;
+                    let fragment = record.hex("fragment", 2)?;
+                    record.hex("identification", 4)?;
+                    if fragment & 0xFFF8 != 0 {
+                        self.fields.push(record.finish("fragment"));
+                        return self.payload(offset + 8, end);
+                    }
+                    8
+                }
+                _ => {
+                    let size = (record.num("length", 1)? as usize + 1) * 8;
+                    record.raw("data", size - 2)?;
+                    size
+                }
+            };
+            self.fields
+                .push(record.finish(ip_protocol(next).unwrap_or("extension")));
+            offset += size;
+            next = following;
+        }
+        self.transport(reader, offset, end, next)
+    }
+
+    // トランスポート層
+    fn transport(
+        &mut self,
+        reader: &Reader,
+        offset: usize,
+        end: usize,
+        protocol: u64,
+    ) -> Result<(), String> {
+        let mut record = reader.record(offset);
+        let (source, destination) = self.addresses.clone();
+        match protocol {
+            6 => {
+                let source_port = record.num("source port", 2)?;
+                let destination_port = record.num("destination port", 2)?;
+                record.num("sequence", 4)?;
+                record.num("acknowledgment", 4)?;
+                let flags = reader.u16(record.offset)?;
+                let header = (flags >> 12) as usize * 4;
+                let names = tcp_flags(flags);
+                record.push("data offset/flags", 2, format!("{header} bytes, {names}"));
+                record.num("window", 2)?;
+                record.hex("checksum", 2)?;
+                record.num("urgent pointer", 2)?;
+                if header > 20 {
+                    record.raw("options", header - 20)?;
+                }
+                let mut field = record.finish("TCP");
+                self.summary = format!(
+                    "TCP {source}:{source_port} → {destination}:{destination_port} [{names}]"
+                );
+                if header < 20 {
+                    field.error = Some(format!("invalid data offset {header}"));
+                    self.fields.push(field);
+                    return Ok(());
+                }
+                self.fields.push(field);
+                self.payload(offset + header, end)
+            }
+            17 => {
+                let source_port = record.num("source port", 2)?;
+                let destination_port = record.num("destination port", 2)?;
+                let len = record.num("length", 2)? as usize;
+                record.hex("checksum", 2)?;
+                self.fields.push(record.finish("UDP"));
+                self.summary =
+                    format!("UDP {source}:{source_port} → {destination}:{destination_port}");
+                self.payload(offset + 8, (offset + len.max(8)).min(end))
+            }
+            1 | 58 => {
+                let name = if protocol == 1 { "ICMP" } else { "ICMPv6" };
+                let kind = record.num("type", 1)?;
+                let code = record.num("code", 1)?;
+                record.hex("checksum", 2)?;
+                self.fields.push(record.finish(name));
+                self.summary = format!("{name} {source} → {destination} type {kind} code {code}");
+                self.payload(offset + 4, end)
+            }
+            _ => self.payload(offset, end),
+        }
+    }
+
+    // 解析しない残り
+    fn payload(&mut self, offset: usize, end: usize) -> Result<(), String> {
+        if offset < end {
+            let value = format!("{} bytes", end - offset);
+            self.fields.push(Field::new("payload", offset..end, value));
+            self.payload = Some(offset);
+        }
+        Ok(())
+    }
+}
+
+fn mac(bytes: &[u8]) -> String {
+    let text: Vec<String> = bytes.iter().map(|x| format!("{x:02x}")).collect();
+    text.join(":")
+}
+
+fn ipv6(bytes: &[u8]) -> Ipv6Addr {
+    let mut octets = [0u8; 16];
+    octets.copy_from_slice(bytes);
+    Ipv6Addr::from(octets)
+}
+
+fn tcp_flags(flags: u64) -> String {
+    const NAMES: [&str; 9] = ["FIN", "SYN", "RST", "PSH", "ACK", "URG", "ECE", "CWR", "NS"];
+    let names: Vec<&str> = NAMES
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| flags & 1 << i != 0)
+        .map(|(_, x)| *x)
+        .collect();
+    names.join(" ")
+}
+
+// pcapng の時刻(if_tsresol の最上位ビットが 1 なら 2 の累乗、0 なら 10 の累乗)
+fn units_to_time(units: u64, resolution: u8) -> String {
+    let exponent = (resolution & 0x7F) as u32;
+    let (seconds, nanos, digits) = if resolution & 0x80 != 0 {
+        let unit = 1u128 << exponent.min(127);
+        let nanos = (units as u128 % unit) * 1_000_000_000 / unit;
+        ((units as u128 / unit) as u64, nanos as u64, 9)
+    } else {
+        // u128 に収まる 10^38 までに抑える
+        let exponent = exponent.min(38);
+        let unit = 10u128.pow(exponent);
+        let fraction = units as u128 % unit;
+        let nanos = match exponent {
+            0..=9 => fraction * 10u128.pow(9 - exponent),
+            _ => fraction / 10u128.pow(exponent - 9),
+        };
+        (
+            (units as u128 / unit) as u64,
+            nanos as u64,
+            exponent.min(9) as usize,
+        )
+    };
+    timestamp(seconds, nanos, digits)
+}
+
+// UNIX 時刻を UTC の日時にする(端数は digits 桁)
+fn timestamp(seconds: u64, nanos: u64, digits: usize) -> String {
+    let days = (seconds / 86400) as i64;
+    let time = seconds % 86400;
+    // 1970-01-01 からの日数を年月日にする
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    let mut text = format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    );
+    if digits > 0 {
+        text.push('.');
+        text.push_str(&format!("{nanos:09}")[..digits]);
+    }
+    text
+}
+
+fn link_type(value: u64) -> Option<&'static str> {
+    Some(match value & 0xFFFF {
+        0 => "NULL",
+        1 => "ETHERNET",
+        101 => "RAW",
+        105 => "IEEE802_11",
+        113 => "LINUX_SLL",
+        127 => "IEEE802_11_RADIOTAP",
+        228 => "IPV4",
+        229 => "IPV6",
+        276 => "LINUX_SLL2",
+        _ => return None,
+    })
+}
+
+fn block_type(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0x0A0D_0D0A => "section header",
+        1 => "interface description",
+        2 => "packet",
+        3 => "simple packet",
+        4 => "name resolution",
+        5 => "interface statistics",
+        6 => "enhanced packet",
+        0x0A => "decryption secrets",
+        0x0BAD | 0x4000_0BAD => "custom",
+        _ => return None,
+    })
+}
+
+fn section_option(value: u64) -> Option<&'static str> {
+    Some(match value {
+        2 => "hardware",
+        3 => "OS",
+        4 => "application",
+        _ => return None,
+    })
+}
+
+fn interface_option(value: u64) -> Option<&'static str> {
+    Some(match value {
+        2 => "name",
+        3 => "description",
+        4 => "IPv4 address",
+        5 => "IPv6 address",
+        6 => "MAC address",
+        8 => "speed",
+        9 => "timestamp resolution",
+        11 => "filter",
+        12 => "OS",
+        _ => return None,
+    })
+}
+
+fn packet_option(value: u64) -> Option<&'static str> {
+    Some(match value {
+        2 => "flags",
+        3 => "hash",
+        4 => "drop count",
+        5 => "packet id",
+        _ => return None,
+    })
+}
+
+fn ether_type(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0x0800 => "IPv4",
+        0x0806 => "ARP",
+        0x8100 => "VLAN",
+        0x88A8 => "QinQ",
+        0x86DD => "IPv6",
+        _ => return None,
+    })
+}
+
+fn ip_protocol(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "hop-by-hop",
+        1 => "ICMP",
+        6 => "TCP",
+        17 => "UDP",
+        43 => "routing",
+        44 => "fragment",
+        58 => "ICMPv6",
+        60 => "destination options",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_pcap() {
+        let mut buf = vec![0xD4, 0xC3, 0xB2, 0xA1, 2, 0, 4, 0];
+        buf.extend([0; 8]);
+        buf.extend(65535u32.to_le_bytes());
+        buf.extend(1u32.to_le_bytes());
+        // Ethernet + IPv4 + UDP(5 バイトのペイロード)
+        let mut frame = vec![0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
+        frame.extend([0x88, 0x99, 0xAA, 0xBB, 0x08, 0x00]);
+        frame.extend([
+            0x45, 0, 0, 33, 0, 1, 0x40, 0, 64, 17, 0, 0, 10, 0, 0, 1, 10, 0, 0, 2,
+        ]);
+        frame.extend([0, 53, 0x13, 0xE9, 0, 13, 0, 0]);
+        frame.extend(b"hello");
+        // 最短フレームまでの埋め草
+        frame.resize(60, 0);
+        buf.extend(1_700_000_000u32.to_le_bytes());
+        buf.extend(123_456u32.to_le_bytes());
+        buf.extend((frame.len() as u32).to_le_bytes());
+        buf.extend((frame.len() as u32).to_le_bytes());
+        buf.extend(&frame);
+
+        let root = parse_pcap(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        let packet = &root.children[1].children[0];
+        assert_eq!(packet.name, "[0] 2023-11-14 22:13:20.123456");
+        assert_eq!(
+            packet.value,
+            "60/60 bytes ETHERNET UDP 10.0.0.1:53 → 10.0.0.2:5097"
+        );
+        assert_eq!(packet.range, 40..100);
+        let payload = packet.children.last().unwrap();
+        assert_eq!(payload.range, 82..87);
+        assert_eq!(packet.link, Some(82));
+    }
+
+    // pcapng のブロック(長さは前後に付ける)
+    fn block(kind: u32, body: &[u8]) -> Vec<u8> {
+        let len = (12 + body.len().next_multiple_of(4)) as u32;
+        let mut buf = kind.to_le_bytes().to_vec();
+        buf.extend(len.to_le_bytes());
+        buf.extend(body);
+        buf.resize(len as usize - 4, 0);
+        buf.extend(len.to_le_bytes());
+        buf
+    }
+
+    // 拡張パケットブロック
+    fn packet(data: &[u8]) -> Vec<u8> {
+        let mut body = vec![0; 12];
+        body.extend((data.len() as u32).to_le_bytes());
+        body.extend((data.len() as u32).to_le_bytes());
+        body.extend(data);
+        block(6, &body)
+    }
+
+    #[test]
+    fn test_parse_pcapng() {
+        let mut header = 0x1A2B_3C4Du32.to_le_bytes().to_vec();
+        header.extend([1, 0, 0, 0]);
+        header.extend([0xFF; 8]);
+        let mut buf = block(0x0A0D_0D0A, &header);
+        // 生の IP
+        buf.extend(block(1, &[101, 0, 0, 0, 0, 0, 0, 0]));
+        let start = buf.len();
+
+        // IPv6 + 中継点オプション + TCP(MSS オプション付き)+ 2 バイト
+        let mut frame = vec![0x60, 0, 0, 0, 0, 34, 0, 64];
+        frame.extend(Ipv6Addr::LOCALHOST.octets());
+        frame.extend(Ipv6Addr::LOCALHOST.octets());
+        frame.extend([6, 0, 1, 4, 0, 0, 0, 0]);
+        frame.extend([0x30, 0x39, 0, 80, 0, 0, 0, 1, 0, 0, 0, 0, 0x60, 0x18]);
+        frame.extend([0xFF, 0xFF, 0, 0, 0, 0, 2, 4, 0x05, 0xB4]);
+        frame.extend(b"hi");
+        buf.extend(packet(&frame));
+        let root = parse_pcapng(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        let packet_field = &root.children[1].children[0];
+        assert_eq!(
+            packet_field.value,
+            "74/74 bytes RAW TCP [::1]:12345 → [::1]:80 [PSH ACK]"
+        );
+        let names: Vec<&str> = packet_field
+            .children
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["enhanced packet", "IPv6", "hop-by-hop", "TCP", "payload"]
+        );
+        assert_eq!(packet_field.link, Some(start + 28 + 72));
+
+        // TCP のオプションの途中で終わる
+        let mut broken = buf[..start].to_vec();
+        broken.extend(packet(&frame[..70]));
+        let root = parse_pcapng(&broken).unwrap();
+        let layers = &root.children[1].children[0].children;
+        assert_eq!(layers.last().unwrap().name, "truncated");
+        assert_eq!(root.error_count(), 1);
+
+        // 拡張ヘッダの長さがパケットを超える
+        let mut frame = frame.clone();
+        frame[41] = 0xFF;
+        let mut broken = buf[..start].to_vec();
+        broken.extend(packet(&frame));
+        assert_eq!(parse_pcapng(&broken).unwrap().error_count(), 1);
+
+        // 固定部分に足りないブロック
+        for kind in [2, 3, 6] {
+            let mut broken = buf[..start].to_vec();
+            broken.extend(block(kind, &[]));
+            let root = parse_pcapng(&broken).unwrap();
+            let field = root.children[0].children.last().unwrap();
+            assert_eq!(field.error.as_deref(), Some("invalid block length 12"));
+        }
+
+        // 極端な時刻の分解能(10^-127 秒)
+        let mut broken = buf[..start - 20].to_vec();
+        broken.extend(block(
+            1,
+            &[101, 0, 0, 0, 0, 0, 0, 0, 9, 0, 1, 0, 0x7F, 0, 0, 0],
+        ));
+        broken.extend(packet(&frame[..40]));
+        let root = parse_pcapng(&broken).unwrap();
+        assert!(root.children[1].children[0]
+            .name
+            .starts_with("[0] 1970-01-01"));
+
+        // 取り込んだ長さがブロックを超える
+        let mut broken = buf.clone();
+        broken[start + 20] = 0xFF;
+        let root = parse_pcapng(&broken).unwrap();
+        let field = root.children[0].children.last().unwrap();
+        assert!(field.error.is_some());
+    }
+}
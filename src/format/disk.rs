@@ -0,0 +1,704 @@
+// ディスクイメージ(MBR、GPT、FAT12/16/32)
+
+// 親モジュール
+use super::{crc32, AddressMap, Reader};
+// 状態管理
+use crate::message::Endian;
+// 構造の木
+use crate::structure::Field;
+
+// 一度に展開する項目数の上限
+const MAX_ENTRIES: usize = 1 << 16;
+// サブディレクトリをたどる深さの上限
+const MAX_DEPTH: usize = 8;
+// MBR/GPT のセクタの大きさ
+const SECTOR: usize = 512;
+
+// 先頭セクタが FAT のブートセクタか
+pub(super) fn is_fat(buf: &[u8], base: usize) -> bool {
+    let Some(sector) = buf.get(base..base + SECTOR) else {
+        return false;
+    };
+    let bytes_per_sector = u16::from_le_bytes([sector[11], sector[12]]);
+    matches!(sector[0], 0xEB | 0xE9)
+        && matches!(bytes_per_sector, 512 | 1024 | 2048 | 4096)
+        && sector[13].is_power_of_two()
+        && sector[510..512] == [0x55, 0xAA]
+}
+
+// MBR(保護 MBR の場合は GPT も)と、その中の FAT ボリューム
+pub(super) fn parse_mbr(buf: &[u8]) -> Result<(Field, Vec<AddressMap>), String> {
+    if buf.get(510..512) != Some(&[0x55, 0xAA]) {
+        return Err(String::from("Boot signature 55 AA not found"));
+    }
+    let reader = Reader::new(buf, Endian::Little);
+    let mut mbr = reader.record(0);
+    mbr.raw("boot code", 446)?;
+    // パーティションの開始位置
+    let mut partitions = Vec::new();
+    let mut gpt = false;
+    for i in 0..4 {
+        let mut entry = reader.record(446 + i * 16);
+        entry.hex("status", 1)?;
+        entry.raw("first CHS", 3)?;
+        let kind = entry.named("type", 1, partition_type)?;
+        entry.raw("last CHS", 3)?;
+        let first = entry.num("first LBA", 4)?;
+        let count = entry.num("sectors", 4)?;
+        let name = partition_type(kind).unwrap_or("unknown");
+        let mut field = entry
+            .finish(format!("[{i}]"))
+            .with_value(format!("{name} {}", size_text(count * SECTOR as u64)));
+        if kind != 0 {
+            field = field.with_link(first as usize * SECTOR);
+            if kind == 0xEE {
+                gpt = true;
+            } else {
+                partitions.push(first as usize * SECTOR);
+            }
+        }
+        mbr.fields.push(field);
+    }
+    mbr.offset = 510;
+    mbr.hex("signature", 2)?;
+    let mut children = vec![mbr.finish("MBR")];
+
+    if gpt {
+        let (header, entries, found) = parse_gpt(&reader, SECTOR)?;
+        children.push(header);
+        children.extend(entries);
+        partitions.extend(found);
+    }
+
+    // FAT ボリュームを解析(クラスタ番号の対応表は最初のボリュームのもの)
+    let mut maps = Vec::new();
+    for (i, offset) in partitions.into_iter().enumerate() {
+        if is_fat(buf, offset) {
+            let (field, map) = parse_fat(buf, offset)?;
+            children.push(Field {
+                name: format!("volume {i}"),
+                ..field
+            });
+            if maps.is_empty() {
+                maps.push(map);
+            }
+        }
+    }
+    let name = if gpt { "GPT disk" } else { "MBR disk" };
+    let root = Field::group(name, children).with_value(size_text(buf.len() as u64));
+    Ok((root, maps))
+}
+
+// GPT ヘッダとパーティションエントリ
+// パーティションの開始位置も返す
+fn parse_gpt(reader: &Reader, offset: usize) -> Result<(Field, Option<Field>, Vec<usize>), String> {
+    if reader.bytes(offset, 8)? != b"EFI PART" {
+        let field =
+            Field::new("GPT header", offset..offset + 8, "").with_error("signature not found");
+        return Ok((field, None, Vec::new()));
+    }
+    let mut header = reader.record(offset);
+    header.push("signature", 8, String::from("EFI PART"));
+    header.hex("revision", 4)?;
+    let size = header.num("header size", 4)? as usize;
+    let stored = header.hex("header CRC32", 4)?;
+    header.hex("reserved", 4)?;
+    header.num("current LBA", 8)?;
+    let backup = header.num("backup LBA", 8)?;
+    header.num("first usable LBA", 8)?;
+    header.num("last usable LBA", 8)?;
+    let disk = guid(reader.bytes(header.offset, 16)?);
+    header.push("disk GUID", 16, disk);
+    let entries_lba = header.num("entries LBA", 8)?;
+    let count = header.num("entry count", 4)? as usize;
+    let entry_size = header.num("entry size", 4)? as usize;
+    // エントリは少なくとも名前の終わりまである
+    let too_small = entry_size < 128;
+    if too_small {
+        let field = header.fields.last_mut().unwrap();
+        field.error = Some(String::from("entry size is below 128"));
+    }
+    let entries_crc = header.hex("entries CRC32", 4)?;
+
+    // ヘッダの CRC は CRC 欄を 0 として計算する
+    let mut errors = Vec::new();
+    match reader.bytes(offset, size) {
+        Ok(bytes) if size >= 92 => {
+            let mut bytes = bytes.to_vec();
+            bytes[16..20].fill(0);
+            let actual = crc32(&bytes) as u64;
+            if actual != stored {
+                errors.push(format!("bad header CRC (computed 0x{actual:08X})"));
+            }
+        }
+        _ => errors.push(String::from("invalid header size")),
+    }
+    let mut header = header
+        .finish("GPT header")
+        .with_value(format!("{count} entries, backup at LBA {backup}"));
+    let Some(start) = lba_offset(entries_lba) else {
+        errors.push(String::from("entries LBA out of range"));
+        return Ok((header.with_error(errors.join(", ")), None, Vec::new()));
+    };
+    header = header.with_link(start);
+    if too_small {
+        return Ok((header, None, Vec::new()));
+    }
+
+    let table_len = count.saturating_mul(entry_size);
+    let Ok(table) = reader.bytes(start, table_len) else {
+        errors.push(String::from("partition entries exceed file"));
+        return Ok((header.with_error(errors.join(", ")), None, Vec::new()));
+    };
+    let actual = crc32(table) as u64;
+    if actual != entries_crc {
+        errors.push(format!("bad entries CRC (computed 0x{actual:08X})"));
+    }
+    if !errors.is_empty() {
+        header = header.with_error(errors.join(", "));
+    }
+
+    let mut fields = Vec::new();
+    let mut found = Vec::new();
+    for i in 0..count.min(MAX_ENTRIES) {
+        let offset = start + i * entry_size;
+        // 種類の GUID が 0 のものは未使用
+        let kind = reader.bytes(offset, 16)?;
+        if kind.iter().all(|&x| x == 0) {
+            continue;
+        }
+        let kind = guid(kind);
+        let mut entry = reader.record(offset);
+        entry.push("type GUID", 16, kind.clone());
+        let unique = guid(reader.bytes(entry.offset, 16)?);
+        entry.push("unique GUID", 16, unique);
+        let first = entry.num("first LBA", 8)?;
+        let last = entry.num("last LBA", 8)?;
+        entry.hex("attributes", 8)?;
+        let units: Vec<u16> = reader
+            .bytes(entry.offset, 72)?
+            .chunks_exact(2)
+            .map(|x| u16::from_le_bytes([x[0], x[1]]))
+            .take_while(|&x| x != 0)
+            .collect();
+        let name = String::from_utf16_lossy(&units);
+        entry.push("name", 72, format!("{name:?}"));
+        let sectors = last.saturating_add(1).saturating_sub(first);
+        let type_name = gpt_type(&kind).unwrap_or("unknown");
+        let field = entry.finish(format!("[{i}] {name}"));
+        let (Some(start), Some(size)) = (lba_offset(first), sectors.checked_mul(SECTOR as u64))
+        else {
+            fields.push(field.with_error("LBA out of range"));
+            continue;
+        };
+        let field = field
+            .with_value(format!("{type_name} {}", size_text(size)))
+            .with_link(start);
+        fields.push(field);
+        found.push(start);
+    }
+    let entries = Field::group("GPT entries", fields).with_value(format!("{}", found.len()));
+    Ok((header, Some(entries), found))
+}
+
+// LBA からファイル内の位置(桁あふれは None)
+fn lba_offset(lba: u64) -> Option<usize> {
+    usize::try_from(lba.checked_mul(SECTOR as u64)?).ok()
+}
+
+// FAT12/16/32 のボリューム
+// base はボリュームの先頭
+pub(super) fn parse_fat(buf: &[u8], base: usize) -> Result<(Field, AddressMap), String> {
+    if !is_fat(buf, base) {
+        return Err(String::from("FAT boot sector not found"));
+    }
+    let reader = Reader::new(buf, Endian::Little);
+    let mut boot = reader.record(base);
+    boot.raw("jump", 3)?;
+    let oem = String::from_utf8_lossy(reader.bytes(boot.offset, 8)?).into_owned();
+    boot.push("OEM name", 8, format!("{oem:?}"));
+    let bytes_per_sector = boot.num("bytes per sector", 2)? as usize;
+    let sectors_per_cluster = boot.num("sectors per cluster", 1)? as usize;
+    let reserved = boot.num("reserved sectors", 2)? as usize;
+    let fat_count = boot.num("FAT count", 1)? as usize;
+    let root_entries = boot.num("root entries", 2)? as usize;
+    let total16 = boot.num("total sectors (16)", 2)?;
+    boot.hex("media", 1)?;
+    let fat16_size = boot.num("sectors per FAT (16)", 2)? as usize;
+    boot.num("sectors per track", 2)?;
+    boot.num("heads", 2)?;
+    boot.num("hidden sectors", 4)?;
+    let total32 = boot.num("total sectors (32)", 4)?;
+    let mut root_cluster = 0;
+    let fat_size = if fat16_size == 0 {
+        let size = boot.num("sectors per FAT (32)", 4)? as usize;
+        boot.hex("extended flags", 2)?;
+        boot.hex("version", 2)?;
+        root_cluster = boot.num("root cluster", 4)?;
+        boot.num("FSInfo sector", 2)?;
+        boot.num("backup boot sector", 2)?;
+        boot.raw("reserved", 12)?;
+        size
+    } else {
+        fat16_size
+    };
+    boot.hex("drive number", 1)?;
+    boot.hex("reserved", 1)?;
+    boot.hex("boot signature", 1)?;
+    boot.hex("volume ID", 4)?;
+    let label = String::from_utf8_lossy(reader.bytes(boot.offset, 11)?).into_owned();
+    boot.push("volume label", 11, format!("{:?}", label.trim_end()));
+    let kind = String::from_utf8_lossy(reader.bytes(boot.offset, 8)?).into_owned();
+    boot.push("file system type", 8, format!("{:?}", kind.trim_end()));
+    boot.offset = base + 510;
+    boot.hex("signature", 2)?;
+
+    // 各領域の位置
+    let total = if total16 != 0 { total16 } else { total32 } as usize;
+    let cluster_size = bytes_per_sector * sectors_per_cluster;
+    let fat_start = base + reserved * bytes_per_sector;
+    let fat_len = fat_size * bytes_per_sector;
+    let root_start = fat_start + fat_count * fat_len;
+    let root_len = root_entries * 32;
+    let data_start = root_start + root_len.div_ceil(bytes_per_sector) * bytes_per_sector;
+    let data_sectors = total.saturating_sub((data_start - base) / bytes_per_sector);
+    let cluster_count = data_sectors / sectors_per_cluster.max(1);
+    // FAT の種類はクラスタ数で決まる
+    let bits = match cluster_count {
+        0..4085 => 12,
+        4085..65525 => 16,
+        _ => 32,
+    };
+    let volume = Volume {
+        reader: &reader,
+        bits,
+        fat_start,
+        data_start,
+        cluster_size,
+        cluster_count,
+    };
+    let mut children = vec![boot.finish("boot sector").with_value(format!("FAT{bits}"))];
+
+    // FAT(2つ目以降は1つ目と同じか確かめる)
+    for i in 0..fat_count.min(4) {
+        let start = fat_start + i * fat_len;
+        let end = (start + fat_len).min(buf.len());
+        let mut field = if i == 0 {
+            let count = (cluster_count + 2).min(fat_len * 8 / bits).min(MAX_ENTRIES);
+            let entries = (0..count)
+                .filter_map(|n| volume.fat_field(n))
+                .collect::<Vec<_>>();
+            Field::group(format!("FAT {}", i + 1), entries)
+        } else {
+            Field::new(format!("FAT {}", i + 1), start..end, "")
+        };
+        field.value = format!("{} entries", fat_len * 8 / bits);
+        if end < start + fat_len {
+            field = field.with_error("exceeds file");
+        } else if i > 0 && buf[fat_start..fat_start + fat_len] != buf[start..end] {
+            field = field.with_error("differs from FAT 1");
+        }
+        children.push(field);
+    }
+
+    // ルートディレクトリ(FAT32 ではクラスタチェーン)
+    let root = if bits == 32 {
+        volume.directory(&volume.chain(root_cluster), 0)
+    } else {
+        let end = (root_start + root_len).min(buf.len());
+        volume.entries(root_start..end, 0)
+    };
+    let count = root.len();
+    let root = Field::group("root directory", root).with_value(format!("{count} entries"));
+    children.push(root.with_link(if bits == 32 {
+        volume.cluster_offset(root_cluster)
+    } else {
+        root_start
+    }));
+
+    let mut map = AddressMap::new("cluster");
+    map.add_units(2, cluster_count as u64, data_start, cluster_size as u64);
+    let value = format!("FAT{bits} {label:?}", label = label.trim_end());
+    Ok((Field::group("FAT", children).with_value(value), map))
+}
+
+// FAT ボリュームの解析に使う値
+struct Volume<'r, 'a> {
+    reader: &'r Reader<'a>,
+    bits: usize,
+    fat_start: usize,
+    data_start: usize,
+    cluster_size: usize,
+    cluster_count: usize,
+}
+
+impl Volume<'_, '_> {
+    // FAT のエントリの位置と値
+    fn fat_entry(&self, n: usize) -> Option<(std::ops::Range<usize>, u64)> {
+        let offset = self.fat_start + n * self.bits / 8;
+        match self.bits {
+            12 => {
+                let value = self.reader.u16(offset).ok()?;
+                let value = if n % 2 == 1 {
+                    value >> 4
+                } else {
+                    value & 0xFFF
+                };
+                Some((offset..offset + 2, value))
+            }
+            16 => Some((offset..offset + 2, self.reader.u16(offset).ok()?)),
+            _ => Some((
+                offset..offset + 4,
+                self.reader.u32(offset).ok()? & 0x0FFF_FFFF,
+            )),
+        }
+    }
+
+    // FAT のエントリの項目(空きは省く)
+    fn fat_field(&self, n: usize) -> Option<Field> {
+        let (range, value) = self.fat_entry(n)?;
+        let end = (1u64 << self.bits.min(28)) - 8;
+        let text = match value {
+            0 => return None,
+            _ if n < 2 => format!("0x{value:X}"),
+            _ if value >= end => String::from("end of chain"),
+            _ if value == end - 1 => String::from("bad cluster"),
+            _ => format!("→ {value}"),
+        };
+        let field = Field::new(format!("[{n}]"), range, text);
+        Some(if n >= 2 {
+            field.with_link(self.cluster_offset(n as u64))
+        } else {
+            field
+        })
+    }
+
+    // クラスタの先頭位置
+    fn cluster_offset(&self, cluster: u64) -> usize {
+        self.data_start + (cluster as usize).saturating_sub(2) * self.cluster_size
+    }
+
+    // クラスタチェーン(ループと範囲外で打ち切る)
+    fn chain(&self, start: u64) -> Vec<u64> {
+        let mut clusters = Vec::new();
+        let mut cluster = start;
+        while (2..self.cluster_count as u64 + 2).contains(&cluster)
+            && clusters.len() < self.cluster_count
+            && !clusters.contains(&cluster)
+        {
+            clusters.push(cluster);
+            cluster = match self.fat_entry(cluster as usize) {
+                Some((_, next)) => next,
+                None => break,
+            };
+        }
+        clusters
+    }
+
+    // クラスタチェーン上のディレクトリ
+    fn directory(&self, clusters: &[u64], depth: usize) -> Vec<Field> {
+        let len = self.reader.buf.len();
+        let mut fields = Vec::new();
+        for &cluster in clusters {
+            let start = self.cluster_offset(cluster);
+            let end = (start + self.cluster_size).min(len);
+            if start >= end {
+                break;
+            }
+            let entries = self.entries(start..end, depth);
+            let done = entries.len() < (end - start) / 32;
+            fields.extend(entries);
+            if done {
+                break;
+            }
+        }
+        fields
+    }
+
+    // ディレクトリエントリの並び(名前の 0 で終わる)
+    fn entries(&self, range: std::ops::Range<usize>, depth: usize) -> Vec<Field> {
+        let buf = self.reader.buf;
+        let mut fields = Vec::new();
+        // 長いファイル名の断片(後ろの断片から並ぶ)
+        let mut long_name: Vec<u16> = Vec::new();
+        let mut offset = range.start;
+        while offset + 32 <= range.end && fields.len() < MAX_ENTRIES {
+            let entry = &buf[offset..offset + 32];
+            if entry[0] == 0 {
+                break;
+            }
+            let attributes = entry[11];
+            if attributes == 0x0F {
+                // 長いファイル名のエントリ
+                let units: Vec<u16> = [1..11, 14..26, 28..32]
+                    .into_iter()
+                    .flat_map(|r| {
+                        entry[r]
+                            .chunks_exact(2)
+                            .map(|x| u16::from_le_bytes([x[0], x[1]]))
+                    })
+                    .take_while(|&x| x != 0 && x != 0xFFFF)
+                    .collect();
+                long_name.splice(0..0, units);
+                offset += 32;
+                continue;
+            }
+            let short = short_name(&entry[..11]);
+            let name = if long_name.is_empty() {
+                short.clone()
+            } else {
+                String::from_utf16_lossy(&long_name)
+            };
+            long_name.clear();
+            fields.push(self.entry_field(offset, name, &short, depth));
+            offset += 32;
+        }
+        fields
+    }
+
+    // ディレクトリエントリ1つ(サブディレクトリは中身も)
+    fn entry_field(&self, offset: usize, name: String, short: &str, depth: usize) -> Field {
+        let reader = self.reader;
+        let mut record = reader.record(offset);
+        record.push("name", 11, format!("{short:?}"));
+        let result: Result<(u64, u64, u64), String> = (|| {
+            let attributes = record.hex("attributes", 1)?;
+            record.hex("reserved", 1)?;
+            record.num("create time (10ms)", 1)?;
+            record.hex("create time", 2)?;
+            record.hex("create date", 2)?;
+            record.hex("access date", 2)?;
+            let high = record.num("cluster (high)", 2)?;
+            record.hex("modify time", 2)?;
+            record.hex("modify date", 2)?;
+            let low = record.num("cluster (low)", 2)?;
+            let size = record.num("size", 4)?;
+            Ok((attributes, high << 16 | low, size))
+        })();
+        let Ok((attributes, cluster, size)) = result else {
+            return record.finish(name).with_error("truncated entry");
+        };
+        let deleted = reader.buf[offset] == 0xE5;
+        let directory = attributes & 0x10 != 0;
+        let value = match (deleted, directory, attributes & 0x08 != 0) {
+            (true, ..) => String::from("deleted"),
+            (_, _, true) => String::from("volume label"),
+            (_, true, _) => String::from("directory"),
+            _ => size_text(size),
+        };
+        let mut contents = Vec::new();
+        if directory && !deleted && depth < MAX_DEPTH && short != "." && short != ".." {
+            contents = self.directory(&self.chain(cluster), depth + 1);
+        }
+        record.fields.extend(contents);
+        let field = record.finish(name).with_value(value);
+        if cluster >= 2 && (cluster as usize) < self.cluster_count + 2 {
+            field.with_link(self.cluster_offset(cluster))
+        } else {
+            field
+        }
+    }
+}
+
+// 8.3 形式の名前
+fn short_name(bytes: &[u8]) -> String {
+    let text = |x: &[u8]| String::from_utf8_lossy(x).trim_end().to_string();
+    let name = text(&bytes[..8]);
+    let extension = text(&bytes[8..11]);
+    let name = if bytes[0] == 0x05 {
+        format!("\u{E5}{}", &name[1..])
+    } else {
+        name
+    };
+    if extension.is_empty() {
+        name
+    } else {
+        format!("{name}.{extension}")
+    }
+}
+
+// GUID(先頭3つはリトルエンディアン)
+fn guid(bytes: &[u8]) -> String {
+    format!(
+        "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{}",
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        u16::from_le_bytes([bytes[4], bytes[5]]),
+        u16::from_le_bytes([bytes[6], bytes[7]]),
+        bytes[8],
+        bytes[9],
+        bytes[10..16]
+            .iter()
+            .map(|x| format!("{x:02X}"))
+            .collect::<String>()
+    )
+}
+
+// バイト数を読みやすい単位で
+fn size_text(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if size < 1024 {
+        return format!("{size} bytes");
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+fn partition_type(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0x00 => "empty",
+        0x01 => "FAT12",
+        0x04 | 0x06 | 0x0E => "FAT16",
+        0x05 | 0x0F => "extended",
+        0x07 => "NTFS/exFAT",
+        0x0B | 0x0C => "FAT32",
+        0x82 => "Linux swap",
+        0x83 => "Linux",
+        0x8E => "Linux LVM",
+        0xA5 => "FreeBSD",
+        0xAF => "HFS+",
+        0xEE => "GPT protective",
+        0xEF => "EFI system",
+        0xFD => "Linux RAID",
+        _ => return None,
+    })
+}
+
+fn gpt_type(guid: &str) -> Option<&'static str> {
+    Some(match guid {
+        "C12A7328-F81F-11D2-BA4B-00A0C93EC93B" => "EFI system",
+        "21686148-6449-6E6F-744E-656564454649" => "BIOS boot",
+        "E3C9E316-0B5C-4DB8-817D-F92DF00215AE" => "Microsoft reserved",
+        "EBD0A0A2-B9E5-4433-87C0-68B6B72699C7" => "basic data",
+        "DE94BBA4-06D1-4D40-A16A-BFD50179D6AC" => "Windows recovery",
+        "0FC63DAF-8483-4772-8E79-3D69D8477DE4" => "Linux filesystem",
+        "0657FD6D-A4AB-43C4-84E5-0933C84B4F4F" => "Linux swap",
+        "E6D6D379-F507-44C2-A23C-238F2A3DF928" => "Linux LVM",
+        "A19D880F-05FC-4D3B-A006-743F0F84911E" => "Linux RAID",
+        "4F68BCE3-E8CD-4DB1-96E7-FBCAF984B709" => "Linux root (x86-64)",
+        "48465300-0000-11AA-AA11-00306543ECAC" => "Apple HFS+",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // MBR の後ろにファイル1つだけの FAT12 ボリューム
+    fn sample() -> Vec<u8> {
+        let mut buf = vec![0u8; SECTOR * 64];
+        buf[446 + 4] = 0x01;
+        buf[446 + 8..446 + 12].copy_from_slice(&1u32.to_le_bytes());
+        buf[446 + 12..446 + 16].copy_from_slice(&63u32.to_le_bytes());
+        buf[510..512].copy_from_slice(&[0x55, 0xAA]);
+        // ブートセクタ(予約1、FAT 1つ×1セクタ、ルート 16 エントリ)
+        let volume = &mut buf[SECTOR..];
+        volume[..3].copy_from_slice(&[0xEB, 0x3C, 0x90]);
+        volume[11..13].copy_from_slice(&512u16.to_le_bytes());
+        volume[13] = 1;
+        volume[14] = 1;
+        volume[16] = 1;
+        volume[17] = 16;
+        volume[19..21].copy_from_slice(&63u16.to_le_bytes());
+        volume[22] = 1;
+        volume[510..512].copy_from_slice(&[0x55, 0xAA]);
+        // FAT: クラスタ 2 は終端
+        volume[SECTOR..SECTOR + 4].copy_from_slice(&[0xF0, 0xFF, 0xFF, 0x0F]);
+        // ルートディレクトリとデータ
+        let root = &mut volume[SECTOR * 2..];
+        root[..11].copy_from_slice(b"HELLO   TXT");
+        root[26] = 2;
+        root[28] = 5;
+        volume[SECTOR * 3..SECTOR * 3 + 5].copy_from_slice(b"hello");
+        buf
+    }
+
+    #[test]
+    fn test_parse_mbr_and_fat() {
+        let buf = sample();
+        let (root, maps) = parse_mbr(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[0].children[1].link, Some(SECTOR));
+        let volume = &root.children[1];
+        assert_eq!(volume.name, "volume 0");
+        let directory = volume.children.last().unwrap();
+        let file = &directory.children[0];
+        assert_eq!(file.name, "HELLO.TXT");
+        assert_eq!(file.link, Some(SECTOR * 4));
+        assert_eq!(maps[0].to_offset(2), Some(SECTOR * 4));
+    }
+
+    // 保護 MBR と、エントリ1つの GPT
+    // 書き換えた後に CRC を計算し直すかは呼び出し側で選ぶ
+    fn gpt(edit: impl Fn(&mut [u8]), fix_crc: bool) -> Vec<u8> {
+        let mut buf = vec![0u8; SECTOR * 8];
+        buf[446 + 4] = 0xEE;
+        buf[446 + 8] = 1;
+        buf[510..512].copy_from_slice(&[0x55, 0xAA]);
+        let header = &mut buf[SECTOR..];
+        header[..8].copy_from_slice(b"EFI PART");
+        header[12] = 92;
+        header[72] = 2;
+        header[80] = 4;
+        header[84] = 128;
+        // 基本データパーティション
+        let entry = &mut buf[SECTOR * 2..];
+        entry[..16].copy_from_slice(&[
+            0xA2, 0xA0, 0xD0, 0xEB, 0xE5, 0xB9, 0x33, 0x44, 0x87, 0xC0, 0x68, 0xB6, 0xB7, 0x26,
+            0x99, 0xC7,
+        ]);
+        entry[32] = 4;
+        entry[40] = 7;
+        let crcs = |buf: &mut [u8]| {
+            let crc = crc32(&buf[SECTOR * 2..SECTOR * 2 + 512]);
+            buf[SECTOR + 88..SECTOR + 92].copy_from_slice(&crc.to_le_bytes());
+            buf[SECTOR + 16..SECTOR + 20].fill(0);
+            let crc = crc32(&buf[SECTOR..SECTOR + 92]);
+            buf[SECTOR + 16..SECTOR + 20].copy_from_slice(&crc.to_le_bytes());
+        };
+        crcs(&mut buf);
+        edit(&mut buf);
+        if fix_crc {
+            crcs(&mut buf);
+        }
+        buf
+    }
+
+    #[test]
+    fn test_parse_gpt() {
+        let (root, _) = parse_mbr(&gpt(|_| {}, false)).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[1].link, Some(SECTOR * 2));
+        let entry = &root.children[2].children[0];
+        assert_eq!(entry.link, Some(SECTOR * 4));
+        assert!(entry.value.ends_with("2.0 KiB"), "{}", entry.value);
+
+        let error = |buf: Vec<u8>| {
+            let (root, _) = parse_mbr(&buf).unwrap();
+            let header = &root.children[1];
+            header.error.clone().unwrap_or_default()
+        };
+        // ヘッダの CRC
+        let buf = gpt(|buf| buf[SECTOR + 32] = 9, false);
+        assert!(error(buf).contains("bad header CRC"));
+        // エントリの CRC
+        let buf = gpt(|buf| buf[SECTOR * 2 + 56] = b'A', false);
+        assert!(error(buf).contains("bad entries CRC"));
+        // 桁あふれする LBA
+        let buf = gpt(|buf| buf[SECTOR + 72..SECTOR + 80].fill(0xFF), true);
+        assert!(error(buf).contains("entries LBA out of range"));
+        let buf = gpt(|buf| buf[SECTOR * 2 + 32..SECTOR * 2 + 48].fill(0xFF), true);
+        let (root, _) = parse_mbr(&buf).unwrap();
+        let entry = &root.children[2].children[0];
+        assert_eq!(entry.error.as_deref(), Some("LBA out of range"));
+        // 小さすぎるエントリ
+        let (root, _) = parse_mbr(&gpt(|buf| buf[SECTOR + 84] = 16, true)).unwrap();
+        assert!(root.error_count() > 0);
+        assert_eq!(root.children.len(), 2);
+    }
+}
@@ -0,0 +1,635 @@
+// ELF(32/64ビット、リトルエンディアン/ビッグエンディアン)
+
+// 親モジュール
+use super::{number, Reader};
+// 状態管理
+use crate::message::Endian;
+// 構造の木
+use crate::structure::Field;
+
+// セクションの種類
+const SHT_SYMTAB: u64 = 2;
+const SHT_NOBITS: u64 = 8;
+const SHT_DYNSYM: u64 = 11;
+const SHT_DYNAMIC: u64 = 6;
+
+// 一度に展開する項目数の上限
+const MAX_ENTRIES: usize = 1 << 16;
+
+// 解析に使うセクションヘッダの値
+struct Section {
+    name_index: u64,
+    name: String,
+    kind: u64,
+    addr: u64,
+    offset: u64,
+    size: u64,
+    link: u64,
+    entsize: u64,
+}
+
+impl Section {
+    // ファイル上の内容の範囲
+    fn contents(&self) -> Option<std::ops::Range<usize>> {
+        let start = usize::try_from(self.offset).ok()?;
+        let len = usize::try_from(self.size).ok()?;
+        (self.kind != SHT_NOBITS).then_some(start..start.checked_add(len)?)
+    }
+}
+
+pub(super) fn parse(buf: &[u8]) -> Result<Field, String> {
+    if !buf.starts_with(b"\x7fELF") {
+        return Err(String::from("Not an ELF file"));
+    }
+    let wide = match buf.get(4) {
+        Some(1) => false,
+        Some(2) => true,
+        _ => return Err(String::from("Unknown ELF class")),
+    };
+    let endian = match buf.get(5) {
+        Some(1) => Endian::Little,
+        Some(2) => Endian::Big,
+        _ => return Err(String::from("Unknown ELF data encoding")),
+    };
+    let reader = Reader::new(buf, endian);
+    // アドレスとオフセットの大きさ
+    let word = if wide { 8 } else { 4 };
+
+    // ELFヘッダ
+    let mut header = reader.record(0);
+    header.push("magic", 4, String::from("7F 'ELF'"));
+    header.named("class", 1, |x| match x {
+        1 => Some("ELF32"),
+        2 => Some("ELF64"),
+        _ => None,
+    })?;
+    header.named("data", 1, |x| match x {
+        1 => Some("LSB"),
+        2 => Some("MSB"),
+        _ => None,
+    })?;
+    header.num("version", 1)?;
+    header.named("osabi", 1, osabi_name)?;
+    header.num("abiversion", 1)?;
+    header.push("pad", 7, String::new());
+    header.named("type", 2, type_name)?;
+    header.named("machine", 2, machine_name)?;
+    header.num("version", 4)?;
+    header.hex("entry", word)?;
+    let phoff = header.num("phoff", word)?;
+    let shoff = header.num("shoff", word)?;
+    header.hex("flags", 4)?;
+    header.num("ehsize", 2)?;
+    let phentsize = header.num("phentsize", 2)?;
+    let phnum = header.num("phnum", 2)?;
+    let shentsize = header.num("shentsize", 2)?;
+    let shnum = header.num("shnum", 2)?;
+    let shstrndx = header.num("shstrndx", 2)?;
+    // ファイルに収まらない表はオフセットの項目に印を付ける
+    for (name, offset, entsize, count, error) in [
+        (
+            "phoff",
+            phoff,
+            phentsize,
+            phnum,
+            "program headers exceed file",
+        ),
+        (
+            "shoff",
+            shoff,
+            shentsize,
+            shnum,
+            "section headers exceed file",
+        ),
+    ] {
+        let fits = count == 0 || table_entry(offset, entsize, count).is_ok_and(|x| x <= buf.len());
+        if let Some(field) = header
+            .fields
+            .iter_mut()
+            .find(|x| x.name == name)
+            .filter(|_| !fits)
+        {
+            field.error = Some(String::from(error));
+        }
+    }
+    let mut children = vec![header
+        .finish("header")
+        .with_value(format!("ELF{} {endian}", if wide { 64 } else { 32 }))];
+
+    // プログラムヘッダ(途中で切れている場合は読めた所までを残す)
+    let mut segments = Vec::new();
+    for i in 0..phnum.min(MAX_ENTRIES as u64) {
+        let Ok(offset) = table_entry(phoff, phentsize, i) else {
+            break;
+        };
+        let mut entry = reader.record(offset);
+        let (kind, flags, file_offset, filesz) = match segment(&mut entry, wide) {
+            Ok(x) => x,
+            Err(e) => {
+                segments.extend(truncated(entry, i, e));
+                break;
+            }
+        };
+        let name = segment_name(kind).map_or_else(|| format!("0x{kind:X}"), String::from);
+        let mut field = entry
+            .finish(format!("[{i}] {name}"))
+            .with_value(format!("{} {filesz} bytes", flag_text(flags)));
+        if filesz > 0 {
+            field = field.with_link(file_offset as usize);
+        }
+        segments.push(field);
+    }
+    if !segments.is_empty() {
+        children.push(Field::group("program headers", segments).with_value(format!("{phnum}")));
+    }
+
+    // セクションヘッダ(名前は後で文字列テーブルから引く)
+    let mut sections = Vec::new();
+    let mut fields = Vec::new();
+    let mut rest = None;
+    for i in 0..shnum.min(MAX_ENTRIES as u64) {
+        let Ok(offset) = table_entry(shoff, shentsize, i) else {
+            break;
+        };
+        let mut entry = reader.record(offset);
+        match section(&mut entry, wide) {
+            Ok(section) => {
+                sections.push(section);
+                fields.push(entry);
+            }
+            Err(e) => {
+                rest = truncated(entry, i, e);
+                break;
+            }
+        }
+    }
+    // セクション名の解決
+    let names: Vec<String> = {
+        let strtab = sections.get(shstrndx as usize).and_then(Section::contents);
+        sections
+            .iter()
+            .map(|x| {
+                strtab
+                    .as_ref()
+                    .and_then(|range| string_at(&reader, range, x.name_index as usize))
+                    .unwrap_or_default()
+            })
+            .collect()
+    };
+    for (section, name) in sections.iter_mut().zip(names) {
+        section.name = name;
+    }
+    if !fields.is_empty() || rest.is_some() {
+        let mut entries: Vec<Field> = fields
+            .into_iter()
+            .zip(&sections)
+            .enumerate()
+            .map(|(i, (entry, section))| {
+                let kind = section_name(section.kind)
+                    .map_or_else(|| format!("0x{:X}", section.kind), String::from);
+                let field = entry
+                    .finish(format!("[{i}] {}", section.name))
+                    .with_value(format!("{kind} {} bytes", section.size));
+                match section.contents() {
+                    Some(range) if !range.is_empty() => field.with_link(range.start),
+                    _ => field,
+                }
+            })
+            .collect();
+        entries.extend(rest);
+        children.push(Field::group("sections", entries).with_value(format!("{shnum}")));
+    }
+
+    // シンボルテーブルと動的セクション
+    for section in &sections {
+        let field = match section.kind {
+            SHT_SYMTAB | SHT_DYNSYM => symbols(&reader, section, &sections, wide),
+            SHT_DYNAMIC => dynamic(&reader, section, &sections, wide),
+            _ => continue,
+        };
+        children.extend(field);
+    }
+
+    Ok(Field::group("ELF", children).with_value(format!("{} bytes", buf.len())))
+}
+
+// プログラムヘッダの項目(種類、権限、オフセット、ファイル上の大きさ)
+fn segment(entry: &mut super::Record, wide: bool) -> Result<(u64, u64, u64, u64), String> {
+    let word = if wide { 8 } else { 4 };
+    let kind = entry.named("type", 4, segment_name)?;
+    let flags = if wide { entry.hex("flags", 4)? } else { 0 };
+    let file_offset = entry.hex("offset", word)?;
+    entry.hex("vaddr", word)?;
+    entry.hex("paddr", word)?;
+    let filesz = entry.num("filesz", word)?;
+    entry.num("memsz", word)?;
+    let flags = if wide { flags } else { entry.hex("flags", 4)? };
+    entry.num("align", word)?;
+    Ok((kind, flags, file_offset, filesz))
+}
+
+// セクションヘッダの項目
+fn section(entry: &mut super::Record, wide: bool) -> Result<Section, String> {
+    let word = if wide { 8 } else { 4 };
+    let name = entry.num("name", 4)?;
+    let kind = entry.named("type", 4, section_name)?;
+    entry.hex("flags", word)?;
+    let addr = entry.hex("addr", word)?;
+    let file_offset = entry.hex("offset", word)?;
+    let size = entry.num("size", word)?;
+    let link = entry.num("link", 4)?;
+    entry.num("info", 4)?;
+    entry.num("addralign", word)?;
+    let entsize = entry.num("entsize", word)?;
+    Ok(Section {
+        name_index: name,
+        name: String::new(),
+        kind,
+        addr,
+        offset: file_offset,
+        size,
+        link,
+        entsize,
+    })
+}
+
+// 途中で切れた表の項目(読めた所までを残す)
+fn truncated(entry: super::Record, i: u64, error: String) -> Option<Field> {
+    (!entry.fields.is_empty()).then(|| entry.finish(format!("[{i}]")).with_error(error))
+}
+
+// 表の i 番目の位置
+fn table_entry(offset: u64, entsize: u64, i: u64) -> Result<usize, String> {
+    offset
+        .checked_add(entsize.saturating_mul(i))
+        .and_then(|x| usize::try_from(x).ok())
+        .ok_or_else(|| String::from("Table offset out of range"))
+}
+
+// 文字列テーブル内の文字列
+fn string_at(reader: &Reader, range: &std::ops::Range<usize>, index: usize) -> Option<String> {
+    let offset = range.start.checked_add(index)?;
+    (offset < range.end).then(|| reader.c_str(offset))?
+}
+
+// 文字列テーブル(リンク先のセクション)の範囲
+fn linked_strtab(section: &Section, sections: &[Section]) -> Option<std::ops::Range<usize>> {
+    sections
+        .get(section.link as usize)
+        .and_then(Section::contents)
+}
+
+// シンボルテーブル
+fn symbols(reader: &Reader, section: &Section, sections: &[Section], wide: bool) -> Option<Field> {
+    let range = section.contents()?;
+    let entsize = if wide { 24 } else { 16 };
+    let strtab = linked_strtab(section, sections);
+    let count = range.len() / section.entsize.max(entsize) as usize;
+    let mut entries = Vec::new();
+    let mut error = None;
+    for i in 0..count.min(MAX_ENTRIES) {
+        let offset = range.start + i * section.entsize.max(entsize) as usize;
+        let (name, info, shndx, value, size) = match symbol(reader, offset, wide) {
+            Ok(x) => x,
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        };
+        let name = strtab
+            .as_ref()
+            .and_then(|x| string_at(reader, x, name as usize))
+            .filter(|x| !x.is_empty())
+            .unwrap_or_else(|| format!("[{i}]"));
+        let text = format!(
+            "{} {} 0x{value:X} {size}",
+            symbol_type(info & 0xF),
+            symbol_bind(info >> 4)
+        );
+        let mut field = Field::new(name, offset..offset + entsize as usize, text);
+        // 定義済みのシンボルはファイル上の位置へ対応付ける
+        if let Some(target) = sections.get(shndx as usize).filter(|_| shndx != 0) {
+            if let Some(contents) = target.contents() {
+                let delta = value.wrapping_sub(target.addr);
+                if delta < target.size {
+                    field = field.with_link(contents.start + delta as usize);
+                }
+            }
+        }
+        entries.push(field);
+    }
+    let group = Field::group(section.name.clone(), entries).with_value(format!("{count} symbols"));
+    Some(with_error(group, error))
+}
+
+// シンボルの値(名前、情報、セクション番号、値、大きさ)
+fn symbol(reader: &Reader, offset: usize, wide: bool) -> Result<(u64, u64, u64, u64, u64), String> {
+    let name = reader.u32(offset)?;
+    let (info, shndx, value, size) = if wide {
+        let info = reader.u8(offset + 4)?;
+        let shndx = reader.u16(offset + 6)?;
+        (
+            info,
+            shndx,
+            reader.uint(offset + 8, 8)?,
+            reader.uint(offset + 16, 8)?,
+        )
+    } else {
+        let value = reader.u32(offset + 4)?;
+        let size = reader.u32(offset + 8)?;
+        (
+            reader.u8(offset + 12)?,
+            reader.u16(offset + 14)?,
+            value,
+            size,
+        )
+    };
+    Ok((name, info, shndx, value, size))
+}
+
+// 動的セクション
+fn dynamic(reader: &Reader, section: &Section, sections: &[Section], wide: bool) -> Option<Field> {
+    let range = section.contents()?;
+    let word = if wide { 8 } else { 4 };
+    let strtab = linked_strtab(section, sections);
+    let mut entries = Vec::new();
+    let mut error = None;
+    let mut offset = range.start;
+    while offset + word * 2 <= range.end && entries.len() < MAX_ENTRIES {
+        let (tag, value) = match reader
+            .uint(offset, word)
+            .and_then(|tag| Ok((tag, reader.uint(offset + word, word)?)))
+        {
+            Ok(x) => x,
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        };
+        let text = match tag {
+            // 文字列テーブルを参照するもの
+            1 | 14 | 15 | 29 => strtab
+                .as_ref()
+                .and_then(|x| string_at(reader, x, value as usize))
+                .unwrap_or_else(|| format!("0x{value:X}")),
+            _ => number(value),
+        };
+        let name = dynamic_tag(tag).map_or_else(|| format!("0x{tag:X}"), String::from);
+        entries.push(Field::new(name, offset..offset + word * 2, text));
+        offset += word * 2;
+        if tag == 0 {
+            break;
+        }
+    }
+    let count = entries.len();
+    let group = Field::group(section.name.clone(), entries).with_value(format!("{count} entries"));
+    Some(with_error(group, error))
+}
+
+// 読み取りの問題があれば項目に付ける
+fn with_error(field: Field, error: Option<String>) -> Field {
+    match error {
+        Some(error) => field.with_error(error),
+        None => field,
+    }
+}
+
+// セグメントの権限(R/W/X)
+fn flag_text(flags: u64) -> String {
+    [(4, 'R'), (2, 'W'), (1, 'X')]
+        .iter()
+        .map(|&(bit, c)| if flags & bit != 0 { c } else { '-' })
+        .collect()
+}
+
+fn osabi_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "SYSV",
+        1 => "HPUX",
+        2 => "NETBSD",
+        3 => "LINUX",
+        6 => "SOLARIS",
+        9 => "FREEBSD",
+        12 => "OPENBSD",
+        97 => "ARM",
+        255 => "STANDALONE",
+        _ => return None,
+    })
+}
+
+fn type_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "NONE",
+        1 => "REL",
+        2 => "EXEC",
+        3 => "DYN",
+        4 => "CORE",
+        _ => return None,
+    })
+}
+
+fn machine_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        2 => "SPARC",
+        3 => "386",
+        8 => "MIPS",
+        20 => "PPC",
+        21 => "PPC64",
+        22 => "S390",
+        40 => "ARM",
+        42 => "SH",
+        43 => "SPARCV9",
+        50 => "IA_64",
+        62 => "X86_64",
+        183 => "AARCH64",
+        243 => "RISCV",
+        247 => "BPF",
+        258 => "LOONGARCH",
+        _ => return None,
+    })
+}
+
+fn segment_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "NULL",
+        1 => "LOAD",
+        2 => "DYNAMIC",
+        3 => "INTERP",
+        4 => "NOTE",
+        5 => "SHLIB",
+        6 => "PHDR",
+        7 => "TLS",
+        0x6474_E550 => "GNU_EH_FRAME",
+        0x6474_E551 => "GNU_STACK",
+        0x6474_E552 => "GNU_RELRO",
+        0x6474_E553 => "GNU_PROPERTY",
+        _ => return None,
+    })
+}
+
+fn section_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "NULL",
+        1 => "PROGBITS",
+        2 => "SYMTAB",
+        3 => "STRTAB",
+        4 => "RELA",
+        5 => "HASH",
+        6 => "DYNAMIC",
+        7 => "NOTE",
+        8 => "NOBITS",
+        9 => "REL",
+        11 => "DYNSYM",
+        14 => "INIT_ARRAY",
+        15 => "FINI_ARRAY",
+        16 => "PREINIT_ARRAY",
+        17 => "GROUP",
+        18 => "SYMTAB_SHNDX",
+        0x6FFF_FFF6 => "GNU_HASH",
+        0x6FFF_FFFD => "VERDEF",
+        0x6FFF_FFFE => "VERNEED",
+        0x6FFF_FFFF => "VERSYM",
+        _ => return None,
+    })
+}
+
+fn symbol_type(value: u64) -> &'static str {
+    match value {
+        0 => "NOTYPE",
+        1 => "OBJECT",
+        2 => "FUNC",
+        3 => "SECTION",
+        4 => "FILE",
+        5 => "COMMON",
+        6 => "TLS",
+        10 => "IFUNC",
+        _ => "?",
+    }
+}
+
+fn symbol_bind(value: u64) -> &'static str {
+    match value {
+        0 => "LOCAL",
+        1 => "GLOBAL",
+        2 => "WEAK",
+        10 => "UNIQUE",
+        _ => "?",
+    }
+}
+
+fn dynamic_tag(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "NULL",
+        1 => "NEEDED",
+        2 => "PLTRELSZ",
+        3 => "PLTGOT",
+        4 => "HASH",
+        5 => "STRTAB",
+        6 => "SYMTAB",
+        7 => "RELA",
+        8 => "RELASZ",
+        9 => "RELAENT",
+        10 => "STRSZ",
+        11 => "SYMENT",
+        12 => "INIT",
+        13 => "FINI",
+        14 => "SONAME",
+        15 => "RPATH",
+        16 => "SYMBOLIC",
+        17 => "REL",
+        18 => "RELSZ",
+        19 => "RELENT",
+        20 => "PLTREL",
+        21 => "DEBUG",
+        22 => "TEXTREL",
+        23 => "JMPREL",
+        24 => "BIND_NOW",
+        25 => "INIT_ARRAY",
+        26 => "FINI_ARRAY",
+        27 => "INIT_ARRAYSZ",
+        28 => "FINI_ARRAYSZ",
+        29 => "RUNPATH",
+        30 => "FLAGS",
+        0x6FFF_FEF5 => "GNU_HASH",
+        0x6FFF_FFF0 => "VERSYM",
+        0x6FFF_FFF9 => "RELACOUNT",
+        0x6FFF_FFFB => "FLAGS_1",
+        0x6FFF_FFFE => "VERNEED",
+        0x6FFF_FFFF => "VERNEEDNUM",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_elf() {
+        let path = std::env::current_exe().unwrap();
+        let buf = std::fs::read(path).unwrap();
+        let root = parse(&buf).unwrap();
+        let sections = root.children.iter().find(|x| x.name == "sections").unwrap();
+        let text = sections
+            .children
+            .iter()
+            .find(|x| x.name.ends_with(" .text"));
+        assert!(text.is_some_and(|x| x.link.is_some()));
+        assert!(parse(b"\x7fELF\x03").is_err());
+    }
+
+    // ビッグエンディアンの ELF32(プログラムヘッダ1つ、セクション2つ)
+    fn elf32() -> Vec<u8> {
+        let words =
+            |values: &[u32]| -> Vec<u8> { values.iter().flat_map(|x| x.to_be_bytes()).collect() };
+        let mut buf = b"\x7fELF\x01\x02\x01".to_vec();
+        buf.resize(16, 0);
+        buf.extend([0, 2, 0, 8]);
+        buf.extend(words(&[1, 0x400000, 52, 96, 0]));
+        buf.extend([0, 52, 0, 32, 0, 1, 0, 40, 0, 2, 0, 1]);
+        buf.extend(words(&[1, 0, 0x400000, 0x400000, 52, 52, 5, 0x1000]));
+        buf.extend(b"\0.shstrtab\0");
+        buf.resize(96, 0);
+        buf.extend([0; 40]);
+        buf.extend(words(&[1, 3, 0, 0, 84, 11, 0, 0, 1, 0]));
+        buf
+    }
+
+    #[test]
+    fn test_parse_elf32() {
+        let buf = elf32();
+        let root = parse(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert!(root.children[0].value.starts_with("ELF32"));
+        let segments = &root.children[1];
+        assert_eq!(segments.children[0].name, "[0] LOAD");
+        assert_eq!(segments.children[0].value, "R-X 52 bytes");
+        let sections = &root.children[2];
+        assert_eq!(sections.children[1].name, "[1] .shstrtab");
+        assert_eq!(sections.children[1].link, Some(84));
+
+        // セクションヘッダの途中で切れている
+        let root = parse(&buf[..150]).unwrap();
+        let header = &root.children[0];
+        let shoff = header.children.iter().find(|x| x.name == "shoff").unwrap();
+        assert!(shoff.error.is_some());
+        let sections = root.children.iter().find(|x| x.name == "sections").unwrap();
+        assert_eq!(sections.children.len(), 2);
+        assert_eq!(sections.children[1].name, "[1]");
+        assert!(sections.children[1].error.is_some());
+
+        // プログラムヘッダの位置がファイルの外
+        let mut buf = buf;
+        buf[28..32].copy_from_slice(&0x1000u32.to_be_bytes());
+        let root = parse(&buf).unwrap();
+        let phoff = root.children[0]
+            .children
+            .iter()
+            .find(|x| x.name == "phoff")
+            .unwrap();
+        assert!(phoff.error.is_some());
+        assert!(root.children.iter().all(|x| x.name != "program headers"));
+        assert!(root.children.iter().any(|x| x.name == "sections"));
+    }
+}
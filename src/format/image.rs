@@ -0,0 +1,643 @@
+// 画像形式(PNG、JPEG、GIF、BMP)
+
+// 親モジュール
+use super::{crc32, Reader, Record};
+// 状態管理
+use crate::message::Endian;
+// 構造の木
+use crate::structure::Field;
+
+// 一度に展開する項目数の上限
+const MAX_ENTRIES: usize = 1 << 16;
+
+pub(super) const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// 途中で切れている残りの部分
+fn truncated(offset: usize, len: usize) -> Field {
+    Field::new("truncated", offset..len, format!("{} bytes", len - offset))
+        .with_error("unexpected end of file")
+}
+
+// 最後の要素より後ろの部分
+fn trailing(offset: usize, len: usize) -> Option<Field> {
+    (offset < len).then(|| {
+        Field::new(
+            "trailing data",
+            offset..len,
+            format!("{} bytes", len - offset),
+        )
+    })
+}
+
+// PNG のチャンク
+pub(super) fn parse_png(buf: &[u8]) -> Result<Field, String> {
+    if !buf.starts_with(PNG_SIGNATURE) {
+        return Err(String::from("Not a PNG file"));
+    }
+    let reader = Reader::new(buf, Endian::Big);
+    let mut children = vec![Field::new("signature", 0..8, "89 'PNG' 0D 0A 1A 0A")];
+    let mut offset = 8;
+    let mut ended = false;
+    while offset < buf.len() && children.len() < MAX_ENTRIES {
+        let Ok(kind) = reader.bytes(offset + 4, 4) else {
+            children.push(truncated(offset, buf.len()));
+            offset = buf.len();
+            break;
+        };
+        let name = String::from_utf8_lossy(kind).into_owned();
+        let mut chunk = reader.record(offset);
+        let length = chunk.num("length", 4)? as usize;
+        chunk.push("type", 4, format!("{name:?}"));
+        let data = chunk.offset;
+        // 長さが残りを超えるものはそこで打ち切る
+        let Some(end) = data.checked_add(length).filter(|x| x + 4 <= buf.len()) else {
+            let field = chunk
+                .finish(name)
+                .with_value(format!("{length} bytes"))
+                .with_error("length exceeds file");
+            children.push(field);
+            children.push(truncated(data, buf.len()));
+            offset = buf.len();
+            break;
+        };
+        match name.as_str() {
+            "IHDR" => {
+                let mut header = reader.record(data);
+                header.num("width", 4)?;
+                header.num("height", 4)?;
+                header.num("bit depth", 1)?;
+                header.named("color type", 1, color_type)?;
+                header.num("compression", 1)?;
+                header.num("filter", 1)?;
+                header.named("interlace", 1, |x| match x {
+                    0 => Some("none"),
+                    1 => Some("Adam7"),
+                    _ => None,
+                })?;
+                chunk.fields.push(header.finish("data"));
+                chunk.offset = end;
+            }
+            "tEXt" | "iTXt" | "zTXt" => {
+                let keyword = reader.c_str(data).unwrap_or_default();
+                chunk.push("data", length, keyword);
+            }
+            _ if length > 0 => chunk.push("data", length, format!("{length} bytes")),
+            _ => {}
+        }
+        let stored = chunk.hex("crc", 4)? as u32;
+        let actual = crc32(&buf[offset + 4..end]);
+        let mut field = chunk
+            .finish(name.clone())
+            .with_value(format!("{length} bytes"));
+        if stored != actual {
+            field = field.with_error(format!("bad CRC (expected 0x{actual:08X})"));
+        } else if name == "IHDR" && length != 13 {
+            field = field.with_error("IHDR length must be 13");
+        }
+        children.push(field);
+        offset = end + 4;
+        if name == "IEND" {
+            ended = true;
+            break;
+        }
+    }
+    children.extend(trailing(offset, buf.len()));
+    let root = Field::group("PNG", children).with_value(format!("{} bytes", buf.len()));
+    Ok(if ended {
+        root
+    } else {
+        root.with_error("missing IEND")
+    })
+}
+
+// JPEG のマーカーとセグメント
+pub(super) fn parse_jpeg(buf: &[u8]) -> Result<Field, String> {
+    if !buf.starts_with(&[0xFF, 0xD8]) {
+        return Err(String::from("Not a JPEG file"));
+    }
+    let reader = Reader::new(buf, Endian::Big);
+    let mut children = Vec::new();
+    let mut offset = 0;
+    let mut ended = false;
+    while offset < buf.len() && children.len() < MAX_ENTRIES {
+        // マーカー以外のバイトは次のマーカーまでまとめる
+        if buf[offset] != 0xFF {
+            let end = buf[offset..]
+                .iter()
+                .position(|&x| x == 0xFF)
+                .map_or(buf.len(), |x| offset + x);
+            let field = Field::new("garbage", offset..end, format!("{} bytes", end - offset))
+                .with_error("expected a marker");
+            children.push(field);
+            offset = end;
+            continue;
+        }
+        // 埋め草の 0xFF を読み飛ばす
+        let start = offset;
+        while buf.get(offset + 1) == Some(&0xFF) {
+            offset += 1;
+        }
+        let Some(&code) = buf.get(offset + 1) else {
+            children.push(truncated(start, buf.len()));
+            offset = buf.len();
+            break;
+        };
+        let name = marker_name(code);
+        // 長さを持たないマーカー
+        if matches!(code, 0x01 | 0xD0..=0xD9) {
+            children.push(Field::new(
+                name,
+                start..offset + 2,
+                format!("FF {code:02X}"),
+            ));
+            offset += 2;
+            if code == 0xD9 {
+                ended = true;
+                break;
+            }
+            continue;
+        }
+        let mut segment = reader.record(start);
+        if offset > start {
+            segment.push("fill", offset - start, String::new());
+        }
+        segment.push("marker", 2, format!("FF {code:02X}"));
+        let Ok(length) = segment.num("length", 2) else {
+            children.push(truncated(start, buf.len()));
+            offset = buf.len();
+            break;
+        };
+        let length = length as usize;
+        let data = offset + 4;
+        let end = offset + 2 + length;
+        if length < 2 || end > buf.len() {
+            let field = segment.finish(name).with_error("invalid length");
+            children.push(field);
+            children.push(truncated(data, buf.len()));
+            offset = buf.len();
+            break;
+        }
+        let size = length - 2;
+        let mut error = None;
+        match code {
+            // SOFn(DHT、JPG、DAC を除く)
+            0xC0..=0xCF if !matches!(code, 0xC4 | 0xC8 | 0xCC) && size >= 6 => {
+                segment.num("precision", 1)?;
+                segment.num("height", 2)?;
+                segment.num("width", 2)?;
+                let components = segment.num("components", 1)? as usize;
+                let rest = size - 6;
+                segment.push("component specs", rest, format!("{rest} bytes"));
+                if components * 3 != rest {
+                    error = Some("component count mismatch");
+                }
+            }
+            // APPn は識別子を表示
+            0xE0..=0xEF => {
+                let identifier = reader.c_str(data).unwrap_or_default();
+                segment.push("data", size, format!("{identifier:?}"));
+            }
+            0xFE => {
+                let text = String::from_utf8_lossy(&buf[data..end]).into_owned();
+                segment.push("comment", size, format!("{text:?}"));
+            }
+            _ if size > 0 => segment.push("data", size, format!("{size} bytes")),
+            _ => {}
+        }
+        let field = segment.finish(name).with_value(format!("{length} bytes"));
+        children.push(match error {
+            Some(error) => field.with_error(error),
+            None => field,
+        });
+        offset = end;
+
+        // SOS の後ろは次のマーカーまで圧縮データ
+        if code == 0xDA {
+            let scan_end = (offset..buf.len().saturating_sub(1))
+                .find(|&i| buf[i] == 0xFF && !matches!(buf[i + 1], 0x00 | 0xD0..=0xD7 | 0xFF))
+                .unwrap_or(buf.len());
+            let size = scan_end - offset;
+            children.push(Field::new(
+                "scan data",
+                offset..scan_end,
+                format!("{size} bytes"),
+            ));
+            offset = scan_end;
+        }
+    }
+    children.extend(trailing(offset, buf.len()));
+    let root = Field::group("JPEG", children).with_value(format!("{} bytes", buf.len()));
+    Ok(if ended {
+        root
+    } else {
+        root.with_error("missing EOI")
+    })
+}
+
+// GIF のブロック
+pub(super) fn parse_gif(buf: &[u8]) -> Result<Field, String> {
+    if !buf.starts_with(b"GIF87a") && !buf.starts_with(b"GIF89a") {
+        return Err(String::from("Not a GIF file"));
+    }
+    let reader = Reader::new(buf, Endian::Little);
+    let version = String::from_utf8_lossy(&buf[3..6]).into_owned();
+    let mut header = reader.record(0);
+    header.push("signature", 3, String::from("GIF"));
+    header.push("version", 3, version.clone());
+    header.num("width", 2)?;
+    header.num("height", 2)?;
+    let flags = header.hex("flags", 1)?;
+    header.num("background", 1)?;
+    header.num("aspect", 1)?;
+    let mut children = vec![header.finish("header").with_value(version)];
+    let mut offset = 13;
+    if let Some(field) = color_table("global color table", offset, flags, buf.len()) {
+        offset = field.range.end;
+        children.push(field);
+    }
+
+    let mut ended = false;
+    while children.len() < MAX_ENTRIES {
+        let Some(&introducer) = buf.get(offset) else {
+            break;
+        };
+        let field = match introducer {
+            // 拡張ブロック
+            0x21 => {
+                let Some(&label) = buf.get(offset + 1) else {
+                    children.push(truncated(offset, buf.len()));
+                    offset = buf.len();
+                    break;
+                };
+                let mut block = reader.record(offset);
+                block.hex("introducer", 1)?;
+                block.named("label", 1, extension_name)?;
+                let name = extension_name(label as u64).unwrap_or("Extension");
+                let (blocks, error) = sub_blocks(buf, block.offset);
+                offset = blocks.range.end;
+                block.fields.push(blocks);
+                let field = block.finish(name);
+                match error {
+                    Some(error) => field.with_error(error),
+                    None => field,
+                }
+            }
+            // 画像
+            0x2C => {
+                let mut block = reader.record(offset);
+                block.hex("separator", 1)?;
+                block.num("left", 2)?;
+                block.num("top", 2)?;
+                let width = block.num("width", 2)?;
+                let height = block.num("height", 2)?;
+                let flags = block.hex("flags", 1)?;
+                if let Some(table) =
+                    color_table("local color table", block.offset, flags, buf.len())
+                {
+                    block.offset = table.range.end;
+                    block.fields.push(table);
+                }
+                block.num("LZW code size", 1)?;
+                let (blocks, error) = sub_blocks(buf, block.offset);
+                offset = blocks.range.end;
+                block.fields.push(blocks);
+                let field = block
+                    .finish("Image")
+                    .with_value(format!("{width}x{height}"));
+                match error {
+                    Some(error) => field.with_error(error),
+                    None => field,
+                }
+            }
+            0x3B => {
+                children.push(Field::new("Trailer", offset..offset + 1, "3B"));
+                offset += 1;
+                ended = true;
+                break;
+            }
+            _ => {
+                let field = Field::new("unknown", offset..buf.len(), format!("0x{introducer:02X}"))
+                    .with_error("unknown block");
+                children.push(field);
+                offset = buf.len();
+                break;
+            }
+        };
+        let failed = field.error.is_some();
+        children.push(field);
+        if failed {
+            break;
+        }
+    }
+    children.extend(trailing(offset, buf.len()));
+    let root = Field::group("GIF", children).with_value(format!("{} bytes", buf.len()));
+    Ok(if ended {
+        root
+    } else {
+        root.with_error("missing trailer")
+    })
+}
+
+// GIF の色テーブル(flags の最上位ビットが立っているときだけ)
+fn color_table(name: &str, offset: usize, flags: u64, len: usize) -> Option<Field> {
+    if flags & 0x80 == 0 {
+        return None;
+    }
+    let colors = 2usize << (flags & 7);
+    let end = offset + colors * 3;
+    let field = Field::new(name, offset..end.min(len), format!("{colors} colors"));
+    Some(if end > len {
+        field.with_error("exceeds file")
+    } else {
+        field
+    })
+}
+
+// GIF のサブブロック列(長さ 0 のブロックで終わる)
+fn sub_blocks(buf: &[u8], start: usize) -> (Field, Option<String>) {
+    let mut offset = start;
+    let mut count = 0;
+    let mut size = 0;
+    let error = loop {
+        match buf.get(offset) {
+            Some(0) => {
+                offset += 1;
+                break None;
+            }
+            Some(&len) if offset + 1 + len as usize <= buf.len() => {
+                offset += 1 + len as usize;
+                count += 1;
+                size += len as usize;
+            }
+            _ => {
+                offset = buf.len();
+                break Some(String::from("sub-block exceeds file"));
+            }
+        }
+    };
+    let value = format!("{size} bytes in {count} blocks");
+    (Field::new("data", start..offset, value), error)
+}
+
+// BMP のヘッダ
+pub(super) fn parse_bmp(buf: &[u8]) -> Result<Field, String> {
+    if !buf.starts_with(b"BM") {
+        return Err(String::from("Not a BMP file"));
+    }
+    let reader = Reader::new(buf, Endian::Little);
+    let mut file = reader.record(0);
+    file.push("type", 2, String::from("BM"));
+    let size = file.num("size", 4)? as usize;
+    file.hex("reserved1", 2)?;
+    file.hex("reserved2", 2)?;
+    let pixels = file.hex("pixel offset", 4)? as usize;
+    let mut file = file.finish("file header");
+    if size != buf.len() {
+        file = file.with_error(format!("size differs from file ({})", buf.len()));
+    }
+    let mut children = vec![file];
+
+    let mut info = reader.record(14);
+    let header_size = info.num("size", 4)? as usize;
+    let name = match header_size {
+        12 => "BITMAPCOREHEADER",
+        40 => "BITMAPINFOHEADER",
+        108 => "BITMAPV4HEADER",
+        124 => "BITMAPV5HEADER",
+        _ => "DIB header",
+    };
+    if header_size != 12 && header_size < 40 {
+        let field = info.finish(name).with_error("unsupported header size");
+        children.push(field);
+        return Ok(Field::group("BMP", children).with_value(format!("{} bytes", buf.len())));
+    }
+    let (width, height, bits, compression) = match dib_header(&mut info, header_size) {
+        Ok(x) => x,
+        Err(e) => {
+            children.push(info.finish(name).with_error(e));
+            return Ok(Field::group("BMP", children).with_value(format!("{} bytes", buf.len())));
+        }
+    };
+    let end = info.offset;
+    let mut field = info
+        .finish(name)
+        .with_value(format!("{width}x{height} {bits}bpp"));
+    if header_size.saturating_add(14) > buf.len() {
+        field = field.with_error("header exceeds file");
+    }
+    children.push(field);
+
+    // ヘッダと画素の間は色テーブル
+    if pixels > end {
+        let table = Field::new("color table", end..pixels.min(buf.len()), String::new());
+        let entry = if header_size == 12 { 3 } else { 4 };
+        children.push(table.with_value(format!("{} colors", (pixels - end) / entry)));
+    }
+    let mut data = Field::new(
+        "pixel data",
+        pixels.min(buf.len())..buf.len(),
+        String::new(),
+    );
+    // 非圧縮の場合は画素数から大きさを確かめる
+    let stride = (width.unsigned_abs().saturating_mul(bits)).div_ceil(32) * 4;
+    let expected = stride.saturating_mul(height.unsigned_abs()) as usize;
+    data.value = format!("{} bytes", data.range.len());
+    if pixels > buf.len() {
+        data = data.with_error("pixel offset exceeds file");
+    } else if matches!(compression, 0 | 3) && data.range.len() < expected {
+        data = data.with_error(format!("expected {expected} bytes"));
+    }
+    children.push(data.with_link(pixels));
+
+    Ok(Field::group("BMP", children).with_value(format!("{} bytes", buf.len())))
+}
+
+// BMP の情報ヘッダ(幅、高さ、ビット数、圧縮方式)
+// 拡張部分はファイルに収まる分だけ読む
+fn dib_header(info: &mut Record, header_size: usize) -> Result<(i64, i64, u64, u64), String> {
+    if header_size == 12 {
+        // OS/2 の BITMAPCOREHEADER
+        let width = info.num("width", 2)? as i64;
+        let height = info.num("height", 2)? as i64;
+        info.num("planes", 2)?;
+        return Ok((width, height, info.num("bit count", 2)?, 0));
+    }
+    let width = info.int("width", 4)?;
+    let height = info.int("height", 4)?;
+    info.num("planes", 2)?;
+    let bits = info.num("bit count", 2)?;
+    let compression = info.named("compression", 4, |x| {
+        ["RGB", "RLE8", "RLE4", "BITFIELDS", "JPEG", "PNG"]
+            .get(x as usize)
+            .copied()
+    })?;
+    info.num("image size", 4)?;
+    info.int("x pixels/m", 4)?;
+    info.int("y pixels/m", 4)?;
+    info.num("colors used", 4)?;
+    info.num("colors important", 4)?;
+    // V4/V5 ヘッダの残り
+    let rest = (header_size - 40).min(info.reader.buf.len() - info.offset);
+    if rest > 0 {
+        info.raw("extension", rest)?;
+    }
+    Ok((width, height, bits, compression))
+}
+
+fn color_type(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "gray",
+        2 => "RGB",
+        3 => "indexed",
+        4 => "gray+alpha",
+        6 => "RGBA",
+        _ => return None,
+    })
+}
+
+fn marker_name(code: u8) -> String {
+    match code {
+        0x01 => String::from("TEM"),
+        0xC4 => String::from("DHT"),
+        0xC8 => String::from("JPG"),
+        0xCC => String::from("DAC"),
+        0xC0..=0xCF => format!("SOF{}", code - 0xC0),
+        0xD0..=0xD7 => format!("RST{}", code - 0xD0),
+        0xD8 => String::from("SOI"),
+        0xD9 => String::from("EOI"),
+        0xDA => String::from("SOS"),
+        0xDB => String::from("DQT"),
+        0xDD => String::from("DRI"),
+        0xE0..=0xEF => format!("APP{}", code - 0xE0),
+        0xFE => String::from("COM"),
+        _ => format!("FF{code:02X}"),
+    }
+}
+
+fn extension_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0x01 => "Plain Text",
+        0xF9 => "Graphic Control",
+        0xFE => "Comment",
+        0xFF => "Application",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_png() {
+        let mut buf = PNG_SIGNATURE.to_vec();
+        let mut chunk = |kind: &[u8], data: &[u8]| {
+            buf.extend((data.len() as u32).to_be_bytes());
+            let start = buf.len();
+            buf.extend(kind);
+            buf.extend(data);
+            let crc = crc32(&buf[start..]);
+            buf.extend(crc.to_be_bytes());
+        };
+        chunk(b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0]);
+        chunk(b"IEND", &[]);
+        let root = parse_png(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[1].name, "IHDR");
+
+        // CRC を壊す
+        let len = buf.len();
+        buf[len - 1] ^= 1;
+        let root = parse_png(&buf).unwrap();
+        assert_eq!(root.error_count(), 1);
+        assert!(root.children[2].error.is_some());
+
+        // 長さが残りを超える
+        buf[11] = 0xFF;
+        let root = parse_png(&buf).unwrap();
+        assert!(root.children[1].error.is_some());
+    }
+
+    #[test]
+    fn test_parse_jpeg() {
+        // SOI、コメント、EOI
+        let mut buf = vec![0xFF, 0xD8, 0xFF, 0xFE, 0x00, 0x04, b'h', b'i', 0xFF, 0xD9];
+        let root = parse_jpeg(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[1].children[2].value, "\"hi\"");
+
+        // 長さが 2 未満
+        buf[5] = 0x01;
+        let root = parse_jpeg(&buf).unwrap();
+        assert_eq!(root.children[1].error.as_deref(), Some("invalid length"));
+
+        // 長さが残りを超える
+        buf[4] = 0xFF;
+        let root = parse_jpeg(&buf).unwrap();
+        assert_eq!(root.children[1].error.as_deref(), Some("invalid length"));
+        assert_eq!(root.children[2].name, "truncated");
+
+        // 長さの途中で終わる
+        let root = parse_jpeg(&buf[..5]).unwrap();
+        assert_eq!(root.children[1].name, "truncated");
+    }
+
+    #[test]
+    fn test_parse_gif() {
+        let mut buf = b"GIF89a\x01\x00\x01\x00\x00\x00\x00".to_vec();
+        // 画像(サブブロック1つ)と終端
+        buf.extend([0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 2, 0x4C, 0x01, 0, 0x3B]);
+        let root = parse_gif(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[1].value, "1x1");
+
+        // サブブロックがファイルを超える
+        buf[24] = 0x40;
+        let root = parse_gif(&buf).unwrap();
+        assert_eq!(
+            root.children[1].error.as_deref(),
+            Some("sub-block exceeds file")
+        );
+        assert_eq!(root.children[1].range.end, buf.len());
+    }
+
+    #[test]
+    fn test_parse_bmp() {
+        // 1x1 の 24 ビット
+        let mut buf = b"BM".to_vec();
+        buf.extend(58u32.to_le_bytes());
+        buf.extend([0; 4]);
+        buf.extend(54u32.to_le_bytes());
+        buf.extend(40u32.to_le_bytes());
+        buf.extend(1i32.to_le_bytes());
+        buf.extend(1i32.to_le_bytes());
+        buf.extend([1, 0, 24, 0]);
+        buf.extend([0; 24]);
+        buf.extend([0xFF; 4]);
+        let root = parse_bmp(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[1].value, "1x1 24bpp");
+
+        // ヘッダの大きさがファイルを超える
+        let mut broken = buf.clone();
+        broken[14..18].copy_from_slice(&u32::MAX.to_le_bytes());
+        let root = parse_bmp(&broken).unwrap();
+        assert!(root.children[1].error.is_some());
+
+        // 対応しないヘッダの大きさ
+        broken[14..18].copy_from_slice(&16u32.to_le_bytes());
+        let root = parse_bmp(&broken).unwrap();
+        assert!(root.children[1].error.is_some());
+
+        // 情報ヘッダの途中で終わる
+        let root = parse_bmp(&buf[..30]).unwrap();
+        assert!(root.children[1].error.is_some());
+
+        // 幅と高さが極端に大きい
+        let mut broken = buf.clone();
+        broken[18..26].fill(0x7F);
+        broken[28] = 0xFF;
+        let root = parse_bmp(&broken).unwrap();
+        assert!(root.children[2].error.is_some());
+    }
+}
@@ -0,0 +1,668 @@
+// PE/COFF(Windows の EXE/DLL)
+
+// 親モジュール
+use super::{AddressMap, Reader};
+// 状態管理
+use crate::message::Endian;
+// 構造の木
+use crate::structure::Field;
+
+// 一度に展開する項目数の上限
+const MAX_ENTRIES: usize = 1 << 16;
+// リソースの階層の上限
+const MAX_RESOURCE_DEPTH: usize = 4;
+
+// データディレクトリの名前
+const DIRECTORIES: [&str; 16] = [
+    "Export",
+    "Import",
+    "Resource",
+    "Exception",
+    "Security",
+    "BaseReloc",
+    "Debug",
+    "Architecture",
+    "GlobalPtr",
+    "TLS",
+    "LoadConfig",
+    "BoundImport",
+    "IAT",
+    "DelayImport",
+    "CLR",
+    "Reserved",
+];
+
+// DOSヘッダの項目(e_lfanew 以外)
+const DOS_FIELDS: [(&str, usize); 18] = [
+    ("e_magic", 2),
+    ("e_cblp", 2),
+    ("e_cp", 2),
+    ("e_crlc", 2),
+    ("e_cparhdr", 2),
+    ("e_minalloc", 2),
+    ("e_maxalloc", 2),
+    ("e_ss", 2),
+    ("e_sp", 2),
+    ("e_csum", 2),
+    ("e_ip", 2),
+    ("e_cs", 2),
+    ("e_lfarlc", 2),
+    ("e_ovno", 2),
+    ("e_res", 8),
+    ("e_oemid", 2),
+    ("e_oeminfo", 2),
+    ("e_res2", 20),
+];
+
+pub(super) fn parse(buf: &[u8]) -> Result<(Field, AddressMap), String> {
+    let reader = Reader::new(buf, Endian::Little);
+    if !buf.starts_with(b"MZ") {
+        return Err(String::from("Not a PE file"));
+    }
+
+    // DOSヘッダ
+    let mut dos = reader.record(0);
+    for (name, size) in DOS_FIELDS {
+        match size {
+            2 => {
+                dos.hex(name, size)?;
+            }
+            _ => dos.raw(name, size)?,
+        }
+    }
+    let lfanew = dos.hex("e_lfanew", 4)? as usize;
+    let mut children = vec![dos.finish("DOS header").with_link(lfanew)];
+    if reader.bytes(lfanew, 4)? != b"PE\0\0" {
+        return Err(String::from("PE signature not found"));
+    }
+
+    // ファイルヘッダ
+    let mut file = reader.record(lfanew + 4);
+    file.named("Machine", 2, machine_name)?;
+    let section_count = file.num("NumberOfSections", 2)?;
+    file.hex("TimeDateStamp", 4)?;
+    file.hex("PointerToSymbolTable", 4)?;
+    file.num("NumberOfSymbols", 4)?;
+    let optional_size = file.num("SizeOfOptionalHeader", 2)? as usize;
+    file.hex("Characteristics", 2)?;
+    let optional_offset = file.offset;
+    let file = file.finish("file header");
+
+    // オプションヘッダ
+    let mut optional = reader.record(optional_offset);
+    let magic = optional.named("Magic", 2, |x| match x {
+        0x10B => Some("PE32"),
+        0x20B => Some("PE32+"),
+        0x107 => Some("ROM"),
+        _ => None,
+    })?;
+    let wide = magic == 0x20B;
+    // PE32+ は一部の項目が8バイト
+    let word = if wide { 8 } else { 4 };
+    optional.num("MajorLinkerVersion", 1)?;
+    optional.num("MinorLinkerVersion", 1)?;
+    optional.num("SizeOfCode", 4)?;
+    optional.num("SizeOfInitializedData", 4)?;
+    optional.num("SizeOfUninitializedData", 4)?;
+    optional.hex("AddressOfEntryPoint", 4)?;
+    optional.hex("BaseOfCode", 4)?;
+    if !wide {
+        optional.hex("BaseOfData", 4)?;
+    }
+    optional.hex("ImageBase", word)?;
+    optional.hex("SectionAlignment", 4)?;
+    optional.hex("FileAlignment", 4)?;
+    for name in [
+        "MajorOperatingSystemVersion",
+        "MinorOperatingSystemVersion",
+        "MajorImageVersion",
+        "MinorImageVersion",
+        "MajorSubsystemVersion",
+        "MinorSubsystemVersion",
+    ] {
+        optional.num(name, 2)?;
+    }
+    optional.num("Win32VersionValue", 4)?;
+    optional.hex("SizeOfImage", 4)?;
+    let headers_size = optional.hex("SizeOfHeaders", 4)?;
+    optional.hex("CheckSum", 4)?;
+    optional.named("Subsystem", 2, subsystem_name)?;
+    optional.hex("DllCharacteristics", 2)?;
+    optional.hex("SizeOfStackReserve", word)?;
+    optional.hex("SizeOfStackCommit", word)?;
+    optional.hex("SizeOfHeapReserve", word)?;
+    optional.hex("SizeOfHeapCommit", word)?;
+    optional.hex("LoaderFlags", 4)?;
+    let directory_count = optional.num("NumberOfRvaAndSizes", 4)?.min(16) as usize;
+    let directory_offset = optional.offset;
+
+    // セクションテーブル(アドレス変換に先に使う)
+    let table_offset = optional_offset + optional_size;
+    let mut map = AddressMap::new("RVA");
+    map.add(0, headers_size, 0);
+    let mut sections = Vec::new();
+    for i in 0..(section_count as usize).min(MAX_ENTRIES) {
+        let offset = table_offset + i * 40;
+        let name = section_name(reader.bytes(offset, 8)?);
+        let mut entry = reader.record(offset);
+        entry.push("Name", 8, format!("{name:?}"));
+        let virtual_size = entry.hex("VirtualSize", 4)?;
+        let address = entry.hex("VirtualAddress", 4)?;
+        let raw_size = entry.hex("SizeOfRawData", 4)?;
+        let raw_offset = entry.hex("PointerToRawData", 4)?;
+        entry.hex("PointerToRelocations", 4)?;
+        entry.hex("PointerToLinenumbers", 4)?;
+        entry.num("NumberOfRelocations", 2)?;
+        entry.num("NumberOfLinenumbers", 2)?;
+        let flags = entry.hex("Characteristics", 4)?;
+        // ファイル上にある部分だけを対応付ける
+        let mapped = match virtual_size {
+            0 => raw_size,
+            _ => raw_size.min(virtual_size),
+        };
+        map.add(address, mapped, raw_offset as usize);
+        let mut field = entry
+            .finish(format!("[{i}] {name}"))
+            .with_value(format!("{} RVA 0x{address:X}", section_flags(flags)));
+        if raw_size > 0 {
+            field = field.with_link(raw_offset as usize);
+        }
+        sections.push(field);
+    }
+
+    // データディレクトリ
+    let mut directories = Vec::new();
+    let mut found = [(0u64, 0u64); 16];
+    for (i, name) in DIRECTORIES.iter().enumerate().take(directory_count) {
+        let mut entry = reader.record(directory_offset + i * 8);
+        let address = entry.hex("VirtualAddress", 4)?;
+        let size = entry.num("Size", 4)?;
+        found[i] = (address, size);
+        let mut field = entry
+            .finish(*name)
+            .with_value(format!("0x{address:X} {size} bytes"));
+        // Security だけはファイル上の位置
+        let target = match i {
+            4 => Some(address as usize),
+            _ => map.to_offset(address),
+        };
+        if let Some(target) = target.filter(|_| size > 0) {
+            field = field.with_link(target);
+        }
+        directories.push(field);
+    }
+
+    // エクスポート、インポート、リソース(解析できなければディレクトリの項目に印を付ける)
+    let context = Context {
+        reader: &reader,
+        map: &map,
+        word,
+    };
+    let mut tables = Vec::new();
+    for (i, &(address, size)) in found.iter().enumerate().take(3) {
+        if size == 0 {
+            continue;
+        }
+        let table = match i {
+            0 => context.exports(address),
+            1 => context.imports(address),
+            _ => context.offset(address).and_then(|offset| {
+                let mut field = context.resources(offset, offset, 0)?;
+                field.name = String::from("resources");
+                Ok(Some(field))
+            }),
+        };
+        match table {
+            Ok(field) => tables.extend(field),
+            Err(e) => directories[i].error = Some(e),
+        }
+    }
+    optional.fields.push(
+        Field::group("data directories", directories).with_value(format!("{directory_count}")),
+    );
+    let value = if wide { "PE32+" } else { "PE32" };
+    let optional = optional.finish("optional header").with_value(value);
+
+    let signature = Field::new("Signature", lfanew..lfanew + 4, "PE");
+    children.push(Field::group("NT headers", vec![signature, file, optional]));
+    if !sections.is_empty() {
+        children.push(Field::group("sections", sections).with_value(format!("{section_count}")));
+    }
+    children.extend(tables);
+
+    let root = Field::group("PE", children).with_value(format!("{} bytes", buf.len()));
+    Ok((root, map))
+}
+
+// 表の解析に使う値
+struct Context<'r, 'a> {
+    reader: &'r Reader<'a>,
+    map: &'r AddressMap,
+    // サンクの大きさ
+    word: usize,
+}
+
+impl Context<'_, '_> {
+    // RVA をファイル上の位置へ
+    fn offset(&self, address: u64) -> Result<usize, String> {
+        self.map
+            .to_offset(address)
+            .ok_or_else(|| format!("RVA 0x{address:X} is not in the file"))
+    }
+
+    // RVA の位置の文字列
+    fn name_at(&self, address: u64) -> String {
+        self.map
+            .to_offset(address)
+            .and_then(|x| self.reader.c_str(x))
+            .unwrap_or_else(|| format!("0x{address:X}"))
+    }
+
+    // エクスポートテーブル
+    fn exports(&self, address: u64) -> Result<Option<Field>, String> {
+        let offset = self.offset(address)?;
+        let mut table = self.reader.record(offset);
+        table.hex("Characteristics", 4)?;
+        table.hex("TimeDateStamp", 4)?;
+        table.num("MajorVersion", 2)?;
+        table.num("MinorVersion", 2)?;
+        let name = table.hex("Name", 4)?;
+        let base = table.num("Base", 4)?;
+        let function_count = table.num("NumberOfFunctions", 4)? as usize;
+        let name_count = table.num("NumberOfNames", 4)? as usize;
+        let functions = table.hex("AddressOfFunctions", 4)?;
+        let names = table.hex("AddressOfNames", 4)?;
+        let ordinals = table.hex("AddressOfNameOrdinals", 4)?;
+        let table = table.finish("directory");
+
+        // 名前付きのものは名前、それ以外は序数で表示
+        let functions = self.offset(functions)?;
+        let mut labels = vec![None; function_count.min(MAX_ENTRIES)];
+        if name_count > 0 {
+            let names = self.offset(names)?;
+            let ordinals = self.offset(ordinals)?;
+            for i in 0..name_count.min(MAX_ENTRIES) {
+                let index = self.reader.u16(ordinals + i * 2)? as usize;
+                if let Some(label) = labels.get_mut(index) {
+                    *label = Some(self.name_at(self.reader.u32(names + i * 4)?));
+                }
+            }
+        }
+        let mut entries = vec![table];
+        for (i, label) in labels.into_iter().enumerate() {
+            let offset = functions + i * 4;
+            let target = self.reader.u32(offset)?;
+            if target == 0 {
+                continue;
+            }
+            let ordinal = base + i as u64;
+            let label = label.unwrap_or_else(|| format!("#{ordinal}"));
+            let text = format!("ordinal {ordinal} RVA 0x{target:X}");
+            let mut field = Field::new(label, offset..offset + 4, text);
+            if let Some(target) = self.map.to_offset(target) {
+                field = field.with_link(target);
+            }
+            entries.push(field);
+        }
+        let count = entries.len() - 1;
+        Ok(Some(
+            Field::group("exports", entries).with_value(format!("{} {count}", self.name_at(name))),
+        ))
+    }
+
+    // インポートテーブル(DLLごと)
+    fn imports(&self, address: u64) -> Result<Option<Field>, String> {
+        let mut offset = self.offset(address)?;
+        let mut dlls = Vec::new();
+        while dlls.len() < MAX_ENTRIES {
+            let mut descriptor = self.reader.record(offset);
+            let lookup = descriptor.hex("OriginalFirstThunk", 4)?;
+            descriptor.hex("TimeDateStamp", 4)?;
+            descriptor.hex("ForwarderChain", 4)?;
+            let name = descriptor.hex("Name", 4)?;
+            let thunks = descriptor.hex("FirstThunk", 4)?;
+            if lookup == 0 && name == 0 && thunks == 0 {
+                break;
+            }
+            offset += 20;
+
+            // 名前の一覧は OriginalFirstThunk、無ければ FirstThunk から読む
+            let table = match lookup {
+                0 => thunks,
+                _ => lookup,
+            };
+            let mut functions = Vec::new();
+            if let Some(mut entry) = self.map.to_offset(table) {
+                let ordinal_flag = 1u64 << (self.word * 8 - 1);
+                while functions.len() < MAX_ENTRIES {
+                    let thunk = self.reader.uint(entry, self.word)?;
+                    if thunk == 0 {
+                        break;
+                    }
+                    let range = entry..entry + self.word;
+                    let field = if thunk & ordinal_flag != 0 {
+                        let ordinal = thunk & 0xFFFF;
+                        Field::new(format!("#{ordinal}"), range, format!("ordinal {ordinal}"))
+                    } else {
+                        let hint_name = self.map.to_offset(thunk & 0x7FFF_FFFF);
+                        let label = hint_name
+                            .and_then(|x| self.reader.c_str(x + 2))
+                            .unwrap_or_else(|| format!("0x{thunk:X}"));
+                        let hint = match hint_name {
+                            Some(x) => self.reader.u16(x)?,
+                            None => 0,
+                        };
+                        let field = Field::new(label, range, format!("hint {hint}"));
+                        match hint_name {
+                            Some(x) => field.with_link(x),
+                            None => field,
+                        }
+                    };
+                    functions.push(field);
+                    entry += self.word;
+                }
+            }
+            let count = functions.len();
+            let mut children = descriptor.fields;
+            children.push(Field::group("functions", functions).with_value(format!("{count}")));
+            dlls.push(
+                Field::group(self.name_at(name), children).with_value(format!("{count} functions")),
+            );
+        }
+        let count = dlls.len();
+        Ok(Some(
+            Field::group("imports", dlls).with_value(format!("{count} DLLs")),
+        ))
+    }
+
+    // リソースディレクトリ(再帰)
+    // base はリソースセクションの先頭
+    fn resources(&self, base: usize, offset: usize, depth: usize) -> Result<Field, String> {
+        let mut directory = self.reader.record(offset);
+        directory.hex("Characteristics", 4)?;
+        directory.hex("TimeDateStamp", 4)?;
+        directory.num("MajorVersion", 2)?;
+        directory.num("MinorVersion", 2)?;
+        let named = directory.num("NumberOfNamedEntries", 2)? as usize;
+        let ids = directory.num("NumberOfIdEntries", 2)? as usize;
+        let mut children = vec![directory.finish("directory")];
+
+        for i in 0..(named + ids).min(MAX_ENTRIES) {
+            let entry_offset = offset + 16 + i * 8;
+            let id = self.reader.u32(entry_offset)?;
+            let target = self.reader.u32(entry_offset + 4)?;
+            let label = if id & 0x8000_0000 != 0 {
+                self.resource_name(base + (id & 0x7FFF_FFFF) as usize)?
+            } else {
+                match (depth, resource_type(id)) {
+                    (0, Some(name)) => name.to_string(),
+                    _ => format!("#{id}"),
+                }
+            };
+            let field = if target & 0x8000_0000 != 0 {
+                // サブディレクトリ
+                let sub = base + (target & 0x7FFF_FFFF) as usize;
+                if depth + 1 >= MAX_RESOURCE_DEPTH || sub <= offset {
+                    Field::new(label, entry_offset..entry_offset + 8, "(too deep)")
+                } else {
+                    let mut field = self.resources(base, sub, depth + 1)?;
+                    field.name = label;
+                    field
+                }
+            } else {
+                // データエントリ
+                let mut data = self.reader.record(base + target as usize);
+                let address = data.hex("OffsetToData", 4)?;
+                let size = data.num("Size", 4)?;
+                data.num("CodePage", 4)?;
+                data.hex("Reserved", 4)?;
+                let field = data.finish(label).with_value(format!("{size} bytes"));
+                match self.map.to_offset(address) {
+                    Some(x) if size > 0 => field.with_link(x),
+                    _ => field,
+                }
+            };
+            children.push(field);
+        }
+        let count = children.len() - 1;
+        Ok(Field::group("", children).with_value(format!("{count} entries")))
+    }
+
+    // 長さ付き UTF-16 のリソース名
+    fn resource_name(&self, offset: usize) -> Result<String, String> {
+        let len = self.reader.u16(offset)? as usize;
+        let units: Vec<u16> = self
+            .reader
+            .bytes(offset + 2, len * 2)?
+            .chunks_exact(2)
+            .map(|x| u16::from_le_bytes([x[0], x[1]]))
+            .collect();
+        Ok(String::from_utf16_lossy(&units))
+    }
+}
+
+// NUL 埋めのセクション名
+fn section_name(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|&x| x == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+// セクションの権限(R/W/X)
+fn section_flags(flags: u64) -> String {
+    [(0x4000_0000, 'R'), (0x8000_0000, 'W'), (0x2000_0000, 'X')]
+        .iter()
+        .map(|&(bit, c)| if flags & bit != 0 { c } else { '-' })
+        .collect()
+}
+
+fn machine_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        0 => "UNKNOWN",
+        0x14C => "I386",
+        0x166 => "R4000",
+        0x1C0 => "ARM",
+        0x1C4 => "ARMNT",
+        0x200 => "IA64",
+        0x8664 => "AMD64",
+        0xAA64 => "ARM64",
+        0x5064 => "RISCV64",
+        0xEBC => "EBC",
+        _ => return None,
+    })
+}
+
+fn subsystem_name(value: u64) -> Option<&'static str> {
+    Some(match value {
+        1 => "NATIVE",
+        2 => "WINDOWS_GUI",
+        3 => "WINDOWS_CUI",
+        7 => "POSIX_CUI",
+        9 => "WINDOWS_CE_GUI",
+        10 => "EFI_APPLICATION",
+        11 => "EFI_BOOT_SERVICE_DRIVER",
+        12 => "EFI_RUNTIME_DRIVER",
+        13 => "EFI_ROM",
+        14 => "XBOX",
+        16 => "WINDOWS_BOOT_APPLICATION",
+        _ => return None,
+    })
+}
+
+fn resource_type(value: u64) -> Option<&'static str> {
+    Some(match value {
+        1 => "CURSOR",
+        2 => "BITMAP",
+        3 => "ICON",
+        4 => "MENU",
+        5 => "DIALOG",
+        6 => "STRING",
+        7 => "FONTDIR",
+        8 => "FONT",
+        9 => "ACCELERATOR",
+        10 => "RCDATA",
+        11 => "MESSAGETABLE",
+        12 => "GROUP_CURSOR",
+        14 => "GROUP_ICON",
+        16 => "VERSION",
+        17 => "DLGINCLUDE",
+        19 => "PLUGPLAY",
+        20 => "VXD",
+        21 => "ANICURSOR",
+        22 => "ANIICON",
+        23 => "HTML",
+        24 => "MANIFEST",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 1セクションとインポート1つだけの PE32+
+    fn sample() -> Vec<u8> {
+        let mut buf = vec![0u8; 0x400];
+        let mut put = |offset: usize, bytes: &[u8]| {
+            buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        put(0, b"MZ");
+        put(0x3C, &0x40u32.to_le_bytes());
+        put(0x40, b"PE\0\0");
+        put(0x44, &0x8664u16.to_le_bytes());
+        put(0x46, &1u16.to_le_bytes());
+        put(0x54, &240u16.to_le_bytes());
+        put(0x58, &0x20Bu16.to_le_bytes());
+        // SizeOfHeaders と NumberOfRvaAndSizes
+        put(0x58 + 60, &0x200u32.to_le_bytes());
+        put(0x58 + 108, &16u32.to_le_bytes());
+        // インポートディレクトリ
+        put(0x58 + 112 + 8, &0x1000u32.to_le_bytes());
+        put(0x58 + 112 + 12, &40u32.to_le_bytes());
+        // セクション .idata (RVA 0x1000 → 0x200)
+        put(0x148, b".idata");
+        put(0x150, &0x200u32.to_le_bytes());
+        put(0x154, &0x1000u32.to_le_bytes());
+        put(0x158, &0x200u32.to_le_bytes());
+        put(0x15C, &0x200u32.to_le_bytes());
+        // 記述子、サンク、DLL名、ヒントと関数名
+        put(0x200, &0x1040u32.to_le_bytes());
+        put(0x20C, &0x1060u32.to_le_bytes());
+        put(0x210, &0x1040u32.to_le_bytes());
+        put(0x240, &0x1070u64.to_le_bytes());
+        put(0x260, b"KERNEL32.dll\0");
+        put(0x270, &5u16.to_le_bytes());
+        put(0x272, b"ExitProcess\0");
+        buf
+    }
+
+    #[test]
+    fn test_parse_pe() {
+        let (root, map) = parse(&sample()).unwrap();
+        assert_eq!(map.to_offset(0x1070), Some(0x270));
+        assert_eq!(map.to_offset(0x3000), None);
+        let imports = root.children.iter().find(|x| x.name == "imports").unwrap();
+        let dll = &imports.children[0];
+        assert_eq!(dll.name, "KERNEL32.dll");
+        let function = dll.children.last().unwrap().children[0].clone();
+        assert_eq!(function.name, "ExitProcess");
+        assert_eq!(function.value, "hint 5");
+        assert_eq!(function.link, Some(0x270));
+        assert!(parse(b"MZ").is_err());
+    }
+
+    // エクスポートとリソースを持つ PE32
+    fn sample32() -> Vec<u8> {
+        let mut buf = vec![0u8; 0x400];
+        let mut put = |offset: usize, bytes: &[u8]| {
+            buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        put(0, b"MZ");
+        put(0x3C, &0x40u32.to_le_bytes());
+        put(0x40, b"PE\0\0");
+        put(0x44, &0x14Cu16.to_le_bytes());
+        put(0x46, &1u16.to_le_bytes());
+        put(0x54, &224u16.to_le_bytes());
+        put(0x58, &0x10Bu16.to_le_bytes());
+        // SizeOfHeaders と NumberOfRvaAndSizes
+        put(0x58 + 60, &0x200u32.to_le_bytes());
+        put(0x58 + 92, &16u32.to_le_bytes());
+        // エクスポートとリソースのディレクトリ
+        put(0x58 + 96, &0x1000u32.to_le_bytes());
+        put(0x58 + 100, &40u32.to_le_bytes());
+        put(0x58 + 112, &0x1100u32.to_le_bytes());
+        put(0x58 + 116, &0x28u32.to_le_bytes());
+        // セクション .rdata (RVA 0x1000 → 0x200)
+        put(0x138, b".rdata");
+        put(0x140, &0x200u32.to_le_bytes());
+        put(0x144, &0x1000u32.to_le_bytes());
+        put(0x148, &0x200u32.to_le_bytes());
+        put(0x14C, &0x200u32.to_le_bytes());
+        // エクスポート: DLL名、序数の基数、関数2つ、名前1つ
+        put(0x20C, &0x1080u32.to_le_bytes());
+        put(0x210, &1u32.to_le_bytes());
+        put(0x214, &2u32.to_le_bytes());
+        put(0x218, &1u32.to_le_bytes());
+        put(0x21C, &0x1040u32.to_le_bytes());
+        put(0x220, &0x1050u32.to_le_bytes());
+        put(0x224, &0x1058u32.to_le_bytes());
+        put(0x240, &0x1090u32.to_le_bytes());
+        put(0x244, &0x1094u32.to_le_bytes());
+        put(0x250, &0x1060u32.to_le_bytes());
+        put(0x258, &1u16.to_le_bytes());
+        put(0x260, b"Foo\0");
+        put(0x280, b"TEST.dll\0");
+        // リソース: ICON のデータエントリ1つ
+        put(0x30E, &1u16.to_le_bytes());
+        put(0x310, &3u32.to_le_bytes());
+        put(0x314, &0x18u32.to_le_bytes());
+        put(0x318, &0x1180u32.to_le_bytes());
+        put(0x31C, &16u32.to_le_bytes());
+        buf
+    }
+
+    // データディレクトリの項目
+    fn directory<'a>(root: &'a Field, name: &str) -> &'a Field {
+        let optional = &root.children[1].children[2];
+        let directories = optional.children.last().unwrap();
+        directories
+            .children
+            .iter()
+            .find(|x| x.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_pe32() {
+        let mut buf = sample32();
+        let (root, _) = parse(&buf).unwrap();
+        assert_eq!(root.error_count(), 0);
+        assert_eq!(root.children[1].children[2].value, "PE32");
+        let exports = root.children.iter().find(|x| x.name == "exports").unwrap();
+        assert_eq!(exports.value, "TEST.dll 2");
+        assert_eq!(exports.children[1].name, "#1");
+        assert_eq!(exports.children[2].name, "Foo");
+        assert_eq!(exports.children[2].link, Some(0x294));
+        let resources = root
+            .children
+            .iter()
+            .find(|x| x.name == "resources")
+            .unwrap();
+        assert_eq!(resources.children[1].name, "ICON");
+        assert_eq!(resources.children[1].value, "16 bytes");
+        assert_eq!(resources.children[1].link, Some(0x380));
+
+        // ファイル外の RVA はディレクトリの項目の問題になり、他の表は残る
+        buf[0x58 + 96..0x58 + 100].copy_from_slice(&0x5000u32.to_le_bytes());
+        let (root, _) = parse(&buf).unwrap();
+        assert!(directory(&root, "Export").error.is_some());
+        assert!(root.children.iter().all(|x| x.name != "exports"));
+        assert!(root.children.iter().any(|x| x.name == "resources"));
+
+        // 名前の表が切れている
+        buf[0x58 + 96..0x58 + 100].copy_from_slice(&0x1000u32.to_le_bytes());
+        buf[0x218..0x21C].copy_from_slice(&0x1000u32.to_le_bytes());
+        let (root, _) = parse(&buf).unwrap();
+        assert!(directory(&root, "Export").error.is_some());
+        assert!(directory(&root, "Resource").error.is_none());
+    }
+}
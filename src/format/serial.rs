@@ -0,0 +1,603 @@
+// 直列化形式(Protobuf、CBOR、MessagePack、BSON)の復号
+
+// 構造の木
+use crate::structure::Field;
+
+use super::number;
+
+// 入れ子の上限
+const MAX_DEPTH: usize = 32;
+// 文字列の表示の上限
+const MAX_TEXT: usize = 48;
+
+// 復号に失敗した位置と理由
+pub(super) struct Failure {
+    pub(super) offset: usize,
+    pub(super) message: String,
+}
+
+type Decoded<T> = Result<T, Failure>;
+
+// 項目を1つ読む関数(名前を付けて out に入れる)
+type Item<'a> = fn(&mut Decoder<'a>, String, &mut Vec<Field>) -> Decoded<()>;
+
+// buf の pos から end までを読む
+// 失敗しても読めたところまでの項目は out に残す
+pub(super) struct Decoder<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    end: usize,
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub(super) fn new(buf: &'a [u8], start: usize, end: usize) -> Self {
+        Self {
+            buf,
+            pos: start,
+            end,
+            depth: 0,
+        }
+    }
+
+    pub(super) fn pos(&self) -> usize {
+        self.pos
+    }
+
+    fn fail(&self, offset: usize, message: impl Into<String>) -> Failure {
+        Failure {
+            offset,
+            message: message.into(),
+        }
+    }
+
+    // len バイト進める
+    fn take(&mut self, len: u64) -> Decoded<&'a [u8]> {
+        let left = self.end - self.pos;
+        match usize::try_from(len) {
+            Ok(len) if len <= left => {
+                self.pos += len;
+                Ok(&self.buf[self.pos - len..self.pos])
+            }
+            _ => Err(self.fail(self.pos, format!("Needs {len} bytes, {left} left"))),
+        }
+    }
+
+    fn byte(&mut self) -> Decoded<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    // 次のバイト(進めない)
+    fn peek(&self) -> Decoded<u8> {
+        match self.pos < self.end {
+            true => Ok(self.buf[self.pos]),
+            false => Err(self.fail(self.pos, "Unexpected end of data")),
+        }
+    }
+
+    // ビッグエンディアンの符号なし整数
+    fn be(&mut self, size: u64) -> Decoded<u64> {
+        let bytes = self.take(size)?;
+        Ok(bytes.iter().fold(0, |acc, &x| acc << 8 | x as u64))
+    }
+
+    // リトルエンディアンの符号なし整数
+    fn le(&mut self, size: u64) -> Decoded<u64> {
+        let bytes = self.take(size)?;
+        Ok(bytes.iter().rev().fold(0, |acc, &x| acc << 8 | x as u64))
+    }
+
+    // 子を読む前に入れ子の深さを確かめる
+    fn enter(&mut self, start: usize) -> Decoded<()> {
+        match self.depth < MAX_DEPTH {
+            true => {
+                self.depth += 1;
+                Ok(())
+            }
+            false => Err(self.fail(start, "Nested too deeply")),
+        }
+    }
+
+    fn leaf(&self, out: &mut Vec<Field>, name: String, start: usize, value: String) {
+        out.push(Field::new(name, start..self.pos, value));
+    }
+
+    // 子を読み終えたところ(失敗した場合はその位置)までを1項目にする
+    fn nest(
+        &mut self,
+        out: &mut Vec<Field>,
+        name: String,
+        start: usize,
+        value: String,
+        children: Vec<Field>,
+    ) {
+        self.depth -= 1;
+        let mut field = Field::new(name, start..self.pos, value);
+        field.children = children;
+        out.push(field);
+    }
+
+    // count 個(None は終端の 0xFF まで)の項目
+    // pairs の場合はキーと値の組
+    fn items(
+        &mut self,
+        item: Item<'a>,
+        count: Option<u64>,
+        pairs: bool,
+        out: &mut Vec<Field>,
+    ) -> Decoded<u64> {
+        let mut i = 0;
+        loop {
+            match count {
+                Some(count) if i == count => break,
+                None if self.peek()? == 0xFF => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => {}
+            }
+            match pairs {
+                true => self.pair(item, out)?,
+                false => item(self, format!("[{i}]"), out)?,
+            }
+            i += 1;
+        }
+        Ok(i)
+    }
+
+    // キーと値の組(値の項目にキーの名前を付ける)
+    fn pair(&mut self, item: Item<'a>, out: &mut Vec<Field>) -> Decoded<()> {
+        let start = self.pos;
+        let mut key = Vec::new();
+        if let Err(e) = item(self, String::from("key"), &mut key) {
+            out.append(&mut key);
+            return Err(e);
+        }
+        let name = match key.pop() {
+            Some(key) if key.children.is_empty() => key.value,
+            _ => String::from("key"),
+        };
+        let len = out.len();
+        let result = item(self, name, out);
+        if let Some(value) = out.get_mut(len) {
+            value.range.start = start;
+        }
+        result
+    }
+
+    // Protobuf のフィールドを end まで(グループ内では終端まで)
+    pub(super) fn protobuf(&mut self, out: &mut Vec<Field>) -> Decoded<()> {
+        self.protobuf_fields(None, out)
+    }
+
+    fn protobuf_fields(&mut self, group: Option<u64>, out: &mut Vec<Field>) -> Decoded<()> {
+        while self.pos < self.end {
+            let start = self.pos;
+            let key = self.varint()?;
+            let (field, wire) = (key >> 3, key & 7);
+            if field == 0 {
+                return Err(self.fail(start, "Field number 0"));
+            }
+            match wire {
+                0 => {
+                    let value = self.varint()?;
+                    let text = match value as i64 {
+                        x if x < 0 => format!("{} ({x})", number(value)),
+                        _ => number(value),
+                    };
+                    self.leaf(out, format!("#{field} varint"), start, text);
+                }
+                1 => {
+                    let value = self.le(8)?;
+                    let text = format!("0x{value:016X} (f64 {})", f64::from_bits(value));
+                    self.leaf(out, format!("#{field} i64"), start, text);
+                }
+                2 => {
+                    let len = self.varint()?;
+                    let data = self.pos;
+                    let bytes = self.take(len)?;
+                    let name = format!("#{field} len");
+                    // 文字列でなければ入れ子のメッセージとして読んでみる
+                    let nested = !bytes.is_empty() && !is_printable(bytes);
+                    let message = (nested && self.depth < MAX_DEPTH)
+                        .then(|| {
+                            let mut inner = Decoder::new(self.buf, data, self.pos);
+                            inner.depth = self.depth + 1;
+                            let mut children = Vec::new();
+                            let result = inner.protobuf_fields(None, &mut children);
+                            result.ok().map(|_| children)
+                        })
+                        .flatten();
+                    match (message, text(bytes)) {
+                        (Some(children), _) => {
+                            self.depth += 1;
+                            let value = format!("message ({len} bytes)");
+                            self.nest(out, name, start, value, children);
+                        }
+                        (None, Some(text)) => self.leaf(out, name, start, text),
+                        (None, None) => self.leaf(out, name, start, preview(bytes)),
+                    }
+                }
+                3 => {
+                    self.enter(start)?;
+                    let mut children = Vec::new();
+                    let result = self.protobuf_fields(Some(field), &mut children);
+                    let name = format!("#{field} group");
+                    self.nest(out, name, start, String::new(), children);
+                    result?;
+                }
+                4 if group == Some(field) => return Ok(()),
+                4 => return Err(self.fail(start, format!("Unexpected end of group #{field}"))),
+                5 => {
+                    let value = self.le(4)?;
+                    let text = format!("0x{value:08X} (f32 {})", f32::from_bits(value as u32));
+                    self.leaf(out, format!("#{field} i32"), start, text);
+                }
+                _ => return Err(self.fail(start, format!("Invalid wire type {wire}"))),
+            }
+        }
+        match group {
+            Some(field) => Err(self.fail(self.pos, format!("Group #{field} is not closed"))),
+            None => Ok(()),
+        }
+    }
+
+    // Protobuf の可変長整数
+    fn varint(&mut self) -> Decoded<u64> {
+        let start = self.pos;
+        let mut value = 0u64;
+        for i in 0..10 {
+            let x = self.byte()?;
+            value |= ((x & 0x7F) as u64) << (i * 7);
+            if x & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.fail(start, "Varint is longer than 10 bytes"))
+    }
+
+    // CBOR のデータ項目
+    pub(super) fn cbor(&mut self, name: String, out: &mut Vec<Field>) -> Decoded<()> {
+        let start = self.pos;
+        let initial = self.byte()?;
+        let (major, info) = (initial >> 5, initial & 0x1F);
+        let argument = match info {
+            0..=23 => Some(info as u64),
+            24..=27 => Some(self.be(1 << (info - 24))?),
+            31 if matches!(major, 2..=5) => None,
+            31 if major == 7 => return Err(self.fail(start, "Unexpected break")),
+            _ => return Err(self.fail(start, format!("Invalid additional information {info}"))),
+        };
+        match (major, argument) {
+            (0, Some(value)) => self.leaf(out, name, start, number(value)),
+            (1, Some(value)) => {
+                let text = format!("-{}", value as u128 + 1);
+                self.leaf(out, name, start, text);
+            }
+            (2 | 3, Some(len)) => {
+                let bytes = self.take(len)?;
+                let text = match major {
+                    2 => preview(bytes),
+                    _ => text(bytes).ok_or_else(|| self.fail(start, "Invalid UTF-8 text"))?,
+                };
+                self.leaf(out, name, start, text);
+            }
+            // 長さ不定の文字列は同じ種類の断片の並び
+            (2 | 3, None) => {
+                self.enter(start)?;
+                let mut children = Vec::new();
+                let result = self.items(Self::cbor, None, false, &mut children);
+                let value = match &result {
+                    Ok(count) => format!("{count} chunks"),
+                    Err(_) => String::new(),
+                };
+                self.nest(out, name, start, value, children);
+                result?;
+            }
+            (4 | 5, count) => {
+                self.enter(start)?;
+                let mut children = Vec::new();
+                let result = self.items(Self::cbor, count, major == 5, &mut children);
+                let value = match (&result, major) {
+                    (Ok(count), 4) => format!("array ({count})"),
+                    (Ok(count), _) => format!("map ({count})"),
+                    _ => String::new(),
+                };
+                self.nest(out, name, start, value, children);
+                result?;
+            }
+            // タグは続く項目の値に付けて表示する
+            (6, Some(tag)) => {
+                let len = out.len();
+                let result = self.cbor(name, out);
+                if let Some(field) = out.get_mut(len) {
+                    field.range.start = start;
+                    field.value = format!("tag {tag}: {}", field.value);
+                }
+                result?;
+            }
+            (7, Some(value)) => {
+                let text = match (info, value) {
+                    (20, _) => String::from("false"),
+                    (21, _) => String::from("true"),
+                    (22, _) => String::from("null"),
+                    (23, _) => String::from("undefined"),
+                    (25, x) => half(x as u16).to_string(),
+                    (26, x) => f32::from_bits(x as u32).to_string(),
+                    (27, x) => f64::from_bits(x).to_string(),
+                    (_, x) => format!("simple({x})"),
+                };
+                self.leaf(out, name, start, text);
+            }
+            _ => return Err(self.fail(start, "Invalid data item")),
+        }
+        Ok(())
+    }
+
+    // MessagePack の値
+    pub(super) fn msgpack(&mut self, name: String, out: &mut Vec<Field>) -> Decoded<()> {
+        let start = self.pos;
+        let marker = self.byte()?;
+        let text = match marker {
+            0x00..=0x7F => number(marker as u64),
+            0xE0..=0xFF => (marker as i8).to_string(),
+            0xC0 => String::from("nil"),
+            0xC2 => String::from("false"),
+            0xC3 => String::from("true"),
+            0xCC..=0xCF => number(self.be(1 << (marker - 0xCC))?),
+            0xD0..=0xD3 => {
+                let size = 1 << (marker - 0xD0);
+                let shift = 64 - size * 8;
+                ((self.be(size)? << shift) as i64 >> shift).to_string()
+            }
+            0xCA => f32::from_bits(self.be(4)? as u32).to_string(),
+            0xCB => f64::from_bits(self.be(8)?).to_string(),
+            0xA0..=0xBF | 0xD9..=0xDB => {
+                let len = match marker {
+                    0xA0..=0xBF => (marker & 0x1F) as u64,
+                    _ => self.be(1 << (marker - 0xD9))?,
+                };
+                let bytes = self.take(len)?;
+                text(bytes).ok_or_else(|| self.fail(start, "Invalid UTF-8 string"))?
+            }
+            0xC4..=0xC6 => {
+                let len = self.be(1 << (marker - 0xC4))?;
+                preview(self.take(len)?)
+            }
+            0xC7..=0xC9 | 0xD4..=0xD8 => {
+                let len = match marker {
+                    0xC7..=0xC9 => self.be(1 << (marker - 0xC7))?,
+                    _ => 1 << (marker - 0xD4),
+                };
+                let kind = self.byte()? as i8;
+                format!("ext {kind}: {}", preview(self.take(len)?))
+            }
+            0x80..=0x9F | 0xDC..=0xDF => {
+                let (count, pairs) = match marker {
+                    0x80..=0x8F => ((marker & 0x0F) as u64, true),
+                    0x90..=0x9F => ((marker & 0x0F) as u64, false),
+                    0xDC | 0xDD => (self.be(2 << (marker - 0xDC))?, false),
+                    _ => (self.be(2 << (marker - 0xDE))?, true),
+                };
+                self.enter(start)?;
+                let mut children = Vec::new();
+                let result = self.items(Self::msgpack, Some(count), pairs, &mut children);
+                let value = match pairs {
+                    true => format!("map ({count})"),
+                    false => format!("array ({count})"),
+                };
+                self.nest(out, name, start, value, children);
+                return result.map(|_| ());
+            }
+            _ => return Err(self.fail(start, format!("Invalid marker 0x{marker:02X}"))),
+        };
+        self.leaf(out, name, start, text);
+        Ok(())
+    }
+
+    // BSON の文書(配列も同じ形)
+    pub(super) fn bson(&mut self, name: String, out: &mut Vec<Field>) -> Decoded<()> {
+        let start = self.pos;
+        let len = self.le(4)?;
+        let end = usize::try_from(len)
+            .ok()
+            .filter(|&x| x >= 5)
+            .and_then(|x| start.checked_add(x))
+            .filter(|&x| x <= self.end)
+            .ok_or_else(|| self.fail(start, format!("Invalid document length {len}")))?;
+        self.enter(start)?;
+        let mut children = Vec::new();
+        let outer = std::mem::replace(&mut self.end, end);
+        let result = self.bson_elements(&mut children);
+        self.end = outer;
+        let value = format!("document ({} elements)", children.len());
+        self.nest(out, name, start, value, children);
+        result?;
+        match self.pos == end {
+            true => Ok(()),
+            false => Err(self.fail(self.pos, format!("Document ends before its length {len}"))),
+        }
+    }
+
+    fn bson_elements(&mut self, out: &mut Vec<Field>) -> Decoded<()> {
+        loop {
+            let start = self.pos;
+            let kind = self.byte()?;
+            if kind == 0 {
+                return Ok(());
+            }
+            let name = self.c_str()?;
+            let text = match kind {
+                0x01 => f64::from_bits(self.le(8)?).to_string(),
+                0x02 | 0x0D | 0x0E => self.bson_string()?,
+                0x03 | 0x04 => {
+                    let len = out.len();
+                    let result = self.bson(name, out);
+                    if let Some(field) = out.get_mut(len) {
+                        field.range.start = start;
+                    }
+                    result?;
+                    continue;
+                }
+                0x05 => {
+                    let len = self.le(4)?;
+                    let subtype = self.byte()?;
+                    format!("binary {subtype}: {}", preview(self.take(len)?))
+                }
+                0x06 => String::from("undefined"),
+                0x07 => hex(self.take(12)?),
+                0x08 => match self.byte()? {
+                    0 => String::from("false"),
+                    1 => String::from("true"),
+                    x => return Err(self.fail(self.pos - 1, format!("Invalid boolean {x}"))),
+                },
+                0x09 => format!("datetime {}", self.le(8)? as i64),
+                0x0A => String::from("null"),
+                0x0B => format!("/{}/{}", self.c_str()?, self.c_str()?),
+                0x0C => format!("{} {}", self.bson_string()?, hex(self.take(12)?)),
+                0x0F => {
+                    self.le(4)?;
+                    let code = self.bson_string()?;
+                    let mut scope = Vec::new();
+                    let result = self.bson(String::from("scope"), &mut scope);
+                    let mut field = Field::new(name, start..self.pos, code);
+                    field.children = scope;
+                    out.push(field);
+                    result?;
+                    continue;
+                }
+                0x10 => (self.le(4)? as i32).to_string(),
+                0x11 => {
+                    let value = self.le(8)?;
+                    format!("timestamp {} #{}", value >> 32, value & 0xFFFF_FFFF)
+                }
+                0x12 => (self.le(8)? as i64).to_string(),
+                0x13 => format!("decimal128 {}", hex(self.take(16)?)),
+                0x7F => String::from("max key"),
+                0xFF => String::from("min key"),
+                _ => return Err(self.fail(start, format!("Unknown element type 0x{kind:02X}"))),
+            };
+            self.leaf(out, name, start, text);
+        }
+    }
+
+    // 長さ付きで NUL 終端の文字列
+    fn bson_string(&mut self) -> Decoded<String> {
+        let start = self.pos;
+        let len = self.le(4)?;
+        let bytes = match len {
+            0 => return Err(self.fail(start, "String length 0")),
+            len => self.take(len)?,
+        };
+        match bytes.split_last() {
+            Some((0, bytes)) => text(bytes).ok_or_else(|| self.fail(start, "Invalid UTF-8 string")),
+            _ => Err(self.fail(start, "String is not NUL-terminated")),
+        }
+    }
+
+    // NUL 終端の名前
+    fn c_str(&mut self) -> Decoded<String> {
+        let start = self.pos;
+        let len = self.buf[start..self.end]
+            .iter()
+            .position(|&x| x == 0)
+            .ok_or_else(|| self.fail(start, "Name is not NUL-terminated"))?;
+        let bytes = self.take(len as u64 + 1)?;
+        Ok(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+}
+
+// UTF-8 の文字列を引用符付きで(長い場合は省略)
+fn text(bytes: &[u8]) -> Option<String> {
+    let quoted = format!("{:?}", std::str::from_utf8(bytes).ok()?);
+    match quoted.chars().count() > MAX_TEXT {
+        true => Some(quoted.chars().take(MAX_TEXT).chain(['…']).collect()),
+        false => Some(quoted),
+    }
+}
+
+// 改行とタブ以外の制御文字を含まない
+fn is_printable(bytes: &[u8]) -> bool {
+    std::str::from_utf8(bytes).is_ok_and(|x| {
+        x.chars()
+            .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+    })
+}
+
+// バイト列の長さと先頭の数バイト
+fn preview(bytes: &[u8]) -> String {
+    let mut text = format!("{} bytes", bytes.len());
+    if !bytes.is_empty() {
+        text.push_str(": ");
+        text.push_str(&hex(&bytes[..bytes.len().min(8)]));
+        if bytes.len() > 8 {
+            text.push_str(" …");
+        }
+    }
+    text
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let text: Vec<String> = bytes.iter().map(|x| format!("{x:02X}")).collect();
+    text.join(" ")
+}
+
+// 半精度浮動小数点数
+fn half(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (bits >> 10) & 0x1F;
+    let fraction = (bits & 0x3FF) as f32;
+    sign * match exponent {
+        0 => fraction * 2f32.powi(-24),
+        31 if fraction == 0.0 => f32::INFINITY,
+        31 => f32::NAN,
+        e => (1.0 + fraction / 1024.0) * 2f32.powi(e as i32 - 15),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::format::Codec;
+
+    #[test]
+    fn test_decode() {
+        // {"a": 1, "b": [2, 3]}
+        let cbor = [0xA2, 0x61, 0x61, 0x01, 0x61, 0x62, 0x82, 0x02, 0x03];
+        let (root, failure) = Codec::Cbor.decode(&cbor, 0..cbor.len());
+        assert!(failure.is_none());
+        let map = &root.children[0];
+        assert_eq!(map.value, "map (2)");
+        assert_eq!(map.children[1].name, "\"b\"");
+        assert_eq!(map.children[1].range, 4..9);
+        assert_eq!(map.children[1].children[1].value, "3");
+
+        let msgpack = [0x82, 0xA1, 0x61, 0x01, 0xA1, 0x62, 0x92, 0x02, 0xD0, 0xFE];
+        let (root, failure) = Codec::MessagePack.decode(&msgpack, 0..msgpack.len());
+        assert!(failure.is_none());
+        assert_eq!(root.children[0].children[1].children[1].value, "-2");
+
+        let bson = [12, 0, 0, 0, 0x10, b'a', 0, 1, 0, 0, 0, 0];
+        let (root, failure) = Codec::Bson.decode(&bson, 0..bson.len());
+        assert!(failure.is_none());
+        assert_eq!(root.children[0].children[0].name, "a");
+
+        // 1: 150, 2: {1: 1}, 3: "abc"
+        let protobuf = [
+            0x08, 0x96, 0x01, 0x12, 0x02, 0x08, 0x01, 0x1A, 0x03, b'a', b'b', b'c',
+        ];
+        let (root, failure) = Codec::Protobuf.decode(&protobuf, 0..protobuf.len());
+        assert!(failure.is_none());
+        assert_eq!(root.children[0].value, "150 (0x96)");
+        assert_eq!(root.children[1].children[0].name, "#1 varint");
+        assert_eq!(root.children[2].value, "\"abc\"");
+
+        // 要素が足りない配列は読めたところまで
+        let (root, failure) = Codec::Cbor.decode(&cbor[6..8], 0..2);
+        assert_eq!(
+            failure.as_deref(),
+            Some("failed at 0x2: Needs 1 bytes, 0 left")
+        );
+        assert_eq!(root.children[0].children.len(), 1);
+        assert_eq!(root.error_count(), 1);
+    }
+}
@@ -0,0 +1,564 @@
+// キー割り当て
+
+// crosstermクレート
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+// キー入力で実行する操作
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Action {
+    Quit,
+    Help,
+    Command,
+    MoveLeft,
+    MoveRight,
+    MoveDown,
+    MoveUp,
+    ToggleSelection,
+    ClearSelection,
+    HexInput,
+    Delete,
+    ToggleMode,
+    Save,
+    ToggleEndian,
+    EditValue,
+    TypeText,
+    Search,
+    SearchNext,
+    NextLayout,
+    ToggleInspector,
+    ToggleOverview,
+    ShrinkMain,
+    GrowMain,
+    NextTheme,
+    NextEncoding,
+    PrevEncoding,
+    NextControlDisplay,
+    NextView,
+    NextValueType,
+    NextPixelFormat,
+    NextPalette,
+    NarrowBitmap,
+    WidenBitmap,
+    NextSampleType,
+    TogglePlotStyle,
+    NextChannel,
+    ZoomIn,
+    ZoomOut,
+    Strings,
+    ToggleStructure,
+    FocusStructure,
+    // 文字列一覧と構造パネルの操作
+    SelectNext,
+    SelectPrev,
+    PageDown,
+    PageUp,
+    Filter,
+    DeleteFilter,
+    Jump,
+    Close,
+    Expand,
+    Collapse,
+    ToggleFold,
+    FollowLink,
+    Unfocus,
+}
+
+// ヘルプ表示用の分類
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Category {
+    General,
+    Cursor,
+    Edit,
+    Search,
+    View,
+    Strings,
+    Structure,
+}
+
+impl Category {
+    pub(crate) const ALL: [Category; 7] = [
+        Category::General,
+        Category::Cursor,
+        Category::Edit,
+        Category::Search,
+        Category::View,
+        Category::Strings,
+        Category::Structure,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Category::General => "General",
+            Category::Cursor => "Cursor",
+            Category::Edit => "Edit",
+            Category::Search => "Search",
+            Category::View => "View",
+            Category::Strings => "Strings list",
+            Category::Structure => "Structure panel",
+        }
+    }
+
+    // 一覧やパネルの表示中だけ使う分類
+    fn is_panel(self) -> bool {
+        matches!(self, Category::Strings | Category::Structure)
+    }
+}
+
+// キーの指定
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Key {
+    // 修飾キーなし(英字は大文字小文字を区別しない)
+    Plain(KeyCode),
+    // Ctrlとの組み合わせ
+    Ctrl(char),
+    // 16進数の数字
+    HexDigit,
+    // 修飾キーなしの文字すべて
+    Printable,
+}
+
+impl Key {
+    // キーイベントに一致するか
+    fn matches(&self, key_event: &KeyEvent) -> bool {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match (self, key_event.code) {
+            (Key::Ctrl(c), KeyCode::Char(x)) => ctrl && c.eq_ignore_ascii_case(&x),
+            (Key::HexDigit, KeyCode::Char(x)) => !ctrl && x.is_ascii_hexdigit(),
+            (Key::Printable, KeyCode::Char(_)) => !ctrl,
+            (Key::Plain(KeyCode::Char(c)), KeyCode::Char(x)) => !ctrl && c.eq_ignore_ascii_case(&x),
+            (Key::Plain(code), x) => !ctrl && *code == x,
+            _ => false,
+        }
+    }
+
+    // ヘルプ表示用の名前
+    pub(crate) fn label(&self) -> String {
+        match self {
+            Key::Plain(KeyCode::Char(' ')) => String::from("Space"),
+            Key::Plain(KeyCode::Char(c)) => c.to_string(),
+            Key::Plain(KeyCode::F(n)) => format!("F{n}"),
+            Key::Plain(KeyCode::Delete) => String::from("Del"),
+            Key::Plain(KeyCode::Esc) => String::from("Esc"),
+            Key::Plain(KeyCode::Enter) => String::from("Enter"),
+            Key::Plain(KeyCode::Tab) => String::from("Tab"),
+            Key::Plain(KeyCode::Up) => String::from("↑"),
+            Key::Plain(KeyCode::Down) => String::from("↓"),
+            Key::Plain(KeyCode::Left) => String::from("←"),
+            Key::Plain(KeyCode::Right) => String::from("→"),
+            Key::Plain(KeyCode::PageUp) => String::from("PgUp"),
+            Key::Plain(KeyCode::PageDown) => String::from("PgDn"),
+            Key::Plain(KeyCode::Backspace) => String::from("BS"),
+            Key::Plain(code) => format!("{code:?}"),
+            Key::Ctrl(c) => format!("Ctrl+{}", c.to_ascii_uppercase()),
+            Key::HexDigit => String::from("0-9 a-f"),
+            Key::Printable => String::from("other chars"),
+        }
+    }
+}
+
+// キー割り当て1件
+pub(crate) struct KeyBinding {
+    pub(crate) keys: &'static [Key],
+    pub(crate) action: Action,
+    pub(crate) category: Category,
+    pub(crate) description: &'static str,
+}
+
+impl KeyBinding {
+    // ヘルプ表示用のキー名
+    pub(crate) fn label(&self) -> String {
+        self.keys
+            .iter()
+            .map(|x| x.label())
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+// キー割り当て一覧
+// キー入力の処理とヘルプ表示の両方がこの表を使う
+pub(crate) const KEY_BINDINGS: &[KeyBinding] = &[
+    // 全般
+    KeyBinding {
+        keys: &[Key::Ctrl('q')],
+        action: Action::Quit,
+        category: Category::General,
+        description: "Quit",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('?')), Key::Plain(KeyCode::F(1))],
+        action: Action::Help,
+        category: Category::General,
+        description: "Show this help",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('w'))],
+        action: Action::Save,
+        category: Category::General,
+        description: "Save to file",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char(':'))],
+        action: Action::Command,
+        category: Category::General,
+        description:
+            "Command (tbl, enc, ctrl, rsearch, rtbl, bitmap, plot, entropy, strings, template, parse, decode, sector, goto)",
+    },
+    // カーソル
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('h'))],
+        action: Action::MoveLeft,
+        category: Category::Cursor,
+        description: "Move left",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('l'))],
+        action: Action::MoveRight,
+        category: Category::Cursor,
+        description: "Move right",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('j'))],
+        action: Action::MoveDown,
+        category: Category::Cursor,
+        description: "Move down",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('k'))],
+        action: Action::MoveUp,
+        category: Category::Cursor,
+        description: "Move up",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('v'))],
+        action: Action::ToggleSelection,
+        category: Category::Cursor,
+        description: "Start / end selection",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Esc)],
+        action: Action::ClearSelection,
+        category: Category::Cursor,
+        description: "Clear selection",
+    },
+    // 編集
+    KeyBinding {
+        keys: &[Key::HexDigit],
+        action: Action::HexInput,
+        category: Category::Edit,
+        description: "Input hex digit",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('x')), Key::Plain(KeyCode::Delete)],
+        action: Action::Delete,
+        category: Category::Edit,
+        description: "Delete byte",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('i'))],
+        action: Action::ToggleMode,
+        category: Category::Edit,
+        description: "Toggle overwrite / insert",
+    },
+    KeyBinding {
+        keys: &[Key::Ctrl('e')],
+        action: Action::ToggleEndian,
+        category: Category::Edit,
+        description: "Toggle endianness",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Enter)],
+        action: Action::EditValue,
+        category: Category::Edit,
+        description: "Edit value (typed view)",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('"'))],
+        action: Action::TypeText,
+        category: Category::Edit,
+        description: "Type text in current encoding",
+    },
+    // 検索
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('/'))],
+        action: Action::Search,
+        category: Category::Search,
+        description: "Search text in current encoding",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('n'))],
+        action: Action::SearchNext,
+        category: Category::Search,
+        description: "Next match",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::F(8))],
+        action: Action::Strings,
+        category: Category::Search,
+        description: "Strings list",
+    },
+    // 表示
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::F(2))],
+        action: Action::NextLayout,
+        category: Category::View,
+        description: "Next layout preset",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::F(3))],
+        action: Action::ToggleInspector,
+        category: Category::View,
+        description: "Toggle inspector",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::F(4))],
+        action: Action::ToggleOverview,
+        category: Category::View,
+        description: "Toggle overview strip",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('['))],
+        action: Action::ShrinkMain,
+        category: Category::View,
+        description: "Shrink main pane",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char(']'))],
+        action: Action::GrowMain,
+        category: Category::View,
+        description: "Grow main pane",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::F(5))],
+        action: Action::NextTheme,
+        category: Category::View,
+        description: "Next color theme",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('t'))],
+        action: Action::NextEncoding,
+        category: Category::View,
+        description: "Next text encoding",
+    },
+    KeyBinding {
+        keys: &[Key::Ctrl('t')],
+        action: Action::PrevEncoding,
+        category: Category::View,
+        description: "Previous text encoding",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::F(6))],
+        action: Action::NextControlDisplay,
+        category: Category::View,
+        description: "Next control character display",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::F(7))],
+        action: Action::NextView,
+        category: Category::View,
+        description: "Next main view (hex / typed / bitmap / plot / analysis)",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('u'))],
+        action: Action::NextValueType,
+        category: Category::View,
+        description: "Next value type (typed view)",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('p'))],
+        action: Action::NextPixelFormat,
+        category: Category::View,
+        description: "Next pixel format (bitmap view)",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('o'))],
+        action: Action::NextPalette,
+        category: Category::View,
+        description: "Next palette (bitmap view)",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char(','))],
+        action: Action::NarrowBitmap,
+        category: Category::View,
+        description: "Bitmap width -1",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('.'))],
+        action: Action::WidenBitmap,
+        category: Category::View,
+        description: "Bitmap width +1",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('s'))],
+        action: Action::NextSampleType,
+        category: Category::View,
+        description: "Next sample type (plot view)",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('m'))],
+        action: Action::TogglePlotStyle,
+        category: Category::View,
+        description: "Toggle line / bar (plot view)",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('y'))],
+        action: Action::NextChannel,
+        category: Category::View,
+        description: "Next channel (plot view)",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('+'))],
+        action: Action::ZoomIn,
+        category: Category::View,
+        description: "Zoom in (plot view)",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('-'))],
+        action: Action::ZoomOut,
+        category: Category::View,
+        description: "Zoom out (plot view)",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::F(9))],
+        action: Action::ToggleStructure,
+        category: Category::View,
+        description: "Show / hide structure panel",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Tab)],
+        action: Action::FocusStructure,
+        category: Category::View,
+        description: "Focus structure panel",
+    },
+    // 文字列一覧
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Down)],
+        action: Action::SelectNext,
+        category: Category::Strings,
+        description: "Next string",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Up)],
+        action: Action::SelectPrev,
+        category: Category::Strings,
+        description: "Previous string",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::PageDown)],
+        action: Action::PageDown,
+        category: Category::Strings,
+        description: "Page down",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::PageUp)],
+        action: Action::PageUp,
+        category: Category::Strings,
+        description: "Page up",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Enter)],
+        action: Action::Jump,
+        category: Category::Strings,
+        description: "Jump to string and close",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Esc)],
+        action: Action::Close,
+        category: Category::Strings,
+        description: "Close",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Backspace)],
+        action: Action::DeleteFilter,
+        category: Category::Strings,
+        description: "Delete filter character",
+    },
+    KeyBinding {
+        keys: &[Key::Printable],
+        action: Action::Filter,
+        category: Category::Strings,
+        description: "Type filter",
+    },
+    // 構造パネル
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Down), Key::Plain(KeyCode::Char('j'))],
+        action: Action::SelectNext,
+        category: Category::Structure,
+        description: "Next field",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Up), Key::Plain(KeyCode::Char('k'))],
+        action: Action::SelectPrev,
+        category: Category::Structure,
+        description: "Previous field",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::PageDown)],
+        action: Action::PageDown,
+        category: Category::Structure,
+        description: "Page down",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::PageUp)],
+        action: Action::PageUp,
+        category: Category::Structure,
+        description: "Page up",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Right), Key::Plain(KeyCode::Char('l'))],
+        action: Action::Expand,
+        category: Category::Structure,
+        description: "Expand field",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Left), Key::Plain(KeyCode::Char('h'))],
+        action: Action::Collapse,
+        category: Category::Structure,
+        description: "Collapse field",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Enter), Key::Plain(KeyCode::Char(' '))],
+        action: Action::ToggleFold,
+        category: Category::Structure,
+        description: "Expand / collapse field",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Char('g'))],
+        action: Action::FollowLink,
+        category: Category::Structure,
+        description: "Go to linked offset",
+    },
+    KeyBinding {
+        keys: &[Key::Plain(KeyCode::Tab), Key::Plain(KeyCode::Esc)],
+        action: Action::Unfocus,
+        category: Category::Structure,
+        description: "Back to hex panel",
+    },
+];
+
+// キーイベントから操作を探す(一覧やパネルの操作は除く)
+pub(crate) fn lookup(key_event: &KeyEvent) -> Option<Action> {
+    find(key_event, |x| !x.is_panel())
+}
+
+// 一覧やパネルの表示中にその分類の操作を探す
+pub(crate) fn lookup_in(key_event: &KeyEvent, category: Category) -> Option<Action> {
+    find(key_event, |x| x == category)
+}
+
+fn find(key_event: &KeyEvent, filter: impl Fn(Category) -> bool) -> Option<Action> {
+    KEY_BINDINGS
+        .iter()
+        .filter(|x| filter(x.category))
+        .find(|x| x.keys.iter().any(|key| key.matches(key_event)))
+        .map(|x| x.action)
+}
+
+// 操作に割り当てられたキー名
+pub(crate) fn label_of(action: Action) -> String {
+    KEY_BINDINGS
+        .iter()
+        .find(|x| x.action == action)
+        .map(|x| x.label())
+        .unwrap_or_default()
+}
@@ -1,23 +1,44 @@
 // モジュールファイルの読み込み
+mod analysis;
 mod constants;
 mod event_handler;
+mod format;
+mod keymap;
 mod message;
+mod settings;
+mod structure;
+mod table;
+mod template;
+mod theme;
 mod tui;
+mod value;
 
 // イベントハンドラ
 use crate::event_handler::EventHandler;
 // TUI関連
 use crate::tui::{render_main, render_prep};
 // 状態管理
-use crate::message::Message;
+use crate::message::{Message, TextEncoding};
+// 文字テーブル
+use crate::table::CharTable;
 // clap v4
 use clap::Parser;
+// crosstermクレート
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
     /// Path to file
     file: Option<String>,
+
+    /// Text pane encoding (e.g. utf-8, sjis, cp037, cp1047, cp437, petscii, iso-8859-5)
+    #[arg(short, long)]
+    encoding: Option<TextEncoding>,
+
+    /// Character table file (.tbl) for the text pane
+    #[arg(short, long)]
+    table: Option<String>,
 }
 
 // main 関数s
@@ -27,6 +48,18 @@ fn main() {
 
     // 引数からファイルの読み込み
     let args = Args::parse();
+    if let Some(encoding) = args.encoding {
+        *message.text_encoding_mut() = encoding;
+    }
+    if let Some(path) = args.table {
+        match CharTable::load(&path) {
+            Ok(table) => message.set_char_table(table),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(-1)
+            }
+        }
+    }
     match args.file {
         // 引数がある場合
         Some(path) => {
@@ -49,6 +82,8 @@ fn main() {
 
     // 画面初期化
     let mut terminal = ratatui::init();
+    // マウス入力を有効化
+    let _ = crossterm::execute!(std::io::stdout(), EnableMouseCapture);
 
     // イベントハンドラ
     let mut event_handler: EventHandler = EventHandler::new();
@@ -64,5 +99,6 @@ fn main() {
     }
 
     // 画面復旧
+    let _ = crossterm::execute!(std::io::stdout(), DisableMouseCapture);
     ratatui::restore();
 }
@@ -5,35 +5,90 @@ use ratatui::prelude::Rect;
 
 use std::cell::Cell;
 use std::cell::RefCell;
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::fmt;
 use std::io::{Read, Write};
-use std::rc::Rc;
+use std::ops::{Range, RangeInclusive};
+use std::str::FromStr;
 
+// データ解析
+use crate::analysis::{find_strings, FoundString, StringKind};
 // 定数
 use crate::constants;
+// ファイル形式の解析
+use crate::format::AddressMap;
+// 設定
+use crate::settings::{LayoutPreset, Settings};
+// 構造の木
+use crate::structure::Field;
+// 文字テーブル
+use crate::table::CharTable;
+// テーマ
+use crate::theme::Theme;
+// 型付きの値
+use crate::value::ValueType;
 
 // 状態管理
 pub(crate) struct Message {
     bin_data: BinData,
     cursor: CursorPosition,
     scroll: Scroll,
+    selection: Selection,
     write_mode: WriteMode,
+    endian: Endian,
+    text_encoding: TextEncoding,
+    control_display: ControlDisplay,
+    main_view: MainView,
+    value_type: ValueType,
+    bitmap: Bitmap,
+    plot: Plot,
+    entropy_window: usize,
+    sectors: Option<Sectors>,
     current_file: CurrentFile,
     notice: Notice,
-    layout: [Rc<[Rect]>; 4], // main_layout, sub_layout, inner_main, inner_sub
+    help: Help,
+    strings: StringsPanel,
+    structure: StructurePanel,
+    address_maps: Vec<AddressMap>,
+    prompt: Option<Prompt>,
+    char_table: Option<CharTable>,
+    search: Option<Search>,
+    settings: Settings,
+    theme: Theme,
+    layout: ScreenLayout,
 }
 
 impl Message {
     pub(crate) fn new() -> Self {
+        let settings = Settings::load();
+        let theme = Theme::load(settings.theme(), settings.styles());
         Self {
             bin_data: BinData::new(),
             cursor: CursorPosition::new(),
             scroll: Scroll::new(),
+            selection: Selection::new(),
             write_mode: WriteMode::OverWrite,
+            endian: Endian::Little,
+            text_encoding: TextEncoding::Ascii,
+            control_display: ControlDisplay::Dot,
+            main_view: MainView::Hex,
+            value_type: ValueType::U16,
+            bitmap: Bitmap::new(),
+            plot: Plot::new(),
+            entropy_window: 256,
+            sectors: None,
             current_file: CurrentFile::new(),
             notice: Notice::new(),
-            layout: Default::default(),
+            help: Help::new(),
+            strings: StringsPanel::new(),
+            structure: StructurePanel::new(),
+            address_maps: Vec::new(),
+            prompt: None,
+            char_table: None,
+            search: None,
+            settings,
+            theme,
+            layout: ScreenLayout::default(),
         }
     }
 
@@ -61,6 +116,173 @@ impl Message {
         &mut self.scroll
     }
 
+    pub(crate) fn selection_mut(&mut self) -> &mut Selection {
+        &mut self.selection
+    }
+
+    // 選択範囲(カーソル位置までを含む)
+    pub(crate) fn selected_range(&self) -> Option<RangeInclusive<usize>> {
+        self.selection
+            .range(self.cursor.index(), self.bin_data.buf().len())
+    }
+
+    pub(crate) fn endian(&self) -> &Endian {
+        &self.endian
+    }
+
+    // エンディアン切り替え
+    pub(crate) fn toggle_endian(&mut self) -> &Endian {
+        use Endian::*;
+        self.endian = match self.endian {
+            Little => Big,
+            Big => Little,
+        };
+        &self.endian
+    }
+
+    pub(crate) fn text_encoding(&self) -> &TextEncoding {
+        &self.text_encoding
+    }
+
+    // テキストパネルの文字コード切り替え
+    pub(crate) fn next_text_encoding(&mut self) -> &TextEncoding {
+        self.text_encoding = self.text_encoding.next();
+        &self.text_encoding
+    }
+
+    pub(crate) fn prev_text_encoding(&mut self) -> &TextEncoding {
+        self.text_encoding = self.text_encoding.prev();
+        &self.text_encoding
+    }
+
+    pub(crate) fn text_encoding_mut(&mut self) -> &mut TextEncoding {
+        &mut self.text_encoding
+    }
+
+    pub(crate) fn control_display(&self) -> &ControlDisplay {
+        &self.control_display
+    }
+
+    pub(crate) fn control_display_mut(&mut self) -> &mut ControlDisplay {
+        &mut self.control_display
+    }
+
+    // 制御文字の表示方法切り替え
+    pub(crate) fn next_control_display(&mut self) -> &ControlDisplay {
+        self.control_display = self.control_display.next();
+        &self.control_display
+    }
+
+    pub(crate) fn main_view(&self) -> &MainView {
+        &self.main_view
+    }
+
+    // メインパネルの表示切り替え
+    // 型付き表示ではカーソルを要素の先頭に揃える
+    pub(crate) fn next_main_view(&mut self) -> &MainView {
+        self.main_view = self.main_view.next();
+        self.align_cursor();
+        &self.main_view
+    }
+
+    // メインパネルの表示を指定
+    pub(crate) fn set_main_view(&mut self, view: MainView) {
+        self.main_view = view;
+        self.align_cursor();
+    }
+
+    pub(crate) fn value_type(&self) -> &ValueType {
+        &self.value_type
+    }
+
+    // 型付き表示の型切り替え
+    pub(crate) fn next_value_type(&mut self) -> &ValueType {
+        self.value_type = self.value_type.next();
+        self.align_cursor();
+        &self.value_type
+    }
+
+    pub(crate) fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    pub(crate) fn bitmap_mut(&mut self) -> &mut Bitmap {
+        &mut self.bitmap
+    }
+
+    pub(crate) fn plot(&self) -> &Plot {
+        &self.plot
+    }
+
+    pub(crate) fn plot_mut(&mut self) -> &mut Plot {
+        &mut self.plot
+    }
+
+    // エントロピーを求める窓のバイト数
+    pub(crate) fn entropy_window(&self) -> usize {
+        self.entropy_window
+    }
+
+    pub(crate) fn entropy_window_mut(&mut self) -> &mut usize {
+        &mut self.entropy_window
+    }
+
+    // セクタ単位の表示(None は通常の表示)
+    pub(crate) fn sectors(&self) -> Option<Sectors> {
+        self.sectors
+    }
+
+    pub(crate) fn sectors_mut(&mut self) -> &mut Option<Sectors> {
+        &mut self.sectors
+    }
+
+    // カーソル移動の単位
+    pub(crate) fn cursor_step(&self) -> usize {
+        match self.main_view {
+            MainView::Hex | MainView::Analysis => 1,
+            MainView::Typed => self.value_type.size(),
+            MainView::Bitmap => self.bitmap.format.bits().div_ceil(8),
+            MainView::Plot => self.plot.frame_size(),
+        }
+    }
+
+    // カーソル上下移動の単位
+    // 波形表示では1画面分、解析表示ではエントロピーの1桁分
+    pub(crate) fn cursor_row_step(&self) -> usize {
+        match self.main_view {
+            MainView::Hex | MainView::Typed => constants::LINE_LEN,
+            MainView::Bitmap => self.bitmap.stride(),
+            MainView::Plot => {
+                let columns = (self.layout.main.area.width as usize).max(1);
+                self.plot.frame_size() * self.plot.zoom() * columns
+            }
+            MainView::Analysis => {
+                let columns = self.layout.entropy.map_or(1, |x| x.width.max(1) as usize);
+                (self.bin_data.buf().len() / columns).max(1)
+            }
+        }
+    }
+
+    // カーソルを移動単位の境界に揃える
+    // 画素表示は画像の先頭を任意のバイトに置けるので揃えない
+    pub(crate) fn align_cursor(&mut self) {
+        let index = self.cursor.index();
+        let len = self.bin_data.buf().len();
+        match self.main_view {
+            MainView::Bitmap => {}
+            // 波形表示はデータの開始位置から数える
+            MainView::Plot => {
+                let offset = self.plot.offset.min(index);
+                let step = self.cursor_step();
+                self.cursor.jump_to(index - (index - offset) % step, len);
+            }
+            _ => {
+                let step = self.cursor_step();
+                self.cursor.jump_to(index - index % step, len);
+            }
+        }
+    }
+
     pub(crate) fn write_mode(&self) -> &WriteMode {
         &self.write_mode
     }
@@ -92,27 +314,179 @@ impl Message {
         &mut self.notice
     }
 
-    pub(crate) fn layout(&self) -> &[Rc<[Rect]>; 4] {
+    pub(crate) fn help(&self) -> &Help {
+        &self.help
+    }
+
+    pub(crate) fn help_mut(&mut self) -> &mut Help {
+        &mut self.help
+    }
+
+    pub(crate) fn strings(&self) -> &StringsPanel {
+        &self.strings
+    }
+
+    pub(crate) fn strings_mut(&mut self) -> &mut StringsPanel {
+        &mut self.strings
+    }
+
+    // 文字列一覧を探し直して表示
+    pub(crate) fn show_strings(&mut self) {
+        self.strings.show(self.bin_data.buf());
+    }
+
+    pub(crate) fn structure(&self) -> &StructurePanel {
+        &self.structure
+    }
+
+    pub(crate) fn structure_mut(&mut self) -> &mut StructurePanel {
+        &mut self.structure
+    }
+
+    pub(crate) fn address_maps(&self) -> &[AddressMap] {
+        &self.address_maps
+    }
+
+    pub(crate) fn address_maps_mut(&mut self) -> &mut Vec<AddressMap> {
+        &mut self.address_maps
+    }
+
+    // 構造の選択中の項目の先頭へカーソルを移動
+    pub(crate) fn jump_to_field(&mut self) {
+        if let Some(field) = self.structure.selected_field() {
+            let index = field.range.start;
+            let len = self.bin_data.buf().len();
+            self.cursor.jump_to(index, len);
+        }
+    }
+
+    // 構造の選択中の項目の参照先へカーソルを移動
+    // 参照先が無ければ false
+    pub(crate) fn jump_to_link(&mut self) -> bool {
+        match self.structure.selected_field().and_then(|x| x.link) {
+            Some(index) => {
+                let len = self.bin_data.buf().len();
+                self.cursor.jump_to(index, len);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn prompt(&self) -> Option<&Prompt> {
+        self.prompt.as_ref()
+    }
+
+    pub(crate) fn prompt_mut(&mut self) -> &mut Option<Prompt> {
+        &mut self.prompt
+    }
+
+    pub(crate) fn char_table(&self) -> Option<&CharTable> {
+        self.char_table.as_ref()
+    }
+
+    // 文字テーブルを設定し、テキストパネルをテーブル表示にする
+    pub(crate) fn set_char_table(&mut self, table: CharTable) {
+        self.char_table = Some(table);
+        self.text_encoding = TextEncoding::Table;
+    }
+
+    // 直前の検索
+    pub(crate) fn search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+
+    pub(crate) fn search_mut(&mut self) -> &mut Option<Search> {
+        &mut self.search
+    }
+
+    pub(crate) fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub(crate) fn settings_mut(&mut self) -> &mut Settings {
+        &mut self.settings
+    }
+
+    // 設定を保存し、失敗したら通知
+    pub(crate) fn save_settings(&mut self) {
+        if let Err(e) = self.settings.save() {
+            self.notice.add(e.to_string());
+        }
+    }
+
+    pub(crate) fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    // テーマ切り替え
+    // NO_COLOR の指定中は切り替えても変わらないため通知のみ
+    pub(crate) fn next_theme(&mut self) {
+        if Theme::no_color() {
+            self.notice
+                .add(String::from("NO_COLOR is set; the theme stays monochrome"));
+            return;
+        }
+        let name = self.settings.next_theme();
+        self.theme = Theme::load(name, self.settings.styles());
+        self.notice.add(format!("Theme: {name}"));
+        self.save_settings();
+    }
+
+    pub(crate) fn layout(&self) -> &ScreenLayout {
         &self.layout
     }
 
-    pub(crate) fn layout_mut(&mut self) -> &mut [Rc<[Rect]>; 4] {
+    pub(crate) fn layout_mut(&mut self) -> &mut ScreenLayout {
         &mut self.layout
     }
 }
 
+// パネル1つ分の領域
+#[derive(Default, Clone, Copy)]
+pub(crate) struct Pane {
+    pub(crate) panel: Rect,
+    pub(crate) header: Rect,
+    pub(crate) area: Rect,
+}
+
+// 画面レイアウト
+#[derive(Default)]
+pub(crate) struct ScreenLayout {
+    // 実際に使われているプリセット(狭い端末では縮退)
+    pub(crate) preset: Option<LayoutPreset>,
+    pub(crate) main: Pane,
+    // ステータスライン
+    pub(crate) status: Rect,
+    pub(crate) text: Option<Pane>,
+    pub(crate) inspector: Option<Rect>,
+    // 全体俯瞰ストリップ
+    pub(crate) overview: Option<Rect>,
+    // 解析表示のエントロピーのグラフ
+    pub(crate) entropy: Option<Rect>,
+    // 構造パネル
+    pub(crate) structure: Option<Rect>,
+}
+
 // 編集用構造体
 pub(crate) struct BinData {
     buf: VecDeque<u8>,
+    modified: bool,
 }
 
 impl BinData {
     pub(crate) fn new() -> Self {
         BinData {
             buf: VecDeque::new(),
+            modified: false,
         }
     }
 
+    // 読み込み・保存後に変更されたか
+    pub(crate) fn is_modified(&self) -> bool {
+        self.modified
+    }
+
     // データ追加
     pub(crate) fn push_back(&mut self, new_buf: Vec<u8>) {
         let mut new_data: VecDeque<u8> = VecDeque::from(new_buf);
@@ -125,6 +499,7 @@ impl BinData {
         // self.buf.make_contiguous();
         self.buf.insert(index, value);
         self.buf.make_contiguous();
+        self.modified = true;
     }
 
     // データ削除
@@ -132,15 +507,48 @@ impl BinData {
         self.buf.make_contiguous();
         if self.buf.len() > 1 {
             self.buf.remove(index);
+            self.modified = true;
             // self.buf.make_contiguous();
         }
     }
 
+    // 先頭から検索
+    // start から後ろを探し、見つからなければ先頭から start まで探す
+    pub(crate) fn find(&self, pattern: &[u8], start: usize) -> Option<usize> {
+        if pattern.is_empty() {
+            return None;
+        }
+        let buf = self.buf();
+        let find_in = |from: usize, to: usize| {
+            let to = (to + pattern.len() - 1).min(buf.len());
+            buf.get(from..to)?
+                .windows(pattern.len())
+                .position(|x| x == pattern)
+                .map(|x| from + x)
+        };
+        find_in(start, buf.len()).or_else(|| find_in(0, start))
+    }
+
+    // index からバイト列を上書きする
+    // 末尾を超える分は追加する
+    pub(crate) fn overwrite(&mut self, index: usize, bytes: &[u8]) {
+        for (k, &x) in bytes.iter().enumerate() {
+            if index + k < self.buf.len() {
+                self.update(index + k, x);
+            } else {
+                self.insert(index + k, x);
+            }
+        }
+    }
+
     // データ上書き
     pub(crate) fn update(&mut self, index: usize, value: u8) {
         self.buf.make_contiguous();
         if let Some(elem) = self.buf.get_mut(index) {
-            *elem = value;
+            if *elem != value {
+                *elem = value;
+                self.modified = true;
+            }
         }
     }
 
@@ -157,14 +565,16 @@ impl BinData {
         let _ = file.read_to_end(&mut tmp_buf)?;
         self.buf.clear();
         self.push_back(tmp_buf);
+        self.modified = false;
 
         Ok(())
     }
 
     // ファイルへ書き込み
-    pub(crate) fn export_to(&self, path: &String) -> Result<(), std::io::Error> {
+    pub(crate) fn export_to(&mut self, path: &String) -> Result<(), std::io::Error> {
         let mut file = std::fs::File::create(path)?;
         file.write_all(self.buf())?;
+        self.modified = false;
 
         Ok(())
     }
@@ -175,6 +585,7 @@ impl From<Vec<u8>> for BinData {
     fn from(buf: Vec<u8>) -> Self {
         BinData {
             buf: VecDeque::from(buf),
+            modified: false,
         }
     }
 }
@@ -194,16 +605,13 @@ pub(crate) struct CursorPosition {
 
 impl CursorPosition {
     const STEP: usize = 3;
-    const ORIGIN_X: u16 = 10;
-    const ORIGIN_Y: u16 = 2;
+    // 表示領域左端から16進数データまでの幅
+    const OFFSET_X: u16 = 9;
 
     pub(crate) fn new() -> Self {
         let index = 0;
         let input_buf_x = 0;
-        let position = Position {
-            x: Self::ORIGIN_X,
-            y: Self::ORIGIN_Y,
-        };
+        let position = Position { x: 0, y: 0 };
 
         Self {
             index,
@@ -227,13 +635,10 @@ impl CursorPosition {
         &self.position
     }
 
-    // カーソル右移動処理
-    pub(crate) fn move_to_right(&mut self, len: usize) {
-        self.index = self.index.saturating_add(1);
-
-        if self.index > len {
-            self.index = len;
-        }
+    // 指定位置へ移動
+    pub(crate) fn jump_to(&mut self, index: usize, len: usize) {
+        self.index = index.min(len.saturating_sub(1));
+        self.input_buf_x = 0;
     }
 
     // カーソル左移動処理
@@ -241,35 +646,65 @@ impl CursorPosition {
         self.index = self.index.saturating_sub(1);
     }
 
-    // カーソル上移動処理
-    pub(crate) fn move_to_up(&mut self) {
-        self.index = self.index.saturating_sub(constants::LINE_LEN);
+    // 指定バイト数だけ左右に移動
+    pub(crate) fn move_to_left_by(&mut self, step: usize) {
+        self.index = self.index.saturating_sub(step);
     }
 
-    // カーソル下移動処理
-    pub(crate) fn move_to_down(&mut self, len: usize) {
-        self.index = self.index.saturating_add(constants::LINE_LEN);
-        if self.index > len {
-            self.index = len;
-        }
+    pub(crate) fn move_to_right_by(&mut self, step: usize, len: usize) {
+        self.index = self.index.saturating_add(step).min(len);
+    }
+
+    // カーソル上移動処理(step は1行のバイト数)
+    pub(crate) fn move_to_up_by(&mut self, step: usize) {
+        self.index = self.index.saturating_sub(step);
     }
+
+    // カーソル下移動処理(step は1行のバイト数)
+    pub(crate) fn move_to_down_by(&mut self, step: usize, len: usize) {
+        self.index = self.index.saturating_add(step).min(len);
+    }
+    // カーソルのある行
+    pub(crate) fn row(&self) -> usize {
+        self.index / constants::LINE_LEN
+    }
+
     // カーソル位置計算
-    pub(crate) fn calc_position(&mut self) {
-        self.position.x = Self::ORIGIN_X
+    // origin は表示領域の左上、scroll はスクロール済みの行数
+    pub(crate) fn calc_position(&mut self, origin: Position, scroll: usize) {
+        self.position.x = origin.x
+            + Self::OFFSET_X
             + (Self::STEP * (self.index % constants::LINE_LEN) + self.input_buf_x) as u16;
-        self.position.y = Self::ORIGIN_Y + (self.index / constants::LINE_LEN) as u16;
+        self.position.y = origin.y + self.row().saturating_sub(scroll) as u16;
     }
-    // カーソル上限計算
-    pub(crate) fn adjust_y(&mut self, border: u16) {
-        if self.position.y > border {
-            self.position.y = border;
-        }
+
+    // 型付き表示でのカーソル位置計算
+    // 要素の右端(1の位)に置く
+    pub(crate) fn calc_typed_position(
+        &mut self,
+        origin: Position,
+        scroll: usize,
+        value_type: ValueType,
+    ) {
+        let width = value_type.width();
+        let column = (self.index % constants::LINE_LEN) / value_type.size();
+        self.position.x = origin.x + Self::OFFSET_X + (column * (width + 1) + width - 1) as u16;
+        self.position.y = origin.y + self.row().saturating_sub(scroll) as u16;
+    }
+    // 画素表示のカーソル位置(画像の左上)
+    pub(crate) fn calc_bitmap_position(&mut self, origin: Position) {
+        self.position.x = origin.x + Self::OFFSET_X;
+        self.position.y = origin.y;
+    }
+    // 画面上の位置を直接指定(波形表示)
+    pub(crate) fn set_position(&mut self, position: Position) {
+        self.position = position;
     }
 }
 
 // スクロール量
 pub(crate) struct Scroll {
-    scroll_y: [u16; 2], // main:0, sub0:1
+    scroll_y: [usize; 2], // main:0, sub0:1
 }
 
 impl Scroll {
@@ -278,109 +713,903 @@ impl Scroll {
         Self { scroll_y }
     }
 
-    pub(crate) fn scroll_y(&self) -> &[u16; 2] {
+    pub(crate) fn scroll_y(&self) -> &[usize; 2] {
         &self.scroll_y
     }
-    pub(crate) fn scroll_y_mut(&mut self) -> &mut [u16; 2] {
+    pub(crate) fn scroll_y_mut(&mut self) -> &mut [usize; 2] {
         &mut self.scroll_y
     }
-    // スクロール上限計算
-    pub(crate) fn calc_border(bottom: u16) -> u16 {
-        const SCROLL_Y_BORDER: u16 = 3;
-        bottom.saturating_sub(SCROLL_Y_BORDER)
-    }
     // スクロール計算
-    pub(crate) fn calc_scroll(y: u16, border: u16) -> u16 {
-        if y > border {
-            y - border
-        } else {
-            0
+    // カーソル行が表示領域の下から2行目を越えたらスクロールする
+    pub(crate) fn calc_scroll(row: usize, height: u16) -> usize {
+        const SCROLL_Y_MARGIN: u16 = 2;
+        let border = height.saturating_sub(SCROLL_Y_MARGIN) as usize;
+        row.saturating_sub(border)
+    }
+}
+
+// 範囲選択
+pub(crate) struct Selection {
+    anchor: Option<usize>,
+}
+
+impl Selection {
+    fn new() -> Self {
+        Self { anchor: None }
+    }
+
+    // 選択開始・解除の切り替え
+    pub(crate) fn toggle(&mut self, index: usize) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(index),
+        };
+    }
+
+    // 選択解除
+    pub(crate) fn clear(&mut self) {
+        self.anchor = None;
+    }
+
+    // 開始位置とカーソル位置から範囲を求める
+    // データ末尾を越える分は切り詰める
+    pub(crate) fn range(&self, cursor: usize, len: usize) -> Option<RangeInclusive<usize>> {
+        let anchor = self.anchor?;
+        let last = len.checked_sub(1)?;
+        let start = anchor.min(cursor).min(last);
+        let end = anchor.max(cursor).min(last);
+        Some(start..=end)
+    }
+}
+
+// エンディアン
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Endian {
+    Little,
+    Big,
+}
+
+impl fmt::Display for Endian {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Little => write!(f, "LE"),
+            Self::Big => write!(f, "BE"),
         }
     }
 }
 
-// 書き込みモード
-pub(crate) enum WriteMode {
-    OverWrite,
-    Insert,
+// テキストパネルの文字コード
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum TextEncoding {
+    Ascii,
+    Latin1,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    ShiftJis,
+    EucJp,
+    Ebcdic037,
+    Ebcdic1047,
+    Cp437,
+    Petscii,
+    // ISO-8859の部番号(1部は Latin1)
+    Iso8859(u8),
+    // 読み込んだ文字テーブル(切り替えの対象外)
+    Table,
 }
 
-impl fmt::Display for WriteMode {
+impl TextEncoding {
+    const ALL: [TextEncoding; 25] = [
+        TextEncoding::Ascii,
+        TextEncoding::Latin1,
+        TextEncoding::Utf8,
+        TextEncoding::Utf16Le,
+        TextEncoding::Utf16Be,
+        TextEncoding::ShiftJis,
+        TextEncoding::EucJp,
+        TextEncoding::Ebcdic037,
+        TextEncoding::Ebcdic1047,
+        TextEncoding::Cp437,
+        TextEncoding::Petscii,
+        TextEncoding::Iso8859(2),
+        TextEncoding::Iso8859(3),
+        TextEncoding::Iso8859(4),
+        TextEncoding::Iso8859(5),
+        TextEncoding::Iso8859(6),
+        TextEncoding::Iso8859(7),
+        TextEncoding::Iso8859(8),
+        TextEncoding::Iso8859(9),
+        TextEncoding::Iso8859(10),
+        TextEncoding::Iso8859(11),
+        TextEncoding::Iso8859(13),
+        TextEncoding::Iso8859(14),
+        TextEncoding::Iso8859(15),
+        TextEncoding::Iso8859(16),
+    ];
+
+    // 次の文字コード
+    fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|&x| x == self)
+            .unwrap_or_default();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    // 前の文字コード
+    fn prev(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|&x| x == self)
+            .unwrap_or_default();
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    // 1バイト1文字の文字コードか
+    pub(crate) fn is_single_byte(self) -> bool {
+        !matches!(
+            self,
+            Self::Utf8 | Self::Utf16Le | Self::Utf16Be | Self::ShiftJis | Self::EucJp | Self::Table
+        )
+    }
+}
+
+// コマンドライン引数の文字コード名を解釈する
+// 大文字小文字と "-", "_" は区別しない
+impl FromStr for TextEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name: String = s
+            .chars()
+            .filter(|x| !matches!(x, '-' | '_' | ' '))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        let encoding = match name.as_str() {
+            "ascii" => Self::Ascii,
+            "latin1" | "iso88591" => Self::Latin1,
+            "utf8" => Self::Utf8,
+            "utf16le" => Self::Utf16Le,
+            "utf16be" => Self::Utf16Be,
+            "shiftjis" | "sjis" => Self::ShiftJis,
+            "eucjp" => Self::EucJp,
+            "ebcdic" | "ebcdic037" | "cp037" | "ibm037" => Self::Ebcdic037,
+            "ebcdic1047" | "cp1047" | "ibm1047" => Self::Ebcdic1047,
+            "cp437" | "ibm437" => Self::Cp437,
+            "petscii" => Self::Petscii,
+            _ => name
+                .strip_prefix("iso8859")
+                .and_then(|x| x.parse().ok())
+                .map(Self::Iso8859)
+                .filter(|x| Self::ALL.contains(x))
+                .ok_or_else(|| format!("Unknown encoding: {s}"))?,
+        };
+        Ok(encoding)
+    }
+}
+
+impl fmt::Display for TextEncoding {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::OverWrite => write!(f, " OVR "),
-            Self::Insert => write!(f, " INT "),
+            Self::Ascii => write!(f, "ASCII"),
+            Self::Latin1 => write!(f, "Latin-1"),
+            Self::Utf8 => write!(f, "UTF-8"),
+            Self::Utf16Le => write!(f, "UTF-16LE"),
+            Self::Utf16Be => write!(f, "UTF-16BE"),
+            Self::ShiftJis => write!(f, "Shift_JIS"),
+            Self::EucJp => write!(f, "EUC-JP"),
+            Self::Ebcdic037 => write!(f, "EBCDIC 037"),
+            Self::Ebcdic1047 => write!(f, "EBCDIC 1047"),
+            Self::Cp437 => write!(f, "CP437"),
+            Self::Petscii => write!(f, "PETSCII"),
+            Self::Iso8859(part) => write!(f, "ISO-8859-{part}"),
+            Self::Table => write!(f, "TBL"),
         }
     }
 }
 
-// 編集対象ファイル
-pub(crate) struct CurrentFile {
-    path: String,
+// メインパネルの表示
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum MainView {
+    // 16進数
+    Hex,
+    // 型付きの値の配列
+    Typed,
+    // 画素
+    Bitmap,
+    // 波形
+    Plot,
+    // エントロピーとバイト値の分布
+    Analysis,
 }
 
-// 編集対象ファイル管理
-impl CurrentFile {
-    fn new() -> Self {
-        let path = String::new();
-        Self { path }
+impl MainView {
+    const ALL: [MainView; 5] = [
+        MainView::Hex,
+        MainView::Typed,
+        MainView::Bitmap,
+        MainView::Plot,
+        MainView::Analysis,
+    ];
+
+    // 次の表示
+    fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|&x| x == self)
+            .unwrap_or_default();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for MainView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hex => write!(f, "hex"),
+            Self::Typed => write!(f, "typed"),
+            Self::Bitmap => write!(f, "bitmap"),
+            Self::Plot => write!(f, "plot"),
+            Self::Analysis => write!(f, "analysis"),
+        }
     }
+}
 
-    pub(crate) fn path(&self) -> Option<&String> {
-        if self.path.is_empty() {
-            None
-        } else {
-            Some(&self.path)
+// 画素の形式
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum PixelFormat {
+    // パレット番号(上位ビットが左の画素)
+    Indexed1,
+    Indexed2,
+    Indexed4,
+    Indexed8,
+    // 16bit (R5 G6 B5、エンディアンの設定に従う)
+    Rgb565,
+    // 24bit (R, G, B の順)
+    Rgb888,
+}
+
+impl PixelFormat {
+    const ALL: [PixelFormat; 6] = [
+        PixelFormat::Indexed1,
+        PixelFormat::Indexed2,
+        PixelFormat::Indexed4,
+        PixelFormat::Indexed8,
+        PixelFormat::Rgb565,
+        PixelFormat::Rgb888,
+    ];
+
+    // 1画素のビット数
+    pub(crate) fn bits(self) -> usize {
+        match self {
+            PixelFormat::Indexed1 => 1,
+            PixelFormat::Indexed2 => 2,
+            PixelFormat::Indexed4 => 4,
+            PixelFormat::Indexed8 => 8,
+            PixelFormat::Rgb565 => 16,
+            PixelFormat::Rgb888 => 24,
         }
     }
 
-    pub(crate) fn path_mut(&mut self) -> &mut String {
-        &mut self.path
+    fn name(self) -> &'static str {
+        match self {
+            PixelFormat::Indexed1 => "1",
+            PixelFormat::Indexed2 => "2",
+            PixelFormat::Indexed4 => "4",
+            PixelFormat::Indexed8 => "8",
+            PixelFormat::Rgb565 => "rgb565",
+            PixelFormat::Rgb888 => "rgb888",
+        }
     }
+}
 
-    // ファイル名を返す
-    pub(crate) fn file_name(&self) -> String {
-        let file_name = std::path::Path::new(&self.path)
-            .file_name()
-            .map(|name| name.to_string_lossy().to_string())
-            // 取得できない場合はフルパスを使う
-            .unwrap_or_else(|| self.path.to_string());
+impl fmt::Display for PixelFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rgb565 | Self::Rgb888 => write!(f, "{}", self.name().to_uppercase()),
+            _ => write!(f, "{}bpp", self.name()),
+        }
+    }
+}
 
-        if file_name.is_empty() {
-            String::from("no file")
-        } else {
-            file_name
+// パレット番号の色
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Palette {
+    // 白黒の濃淡
+    Gray,
+    // VGAの16色(8bppは端末の256色)
+    Vga,
+    // 青から赤への変化
+    Heat,
+}
+
+impl Palette {
+    const ALL: [Palette; 3] = [Palette::Gray, Palette::Vga, Palette::Heat];
+
+    fn name(self) -> &'static str {
+        match self {
+            Palette::Gray => "gray",
+            Palette::Vga => "vga",
+            Palette::Heat => "heat",
         }
     }
 }
 
-// ステータス伝達
-pub(crate) struct Notice {
-    count: Cell<u8>,
-    notice: RefCell<VecDeque<String>>,
-    cache: RefCell<String>,
+impl fmt::Display for Palette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
 
-impl Notice {
+// 画素表示の設定
+#[derive(Clone)]
+pub(crate) struct Bitmap {
+    // 1行の画素数
+    pub(crate) width: usize,
+    pub(crate) format: PixelFormat,
+    pub(crate) palette: Palette,
+    // 1行のバイト数(0は画素数から求める)
+    stride: usize,
+}
+
+impl Bitmap {
+    // 画素数と1行のバイト数の上限
+    const MAX_WIDTH: usize = 4096;
+    const MAX_STRIDE: usize = Self::MAX_WIDTH * 4;
+
     pub(crate) fn new() -> Self {
         Self {
-            count: Cell::new(0),
-            notice: RefCell::new(VecDeque::new()),
-            cache: RefCell::new(String::new()),
+            width: 32,
+            format: PixelFormat::Indexed8,
+            palette: Palette::Gray,
+            stride: 0,
         }
     }
 
-    // メッセージキューへ追加
-    pub(crate) fn add(&mut self, state: String) {
-        self.notice.borrow_mut().push_back(state);
+    // 1行のバイト数
+    pub(crate) fn stride(&self) -> usize {
+        match self.stride {
+            0 => (self.width * self.format.bits()).div_ceil(8),
+            x => x,
+        }
     }
 
-    // メッセージ取得
-    pub(crate) fn pop_front(&self) -> String {
-        const LIMIT: u8 = 2; // しばらく表示するためのアクション猶予回数
+    // 画素数を増減
+    pub(crate) fn resize(&mut self, delta: isize) {
+        self.width = self
+            .width
+            .saturating_add_signed(delta)
+            .clamp(1, Self::MAX_WIDTH);
+    }
 
-        match self.count.get() {
-            0 => {
+    // 次の画素の形式
+    pub(crate) fn next_format(&mut self) -> PixelFormat {
+        let i = PixelFormat::ALL
+            .iter()
+            .position(|&x| x == self.format)
+            .unwrap_or_default();
+        self.format = PixelFormat::ALL[(i + 1) % PixelFormat::ALL.len()];
+        self.format
+    }
+
+    // 次のパレット
+    pub(crate) fn next_palette(&mut self) -> Palette {
+        let i = Palette::ALL
+            .iter()
+            .position(|&x| x == self.palette)
+            .unwrap_or_default();
+        self.palette = Palette::ALL[(i + 1) % Palette::ALL.len()];
+        self.palette
+    }
+
+    // "width=64 bpp=4 palette=vga stride=32" のような指定を反映する
+    // 途中で失敗した場合は何も変えない
+    pub(crate) fn apply(&mut self, args: &str) -> Result<(), String> {
+        let mut bitmap = self.clone();
+        for arg in args.split_whitespace() {
+            let (key, value) = arg
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value: {arg}"))?;
+            let invalid = || format!("Invalid {key}: {value}");
+            match key {
+                "width" => {
+                    let width: usize = value.parse().map_err(|_| invalid())?;
+                    if !(1..=Self::MAX_WIDTH).contains(&width) {
+                        return Err(invalid());
+                    }
+                    bitmap.width = width;
+                }
+                "bpp" => {
+                    bitmap.format = PixelFormat::ALL
+                        .into_iter()
+                        .find(|x| x.name() == value.to_ascii_lowercase())
+                        .ok_or_else(invalid)?;
+                }
+                "palette" => {
+                    bitmap.palette = Palette::ALL
+                        .into_iter()
+                        .find(|x| x.name() == value.to_ascii_lowercase())
+                        .ok_or_else(invalid)?;
+                }
+                "stride" => {
+                    let stride: usize = value.parse().map_err(|_| invalid())?;
+                    if stride > Self::MAX_STRIDE {
+                        return Err(invalid());
+                    }
+                    bitmap.stride = stride;
+                }
+                _ => return Err(format!("Unknown bitmap option: {key}")),
+            }
+        }
+        *self = bitmap;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Bitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}px {} {} stride {}",
+            self.width,
+            self.format,
+            self.palette,
+            self.stride()
+        )
+    }
+}
+
+// 波形表示の標本の型
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum SampleType {
+    U8,
+    I16,
+    F32,
+}
+
+impl SampleType {
+    const ALL: [SampleType; 3] = [SampleType::U8, SampleType::I16, SampleType::F32];
+
+    // 1標本のバイト数
+    pub(crate) fn size(self) -> usize {
+        match self {
+            SampleType::U8 => 1,
+            SampleType::I16 => 2,
+            SampleType::F32 => 4,
+        }
+    }
+
+    // 標本の値
+    // bytes が標本のバイト数に足りない場合は None
+    pub(crate) fn read(self, bytes: &[u8], endian: Endian) -> Option<f64> {
+        let bytes = bytes.get(..self.size())?;
+        let value = match (self, endian) {
+            (SampleType::U8, _) => bytes[0] as f64,
+            (SampleType::I16, Endian::Little) => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            (SampleType::I16, Endian::Big) => i16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+            (SampleType::F32, Endian::Little) => {
+                f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+            }
+            (SampleType::F32, Endian::Big) => {
+                f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+            }
+        };
+        Some(value)
+    }
+}
+
+impl fmt::Display for SampleType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::U8 => write!(f, "u8"),
+            Self::I16 => write!(f, "i16"),
+            Self::F32 => write!(f, "f32"),
+        }
+    }
+}
+
+// 波形の描き方
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum PlotStyle {
+    // 折れ線
+    Line,
+    // 棒
+    Bar,
+}
+
+impl fmt::Display for PlotStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Line => write!(f, "line"),
+            Self::Bar => write!(f, "bar"),
+        }
+    }
+}
+
+// 波形表示の設定
+#[derive(Clone)]
+pub(crate) struct Plot {
+    pub(crate) sample: SampleType,
+    pub(crate) style: PlotStyle,
+    // データの開始位置
+    pub(crate) offset: usize,
+    // チャンネル数と表示するチャンネル
+    channels: usize,
+    channel: usize,
+    // 1桁あたりの標本数
+    zoom: usize,
+}
+
+impl Plot {
+    // チャンネル数と拡大率の上限
+    const MAX_CHANNELS: usize = 16;
+    const MAX_ZOOM: usize = 1 << 16;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            sample: SampleType::U8,
+            style: PlotStyle::Line,
+            offset: 0,
+            channels: 1,
+            channel: 0,
+            zoom: 1,
+        }
+    }
+
+    pub(crate) fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub(crate) fn zoom(&self) -> usize {
+        self.zoom
+    }
+
+    // 全チャンネル1組のバイト数
+    pub(crate) fn frame_size(&self) -> usize {
+        self.sample.size() * self.channels
+    }
+
+    // index のバイトを含む標本の番号
+    pub(crate) fn frame_at(&self, index: usize) -> usize {
+        index.saturating_sub(self.offset) / self.frame_size()
+    }
+
+    // frame 番目の標本の値
+    pub(crate) fn sample_at(&self, buf: &[u8], endian: Endian, frame: usize) -> Option<f64> {
+        let index = self.offset + frame * self.frame_size() + self.channel * self.sample.size();
+        self.sample.read(buf.get(index..)?, endian)
+    }
+
+    // 表示の先頭の標本(columns 桁ずつのページ単位)
+    pub(crate) fn window_start(&self, frame: usize, columns: usize) -> usize {
+        let page = (columns * self.zoom).max(1);
+        frame / page * page
+    }
+
+    // 次の標本の型
+    pub(crate) fn next_sample(&mut self) -> SampleType {
+        let i = SampleType::ALL
+            .iter()
+            .position(|&x| x == self.sample)
+            .unwrap_or_default();
+        self.sample = SampleType::ALL[(i + 1) % SampleType::ALL.len()];
+        self.sample
+    }
+
+    // 折れ線と棒の切り替え
+    pub(crate) fn toggle_style(&mut self) -> PlotStyle {
+        self.style = match self.style {
+            PlotStyle::Line => PlotStyle::Bar,
+            PlotStyle::Bar => PlotStyle::Line,
+        };
+        self.style
+    }
+
+    // 次のチャンネル
+    pub(crate) fn next_channel(&mut self) -> usize {
+        self.channel = (self.channel + 1) % self.channels;
+        self.channel
+    }
+
+    // 拡大(1桁あたりの標本数を減らす)
+    pub(crate) fn zoom_in(&mut self) {
+        self.zoom = (self.zoom / 2).max(1);
+    }
+
+    // 縮小(1桁あたりの標本数を増やす)
+    pub(crate) fn zoom_out(&mut self) {
+        self.zoom = (self.zoom * 2).min(Self::MAX_ZOOM);
+    }
+
+    // "type=i16 channels=2 channel=1 offset=44 zoom=4 style=bar" のような指定を反映する
+    // 途中で失敗した場合は何も変えない
+    pub(crate) fn apply(&mut self, args: &str) -> Result<(), String> {
+        let mut plot = self.clone();
+        // channel は channels の指定順によらず、すべて読んだ後に確かめる
+        let mut channel = None;
+        for arg in args.split_whitespace() {
+            let (key, value) = arg
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value: {arg}"))?;
+            let invalid = || format!("Invalid {key}: {value}");
+            let number = || -> Result<usize, String> {
+                match value.strip_prefix("0x") {
+                    Some(hex) => usize::from_str_radix(hex, 16).map_err(|_| invalid()),
+                    None => value.parse().map_err(|_| invalid()),
+                }
+            };
+            match key {
+                "type" => {
+                    plot.sample = SampleType::ALL
+                        .into_iter()
+                        .find(|x| x.to_string() == value.to_ascii_lowercase())
+                        .ok_or_else(invalid)?;
+                }
+                "style" => {
+                    plot.style = match value {
+                        "line" => PlotStyle::Line,
+                        "bar" => PlotStyle::Bar,
+                        _ => return Err(invalid()),
+                    };
+                }
+                "offset" => plot.offset = number()?,
+                "channels" => {
+                    let channels = number()?;
+                    if !(1..=Self::MAX_CHANNELS).contains(&channels) {
+                        return Err(invalid());
+                    }
+                    plot.channels = channels;
+                    plot.channel = plot.channel.min(channels - 1);
+                }
+                "channel" => channel = Some(number()?),
+                "zoom" => {
+                    let zoom = number()?;
+                    if !(1..=Self::MAX_ZOOM).contains(&zoom) {
+                        return Err(invalid());
+                    }
+                    plot.zoom = zoom;
+                }
+                _ => return Err(format!("Unknown plot option: {key}")),
+            }
+        }
+        if let Some(channel) = channel {
+            if channel >= plot.channels {
+                return Err(format!("Invalid channel: {channel}"));
+            }
+            plot.channel = channel;
+        }
+        *self = plot;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Plot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.sample, self.style)?;
+        if self.channels > 1 {
+            write!(f, " ch {}/{}", self.channel + 1, self.channels)?;
+        }
+        if self.offset > 0 {
+            write!(f, " @{:X}", self.offset)?;
+        }
+        write!(f, " x{}", self.zoom)
+    }
+}
+
+// セクタ(フラッシュのページ)単位の表示
+// spare はページごとの OOB 領域のバイト数
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct Sectors {
+    size: usize,
+    spare: usize,
+}
+
+impl Sectors {
+    // 1単位のバイト数
+    pub(crate) fn unit(self) -> usize {
+        self.size + self.spare
+    }
+
+    // offset を含む単位の番号
+    pub(crate) fn index(self, offset: usize) -> usize {
+        offset / self.unit()
+    }
+
+    // 番号から先頭のオフセット
+    pub(crate) fn start(self, index: usize) -> Option<usize> {
+        index.checked_mul(self.unit())
+    }
+
+    // OOB 領域に含まれるか
+    pub(crate) fn is_spare(self, offset: usize) -> bool {
+        offset % self.unit() >= self.size
+    }
+
+    // 1行下に単位の境界があるか(境界の直前の行に区切り線を引く)
+    pub(crate) fn is_before_boundary(self, offset: usize) -> bool {
+        self.index(offset + constants::LINE_LEN) > self.index(offset)
+    }
+
+    // 行頭のオフセット欄(8文字)
+    // 行内で単位が始まる場合は番号、それ以外は単位内のオフセットを表示する
+    pub(crate) fn label(self, offset: usize) -> String {
+        let last = offset + constants::LINE_LEN - 1;
+        let index = self.index(last);
+        let label = if offset == 0 || self.index(offset - 1) != index {
+            format!("#{index:>7}")
+        } else {
+            format!("+{:>7X}", offset % self.unit())
+        };
+        match label.len() {
+            8 => label,
+            _ => format!("{offset:08X}"),
+        }
+    }
+}
+
+impl FromStr for Sectors {
+    type Err = String;
+
+    // "512" や "2048+64"(ページ+OOB)のような指定
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid sector size: {s}");
+        let (size, spare) = s.split_once('+').unwrap_or((s, "0"));
+        let size: usize = size.trim().parse().map_err(|_| invalid())?;
+        let spare: usize = spare.trim().parse().map_err(|_| invalid())?;
+        if size == 0 || size.checked_add(spare).is_none() {
+            return Err(invalid());
+        }
+        Ok(Self { size, spare })
+    }
+}
+
+impl fmt::Display for Sectors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.spare {
+            0 => write!(f, "{} bytes", self.size),
+            spare => write!(f, "{}+{spare} bytes", self.size),
+        }
+    }
+}
+
+// テキストパネルの制御文字の表示方法
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ControlDisplay {
+    // すべて "."
+    Dot,
+    // 制御文字の図記号(␀ ␊ ␍)
+    Pictures,
+    // キャレット記法の文字(^J の J に上線を重ねる)
+    Caret,
+    // 種類ごとの記号
+    Glyphs,
+}
+
+impl ControlDisplay {
+    const ALL: [ControlDisplay; 4] = [
+        ControlDisplay::Dot,
+        ControlDisplay::Pictures,
+        ControlDisplay::Caret,
+        ControlDisplay::Glyphs,
+    ];
+
+    // 次の表示方法
+    fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|&x| x == self)
+            .unwrap_or_default();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ControlDisplay::Dot => "dot",
+            ControlDisplay::Pictures => "pictures",
+            ControlDisplay::Caret => "caret",
+            ControlDisplay::Glyphs => "glyphs",
+        }
+    }
+}
+
+impl FromStr for ControlDisplay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|x| x.name() == s.to_ascii_lowercase())
+            .ok_or_else(|| format!("Unknown control display: {s} (dot, pictures, caret, glyphs)"))
+    }
+}
+
+impl fmt::Display for ControlDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// 書き込みモード
+pub(crate) enum WriteMode {
+    OverWrite,
+    Insert,
+}
+
+impl fmt::Display for WriteMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OverWrite => write!(f, " OVR "),
+            Self::Insert => write!(f, " INT "),
+        }
+    }
+}
+
+// 編集対象ファイル
+pub(crate) struct CurrentFile {
+    path: String,
+}
+
+// 編集対象ファイル管理
+impl CurrentFile {
+    fn new() -> Self {
+        let path = String::new();
+        Self { path }
+    }
+
+    pub(crate) fn path(&self) -> Option<&String> {
+        if self.path.is_empty() {
+            None
+        } else {
+            Some(&self.path)
+        }
+    }
+
+    pub(crate) fn path_mut(&mut self) -> &mut String {
+        &mut self.path
+    }
+
+    // ファイル名を返す
+    pub(crate) fn file_name(&self) -> String {
+        let file_name = std::path::Path::new(&self.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            // 取得できない場合はフルパスを使う
+            .unwrap_or_else(|| self.path.to_string());
+
+        if file_name.is_empty() {
+            String::from("no file")
+        } else {
+            file_name
+        }
+    }
+}
+
+// ステータス伝達
+pub(crate) struct Notice {
+    count: Cell<u8>,
+    notice: RefCell<VecDeque<String>>,
+    cache: RefCell<String>,
+}
+
+impl Notice {
+    pub(crate) fn new() -> Self {
+        Self {
+            count: Cell::new(0),
+            notice: RefCell::new(VecDeque::new()),
+            cache: RefCell::new(String::new()),
+        }
+    }
+
+    // メッセージキューへ追加
+    pub(crate) fn add(&mut self, state: String) {
+        self.notice.borrow_mut().push_back(state);
+    }
+
+    // メッセージ取得
+    pub(crate) fn pop_front(&self) -> String {
+        const LIMIT: u8 = 2; // しばらく表示するためのアクション猶予回数
+
+        match self.count.get() {
+            0 => {
                 if let Some(state) = self.notice.borrow_mut().pop_front() {
                     *self.cache.borrow_mut() = format!(" {state} ");
                     self.count.set(1);
@@ -399,3 +1628,441 @@ impl Notice {
         self.cache.borrow().clone()
     }
 }
+
+// 検索内容
+pub(crate) enum Search {
+    // バイト列の一致
+    Bytes(Vec<u8>),
+    // 相対検索(単語と一致位置)
+    Relative { word: String, hits: Vec<usize> },
+}
+
+impl Search {
+    // start 以降で最初の一致位置
+    // 見つからなければ先頭へ戻って探す
+    pub(crate) fn find(&self, bin_data: &BinData, start: usize) -> Option<usize> {
+        match self {
+            Search::Bytes(pattern) => bin_data.find(pattern, start),
+            Search::Relative { hits, .. } => {
+                hits.iter().find(|&&x| x >= start).or(hits.first()).copied()
+            }
+        }
+    }
+}
+
+// 入力欄の種類
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum PromptKind {
+    // コマンド(":")
+    Command,
+    // テキスト検索("/")
+    Search,
+    // テキスト入力
+    Text,
+    // 型付き表示の値
+    Value,
+}
+
+impl PromptKind {
+    // 入力欄の先頭に表示する記号
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            PromptKind::Command => ":",
+            PromptKind::Search => "/",
+            PromptKind::Text => "\"",
+            PromptKind::Value => "=",
+        }
+    }
+}
+
+// ステータス行の入力欄
+pub(crate) struct Prompt {
+    kind: PromptKind,
+    input: String,
+}
+
+impl Prompt {
+    pub(crate) fn new(kind: PromptKind) -> Self {
+        Self {
+            kind,
+            input: String::new(),
+        }
+    }
+
+    pub(crate) fn kind(&self) -> PromptKind {
+        self.kind
+    }
+
+    pub(crate) fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub(crate) fn input_mut(&mut self) -> &mut String {
+        &mut self.input
+    }
+}
+
+// ヘルプ表示
+pub(crate) struct Help {
+    visible: bool,
+    scroll: u16,
+}
+
+impl Help {
+    fn new() -> Self {
+        Self {
+            visible: false,
+            scroll: 0,
+        }
+    }
+
+    pub(crate) fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    // 表示開始(先頭から)
+    pub(crate) fn show(&mut self) {
+        self.visible = true;
+        self.scroll = 0;
+    }
+
+    pub(crate) fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    pub(crate) fn scroll(&self) -> u16 {
+        self.scroll
+    }
+
+    pub(crate) fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_add(1);
+    }
+
+    pub(crate) fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    // 表示できる範囲を超えたスクロールを戻す
+    pub(crate) fn clamp_scroll(&mut self, max: u16) {
+        self.scroll = self.scroll.min(max);
+    }
+}
+
+// 文字列一覧
+pub(crate) struct StringsPanel {
+    visible: bool,
+    // 最短の文字数
+    min_len: usize,
+    // 探す文字列の種類
+    kinds: Vec<StringKind>,
+    items: Vec<FoundString>,
+    // 絞り込み(大文字小文字を区別しない部分一致)
+    filter: String,
+    // 絞り込み後の選択位置
+    selected: usize,
+}
+
+impl StringsPanel {
+    fn new() -> Self {
+        Self {
+            visible: false,
+            min_len: 4,
+            kinds: StringKind::ALL.to_vec(),
+            items: Vec::new(),
+            filter: String::new(),
+            selected: 0,
+        }
+    }
+
+    pub(crate) fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    // 探し直して表示開始(絞り込みは残す)
+    fn show(&mut self, buf: &[u8]) {
+        self.items = find_strings(buf, self.min_len, &self.kinds);
+        self.visible = true;
+        self.clamp_selected();
+    }
+
+    pub(crate) fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    pub(crate) fn min_len(&self) -> usize {
+        self.min_len
+    }
+
+    pub(crate) fn kinds(&self) -> &[StringKind] {
+        &self.kinds
+    }
+
+    // "6 ascii,utf16le" のような指定を反映する
+    pub(crate) fn apply(&mut self, args: &str) -> Result<(), String> {
+        for arg in args.split_whitespace() {
+            if let Ok(min_len) = arg.parse::<usize>() {
+                self.min_len = min_len.max(1);
+                continue;
+            }
+            let kinds = arg
+                .split(',')
+                .map(|name| {
+                    StringKind::ALL
+                        .into_iter()
+                        .find(|x| x.name() == name.to_ascii_lowercase())
+                        .ok_or_else(|| format!("Unknown string kind: {name}"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            self.kinds = kinds;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    // 絞り込みの1文字追加
+    pub(crate) fn push_filter(&mut self, c: char) {
+        self.filter.push(c);
+        self.clamp_selected();
+    }
+
+    // 絞り込みの1文字削除
+    pub(crate) fn pop_filter(&mut self) {
+        self.filter.pop();
+        self.clamp_selected();
+    }
+
+    pub(crate) fn total(&self) -> usize {
+        self.items.len()
+    }
+
+    // 絞り込み後の一覧
+    pub(crate) fn filtered(&self) -> Vec<&FoundString> {
+        let filter = self.filter.to_lowercase();
+        self.items
+            .iter()
+            .filter(|x| x.text.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    pub(crate) fn selected(&self) -> usize {
+        self.selected
+    }
+
+    // 選択中の文字列
+    pub(crate) fn selected_item(&self) -> Option<&FoundString> {
+        self.filtered().get(self.selected).copied()
+    }
+
+    // 選択位置の移動
+    pub(crate) fn select_by(&mut self, delta: isize) {
+        let count = self.filtered().len();
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(count.saturating_sub(1));
+    }
+
+    fn clamp_selected(&mut self) {
+        self.select_by(0);
+    }
+}
+
+// 構造の木の表示行
+pub(crate) struct TreeRow<'a> {
+    pub(crate) depth: usize,
+    pub(crate) field: &'a Field,
+    pub(crate) collapsed: bool,
+}
+
+// 構造パネル
+pub(crate) struct StructurePanel {
+    root: Option<Field>,
+    // 閉じている項目の位置
+    collapsed: HashSet<Vec<usize>>,
+    // 子を持たない項目の範囲(色分け用、開始位置の順)
+    leaves: Vec<Range<usize>>,
+    // 表示行の選択位置
+    selected: usize,
+    visible: bool,
+    // キー入力を受け付けているか
+    focused: bool,
+}
+
+impl StructurePanel {
+    fn new() -> Self {
+        Self {
+            root: None,
+            collapsed: HashSet::new(),
+            leaves: Vec::new(),
+            selected: 0,
+            visible: false,
+            focused: false,
+        }
+    }
+
+    // 木を差し替えて表示(根だけ開き、子を持つ項目は畳む)
+    pub(crate) fn set_root(&mut self, root: Field) {
+        self.collapsed.clear();
+        let mut stack: Vec<(Vec<usize>, &Field)> = vec![(Vec::new(), &root)];
+        while let Some((path, field)) = stack.pop() {
+            for (i, child) in field.children.iter().enumerate() {
+                if !child.children.is_empty() {
+                    let mut path = path.clone();
+                    path.push(i);
+                    self.collapsed.insert(path.clone());
+                    stack.push((path, child));
+                }
+            }
+        }
+        self.leaves = root.leaf_ranges();
+        self.root = Some(root);
+        self.selected = 0;
+        self.visible = true;
+        self.focused = true;
+    }
+
+    pub(crate) fn root(&self) -> Option<&Field> {
+        self.root.as_ref()
+    }
+
+    pub(crate) fn is_visible(&self) -> bool {
+        self.visible && self.root.is_some()
+    }
+
+    pub(crate) fn toggle_visible(&mut self) -> bool {
+        self.visible = !self.visible;
+        self.focused = self.visible;
+        self.is_visible()
+    }
+
+    pub(crate) fn is_focused(&self) -> bool {
+        self.focused && self.is_visible()
+    }
+
+    pub(crate) fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    // 開いている項目をたどった表示行(位置付き)
+    fn visible_paths(&self) -> Vec<(Vec<usize>, usize)> {
+        let mut paths = Vec::new();
+        let Some(root) = &self.root else {
+            return paths;
+        };
+        let mut stack = vec![(Vec::new(), root)];
+        while let Some((path, field)) = stack.pop() {
+            let depth = path.len();
+            let open = !self.collapsed.contains(&path);
+            if open {
+                for (i, child) in field.children.iter().enumerate().rev() {
+                    let mut child_path = path.clone();
+                    child_path.push(i);
+                    stack.push((child_path, child));
+                }
+            }
+            paths.push((path, depth));
+        }
+        paths
+    }
+
+    pub(crate) fn rows(&self) -> Vec<TreeRow<'_>> {
+        let Some(root) = &self.root else {
+            return Vec::new();
+        };
+        self.visible_paths()
+            .into_iter()
+            .filter_map(|(path, depth)| {
+                let field = root.get(&path)?;
+                let collapsed = self.collapsed.contains(&path);
+                Some(TreeRow {
+                    depth,
+                    field,
+                    collapsed,
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn selected(&self) -> usize {
+        self.selected
+    }
+
+    fn selected_path(&self) -> Option<Vec<usize>> {
+        self.visible_paths()
+            .into_iter()
+            .nth(self.selected)
+            .map(|x| x.0)
+    }
+
+    pub(crate) fn selected_field(&self) -> Option<&Field> {
+        self.root.as_ref()?.get(&self.selected_path()?)
+    }
+
+    pub(crate) fn select_by(&mut self, delta: isize) {
+        let count = self.visible_paths().len();
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(count.saturating_sub(1));
+    }
+
+    // 選択中の項目を開く / 閉じる
+    pub(crate) fn set_collapsed(&mut self, collapsed: bool) {
+        let Some(path) = self.selected_path() else {
+            return;
+        };
+        let has_children = self
+            .selected_field()
+            .is_some_and(|x| !x.children.is_empty());
+        if collapsed && (!has_children || self.collapsed.contains(&path)) {
+            // 閉じている項目では親へ移動
+            if let Some(parent) = path.split_last().map(|x| x.1.to_vec()) {
+                let paths = self.visible_paths();
+                self.selected = paths
+                    .iter()
+                    .position(|x| x.0 == parent)
+                    .unwrap_or(self.selected);
+            }
+        } else if collapsed {
+            self.collapsed.insert(path);
+        } else {
+            self.collapsed.remove(&path);
+        }
+    }
+
+    pub(crate) fn toggle_collapsed(&mut self) {
+        if let Some(path) = self.selected_path() {
+            if !self.collapsed.remove(&path) {
+                self.collapsed.insert(path);
+            }
+        }
+    }
+
+    // index を含む子を持たない項目の番号
+    pub(crate) fn leaf_at(&self, index: usize) -> Option<usize> {
+        let i = self.leaves.partition_point(|x| x.start <= index);
+        let i = i.checked_sub(1)?;
+        self.leaves[i].contains(&index).then_some(i)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sectors() {
+        let sectors: Sectors = "2048+64".parse().unwrap();
+        assert_eq!(sectors.unit(), 2112);
+        assert_eq!(sectors.start(2), Some(4224));
+        assert!(sectors.is_spare(2048));
+        assert_eq!(sectors.label(2112), "#      1");
+        assert_eq!(sectors.label(2128), "+     10");
+        assert!("0".parse::<Sectors>().is_err());
+        assert!("512+x".parse::<Sectors>().is_err());
+        assert!("18446744073709551615+1".parse::<Sectors>().is_err());
+    }
+}
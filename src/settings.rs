@@ -0,0 +1,290 @@
+// 設定管理
+
+// 標準ライブラリ
+use std::fmt;
+use std::fs;
+use std::io::{self};
+use std::path::PathBuf;
+// テーマ
+use crate::theme::ThemeName;
+
+// レイアウトプリセット
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum LayoutPreset {
+    // 16進数パネルとテキストパネルを左右に並べる
+    SideBySide,
+    // 16進数とテキストを1行にまとめる(xxd風)
+    Xxd,
+    // 16進数パネルとテキストパネルを上下に並べる
+    Stacked,
+    // 16進数パネルのみ
+    HexOnly,
+}
+
+impl LayoutPreset {
+    // 次のプリセット
+    pub(crate) fn next(self) -> Self {
+        use LayoutPreset::*;
+        match self {
+            SideBySide => Xxd,
+            Xxd => Stacked,
+            Stacked => HexOnly,
+            HexOnly => SideBySide,
+        }
+    }
+
+    // 設定ファイル上の名前
+    fn name(self) -> &'static str {
+        use LayoutPreset::*;
+        match self {
+            SideBySide => "side-by-side",
+            Xxd => "xxd",
+            Stacked => "stacked",
+            HexOnly => "hex-only",
+        }
+    }
+
+    // 設定ファイル上の名前から変換
+    fn from_name(name: &str) -> Option<Self> {
+        use LayoutPreset::*;
+        [SideBySide, Xxd, Stacked, HexOnly]
+            .into_iter()
+            .find(|x| x.name() == name)
+    }
+}
+
+impl fmt::Display for LayoutPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// 永続化する設定
+pub(crate) struct Settings {
+    layout: LayoutPreset,
+    inspector: bool,
+    overview: bool,
+    main_ratio: u16,
+    theme: ThemeName,
+    // style.<要素名> = <装飾> の指定
+    styles: Vec<(String, String)>,
+}
+
+impl Settings {
+    // メインパネルの比率(%)の範囲と増減量
+    const RATIO_MIN: u16 = 20;
+    const RATIO_MAX: u16 = 80;
+    const RATIO_STEP: u16 = 5;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            layout: LayoutPreset::SideBySide,
+            inspector: true,
+            overview: false,
+            main_ratio: 50,
+            theme: ThemeName::Dark,
+            styles: Vec::new(),
+        }
+    }
+
+    pub(crate) fn layout(&self) -> LayoutPreset {
+        self.layout
+    }
+
+    // レイアウト切り替え
+    pub(crate) fn next_layout(&mut self) -> LayoutPreset {
+        self.layout = self.layout.next();
+        self.layout
+    }
+
+    pub(crate) fn inspector(&self) -> bool {
+        self.inspector
+    }
+
+    // インスペクタ表示切り替え
+    pub(crate) fn toggle_inspector(&mut self) -> bool {
+        self.inspector = !self.inspector;
+        self.inspector
+    }
+
+    pub(crate) fn overview(&self) -> bool {
+        self.overview
+    }
+
+    // 全体俯瞰ストリップ表示切り替え
+    pub(crate) fn toggle_overview(&mut self) -> bool {
+        self.overview = !self.overview;
+        self.overview
+    }
+
+    pub(crate) fn main_ratio(&self) -> u16 {
+        self.main_ratio
+    }
+
+    // メインパネル拡大
+    pub(crate) fn grow_main(&mut self) {
+        self.main_ratio = (self.main_ratio + Self::RATIO_STEP).min(Self::RATIO_MAX);
+    }
+
+    // メインパネル縮小
+    pub(crate) fn shrink_main(&mut self) {
+        self.main_ratio = self
+            .main_ratio
+            .saturating_sub(Self::RATIO_STEP)
+            .max(Self::RATIO_MIN);
+    }
+
+    pub(crate) fn theme(&self) -> ThemeName {
+        self.theme
+    }
+
+    // テーマ切り替え
+    pub(crate) fn next_theme(&mut self) -> ThemeName {
+        self.theme = self.theme.next();
+        self.theme
+    }
+
+    pub(crate) fn styles(&self) -> &[(String, String)] {
+        &self.styles
+    }
+
+    // 設定ファイルのパス
+    // BINLLION_CONFIG > XDG_CONFIG_HOME > HOME の順に探す
+    fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("BINLLION_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|x| PathBuf::from(x).join(".config")))?;
+        Some(base.join("binllion").join("config"))
+    }
+
+    // 設定ファイルから読み込み
+    // ファイルが無い場合や解釈できない行は既定値のまま
+    pub(crate) fn load() -> Self {
+        let mut settings = Self::new();
+        let Some(text) = Self::path().and_then(|path| fs::read_to_string(path).ok()) else {
+            return settings;
+        };
+
+        for (key, value) in text.lines().filter_map(Self::parse_line) {
+            settings.apply(key, value);
+        }
+
+        settings
+    }
+
+    // 1行を key と value に分ける(空行とコメントは None)
+    fn parse_line(line: &str) -> Option<(&str, &str)> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (key, value) = line.split_once('=')?;
+        Some((key.trim(), value.trim()))
+    }
+
+    // key = value を1つ反映
+    fn apply(&mut self, key: &str, value: &str) {
+        match key {
+            "layout" => {
+                if let Some(layout) = LayoutPreset::from_name(value) {
+                    self.layout = layout;
+                }
+            }
+            "inspector" => {
+                if let Ok(inspector) = value.parse() {
+                    self.inspector = inspector;
+                }
+            }
+            "overview" => {
+                if let Ok(overview) = value.parse() {
+                    self.overview = overview;
+                }
+            }
+            "main_ratio" => {
+                if let Ok(ratio) = value.parse::<u16>() {
+                    self.main_ratio = ratio.clamp(Self::RATIO_MIN, Self::RATIO_MAX);
+                }
+            }
+            "theme" => {
+                if let Some(theme) = ThemeName::from_name(value) {
+                    self.theme = theme;
+                }
+            }
+            _ => {
+                if let Some(element) = key.strip_prefix("style.") {
+                    self.styles.push((element.to_string(), value.to_string()));
+                }
+            }
+        }
+    }
+
+    // 設定ファイルへ書き込み
+    pub(crate) fn save(&self) -> Result<(), io::Error> {
+        let Some(path) = Self::path() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Not found config directory",
+            ));
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        fs::write(path, self.update(&text))
+    }
+
+    // 設定ファイルの内容のうち、値の変わった項目の行だけ置き換える
+    // コメントや他の行、並びはそのまま残し、無い項目は末尾に足す
+    fn update(&self, text: &str) -> String {
+        let entries = [
+            ("layout", self.layout.to_string()),
+            ("inspector", self.inspector.to_string()),
+            ("overview", self.overview.to_string()),
+            ("main_ratio", self.main_ratio.to_string()),
+            ("theme", self.theme.to_string()),
+        ];
+        let mut lines: Vec<String> = text.lines().map(String::from).collect();
+        for (key, value) in entries {
+            // 同じ項目が複数あれば読み込みで効く最後の行
+            let found = lines
+                .iter()
+                .rposition(|x| Self::parse_line(x).is_some_and(|x| x.0 == key));
+            match found {
+                Some(i) if Self::parse_line(&lines[i]).is_some_and(|x| x.1 == value) => {}
+                Some(i) => lines[i] = format!("{key} = {value}"),
+                None => lines.push(format!("{key} = {value}")),
+            }
+        }
+        lines.into_iter().map(|x| x + "\n").collect()
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_update() {
+        let text = "# my settings\nstyle.title = bold\nlayout=xxd\nunknown = 1\ntheme = dark\n";
+        let mut settings = Settings::new();
+        for (key, value) in text.lines().filter_map(Settings::parse_line) {
+            settings.apply(key, value);
+        }
+        settings.next_theme();
+        assert_eq!(
+            settings.update(text),
+            "# my settings\nstyle.title = bold\nlayout=xxd\nunknown = 1\ntheme = light\n\
+             inspector = true\noverview = false\nmain_ratio = 50\n"
+        );
+    }
+}
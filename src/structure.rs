@@ -0,0 +1,105 @@
+// 構造の木(テンプレートや形式の解析結果)
+
+// 標準ライブラリ
+use std::ops::Range;
+
+// 木の1項目
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Field {
+    pub(crate) name: String,
+    // 対応するバイト範囲
+    pub(crate) range: Range<usize>,
+    // 表示する値(子を持つ項目では要約)
+    pub(crate) value: String,
+    pub(crate) children: Vec<Field>,
+    // 参照先の位置(セクションの中身など)
+    pub(crate) link: Option<usize>,
+    // 不正な長さや CRC の不一致など
+    pub(crate) error: Option<String>,
+}
+
+impl Field {
+    // 値を持つ項目
+    pub(crate) fn new(
+        name: impl Into<String>,
+        range: Range<usize>,
+        value: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            range,
+            value: value.into(),
+            children: Vec::new(),
+            link: None,
+            error: None,
+        }
+    }
+
+    // 子を持つ項目(範囲は子から求める)
+    pub(crate) fn group(name: impl Into<String>, children: Vec<Field>) -> Self {
+        let start = children
+            .iter()
+            .map(|x| x.range.start)
+            .min()
+            .unwrap_or_default();
+        let end = children.iter().map(|x| x.range.end).max().unwrap_or(start);
+        Self {
+            name: name.into(),
+            range: start..end,
+            value: String::new(),
+            children,
+            link: None,
+            error: None,
+        }
+    }
+
+    // 表示する値を付ける
+    pub(crate) fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.value = value.into();
+        self
+    }
+
+    // 参照先を付ける
+    pub(crate) fn with_link(mut self, link: usize) -> Self {
+        self.link = Some(link);
+        self
+    }
+
+    // 問題点を付ける
+    pub(crate) fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+
+    // 自身と子孫の問題点の数
+    pub(crate) fn error_count(&self) -> usize {
+        let count: usize = self.children.iter().map(Field::error_count).sum();
+        count + usize::from(self.error.is_some())
+    }
+
+    // path の位置の項目
+    pub(crate) fn get(&self, path: &[usize]) -> Option<&Field> {
+        match path.split_first() {
+            None => Some(self),
+            Some((&i, rest)) => self.children.get(i)?.get(rest),
+        }
+    }
+
+    // 子を持たない項目の範囲(開始位置の順)
+    pub(crate) fn leaf_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        self.collect_leaves(&mut ranges);
+        ranges.sort_by_key(|x| x.start);
+        ranges
+    }
+
+    fn collect_leaves(&self, ranges: &mut Vec<Range<usize>>) {
+        if self.children.is_empty() {
+            if !self.range.is_empty() {
+                ranges.push(self.range.clone());
+            }
+        } else {
+            self.children.iter().for_each(|x| x.collect_leaves(ranges));
+        }
+    }
+}
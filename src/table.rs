@@ -0,0 +1,279 @@
+// 文字テーブル(Thingy形式の .tbl ファイル)
+
+// 標準ライブラリ
+use std::collections::HashMap;
+
+// 項目の種類
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum EntryKind {
+    // 通常の文字
+    Normal,
+    // 文字列の終端("/XX")
+    End,
+    // 改行("*XX")
+    Newline,
+}
+
+// テーブルの1項目
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Entry {
+    pub(crate) text: String,
+    pub(crate) kind: EntryKind,
+}
+
+// バイト列と文字列の対応表
+pub(crate) struct CharTable {
+    name: String,
+    entries: HashMap<Vec<u8>, Entry>,
+    // 符号化用の逆引き(先に定義された項目を優先)
+    reverse: HashMap<String, Vec<u8>>,
+    // 最長のバイト列
+    max_len: usize,
+    // 最長の文字列(文字数)
+    max_text_len: usize,
+}
+
+impl CharTable {
+    // 空のテーブル
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            entries: HashMap::new(),
+            reverse: HashMap::new(),
+            max_len: 0,
+            max_text_len: 0,
+        }
+    }
+
+    // ファイルから読み込む
+    // UTF-8 として読めない場合は Shift_JIS とみなす
+    pub(crate) fn load(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{path}: {e}"))?;
+        let text = match std::str::from_utf8(&bytes) {
+            Ok(text) => text.trim_start_matches('\u{feff}').to_string(),
+            Err(_) => encoding_rs::SHIFT_JIS.decode(&bytes).0.into_owned(),
+        };
+        let name = std::path::Path::new(path)
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+        Self::parse(name, &text)
+    }
+
+    // テーブルの内容を解釈する
+    // "XX=文字"、"/XX=文字"(終端)、"*XX"(改行) を扱い、"@" で始まる行は読み飛ばす
+    pub(crate) fn parse(name: String, text: &str) -> Result<Self, String> {
+        let mut table = Self::new(name);
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('@') {
+                continue;
+            }
+
+            let (kind, line) = match line.chars().next() {
+                Some('/') => (EntryKind::End, &line[1..]),
+                Some('*') => (EntryKind::Newline, &line[1..]),
+                _ => (EntryKind::Normal, line),
+            };
+            // 値に "=" を含む場合があるので最初の "=" で分ける
+            let (hex, text) = match line.split_once('=') {
+                Some((hex, text)) => (hex.trim(), text),
+                None if kind != EntryKind::Normal => (line.trim(), ""),
+                None => return Err(format!("line {}: missing '='", i + 1)),
+            };
+            let bytes = parse_hex(hex).ok_or_else(|| format!("line {}: bad hex '{hex}'", i + 1))?;
+            if kind == EntryKind::Normal && text.is_empty() {
+                return Err(format!("line {}: empty text", i + 1));
+            }
+
+            table.insert(bytes, text.to_string(), kind);
+        }
+
+        if table.entries.is_empty() {
+            return Err(String::from("No entries"));
+        }
+        Ok(table)
+    }
+
+    // 項目を追加
+    pub(crate) fn insert(&mut self, bytes: Vec<u8>, text: String, kind: EntryKind) {
+        self.max_len = self.max_len.max(bytes.len());
+        if !text.is_empty() {
+            self.max_text_len = self.max_text_len.max(text.chars().count());
+            self.reverse
+                .entry(text.clone())
+                .or_insert_with(|| bytes.clone());
+        }
+        self.entries.insert(bytes, Entry { text, kind });
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // buf の先頭に最長一致する項目と、そのバイト数を返す
+    pub(crate) fn decode(&self, buf: &[u8]) -> Option<(usize, &Entry)> {
+        (1..=self.max_len.min(buf.len()))
+            .rev()
+            .find_map(|len| self.entries.get(&buf[..len]).map(|x| (len, x)))
+    }
+
+    // .tbl 形式の文字列にする(バイト列の順)
+    pub(crate) fn to_tbl(&self) -> String {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+            .into_iter()
+            .map(|(bytes, entry)| {
+                let hex: String = bytes.iter().map(|x| format!("{x:02X}")).collect();
+                let prefix = match entry.kind {
+                    EntryKind::Normal => "",
+                    EntryKind::End => "/",
+                    EntryKind::Newline => "*",
+                };
+                format!("{prefix}{hex}={}\n", entry.text)
+            })
+            .collect()
+    }
+
+    // ファイルへ保存
+    pub(crate) fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_tbl()).map_err(|e| format!("{path}: {e}"))
+    }
+
+    // 文字列をテーブルで符号化する
+    // 先頭から最長一致で項目を選ぶ
+    pub(crate) fn encode(&self, text: &str) -> Result<Vec<u8>, String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut bytes = Vec::new();
+        let mut pos = 0;
+        while pos < chars.len() {
+            let found = (1..=self.max_text_len.min(chars.len() - pos))
+                .rev()
+                .find_map(|len| {
+                    let key: String = chars[pos..pos + len].iter().collect();
+                    self.reverse.get(&key).map(|x| (len, x))
+                });
+            let Some((len, x)) = found else {
+                return Err(format!("Not in table: '{}'", chars[pos]));
+            };
+            bytes.extend_from_slice(x);
+            pos += len;
+        }
+        Ok(bytes)
+    }
+}
+
+// 相対検索
+// 隣り合う文字の差と隣り合うバイトの差がすべて一致する位置を返す
+pub(crate) fn relative_search(buf: &[u8], word: &str) -> Result<Vec<usize>, String> {
+    let chars: Vec<i64> = word.chars().map(|x| x as i64).collect();
+    if chars.len() < 2 {
+        return Err(String::from("Relative search needs at least 2 letters"));
+    }
+    let deltas: Vec<i64> = chars.windows(2).map(|x| x[1] - x[0]).collect();
+
+    let hits = buf
+        .windows(chars.len())
+        .enumerate()
+        .filter(|(_, x)| {
+            x.windows(2)
+                .zip(&deltas)
+                .all(|(x, &delta)| x[1] as i64 - x[0] as i64 == delta)
+        })
+        .map(|(i, _)| i)
+        .collect();
+    Ok(hits)
+}
+
+// 相対検索の一致から仮の文字テーブルを作る
+// first は一致位置の先頭バイトで、単語に含まれる英大文字・英小文字・数字は同じずれで全体を割り当てる
+pub(crate) fn relative_table(first: u8, word: &str) -> CharTable {
+    let mut table = CharTable::new(String::from("provisional.tbl"));
+    let Some(head) = word.chars().next() else {
+        return table;
+    };
+    let shift = first as i64 - head as i64;
+
+    let mut chars: Vec<char> = Vec::new();
+    for (found, range) in [
+        (word.chars().any(|x| x.is_ascii_uppercase()), 'A'..='Z'),
+        (word.chars().any(|x| x.is_ascii_lowercase()), 'a'..='z'),
+        (word.chars().any(|x| x.is_ascii_digit()), '0'..='9'),
+    ] {
+        if found {
+            chars.extend(range);
+        }
+    }
+    chars.extend(word.chars());
+
+    for ch in chars {
+        let Ok(byte) = u8::try_from(ch as i64 + shift) else {
+            continue;
+        };
+        if table.decode(&[byte]).is_none() {
+            table.insert(vec![byte], ch.to_string(), EntryKind::Normal);
+        }
+    }
+    table
+}
+
+// 16進数の文字列をバイト列へ変換
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.is_empty() || !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_decode_encode() {
+        let text = "@main\n00=A\n01=B\n0102=th\n8140=あ\n20= \n/FF=<end>\n*FE\n";
+        let table = CharTable::parse(String::from("test.tbl"), text).unwrap();
+        assert_eq!(table.len(), 7);
+
+        // 最長一致
+        let (len, entry) = table.decode(&[0x01, 0x02, 0x00]).unwrap();
+        assert_eq!((len, entry.text.as_str()), (2, "th"));
+        let (len, entry) = table.decode(&[0x01, 0x00]).unwrap();
+        assert_eq!((len, entry.text.as_str()), (1, "B"));
+        assert_eq!(table.decode(&[0xff]).unwrap().1.kind, EntryKind::End);
+        assert_eq!(table.decode(&[0xfe]).unwrap().1.kind, EntryKind::Newline);
+        assert!(table.decode(&[0x99]).is_none());
+
+        assert_eq!(
+            table.encode("Ath あB"),
+            Ok(vec![0x00, 0x01, 0x02, 0x20, 0x81, 0x40, 0x01])
+        );
+        assert!(table.encode("C").is_err());
+        assert!(CharTable::parse(String::new(), "0=A").is_err());
+        assert!(CharTable::parse(String::new(), "00").is_err());
+    }
+
+    #[test]
+    fn test_relative_search() {
+        // "Hello" を A=0x10 のずれで埋め込む
+        let word: Vec<u8> = "Hello".bytes().map(|x| x - b'A' + 0x10).collect();
+        let mut buf = vec![0x00, 0x01];
+        buf.extend_from_slice(&word);
+        assert_eq!(relative_search(&buf, "Hello"), Ok(vec![2]));
+        assert!(relative_search(&buf, "H").is_err());
+
+        let table = relative_table(buf[2], "Hello");
+        assert_eq!(table.decode(&[0x10]).unwrap().1.text, "A");
+        assert_eq!(table.decode(&[0x30]).unwrap().1.text, "a");
+        assert!(table.to_tbl().starts_with("10=A\n11=B\n"));
+    }
+}
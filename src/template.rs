@@ -0,0 +1,884 @@
+// 構造テンプレート
+//
+// 例:
+//   enum Kind : u8 { Text = 1, Image, Sound = 5 }
+//   struct Entry {
+//       u16 id;
+//       u8 len;
+//       char name[len];
+//   }
+//   struct main {
+//       char magic[4];
+//       be u16 version;
+//       u16 count;
+//       Kind kind;
+//       Entry entries[count];
+//       if (version >= 2) { u32 extra; } else { u16 extra; }
+//   }
+//
+// "struct main"(無ければ最後の struct)を開始位置から当てはめる
+// "endian big;" 以降のフィールドはビッグエンディアン、"le"/"be" はフィールド単位の指定
+
+// 標準ライブラリ
+use std::collections::HashMap;
+// 状態管理
+use crate::message::Endian;
+// 構造の木
+use crate::structure::Field;
+
+// 入れ子の上限
+const MAX_DEPTH: usize = 64;
+// 配列の要素数の上限
+const MAX_COUNT: usize = 1 << 20;
+// 当てはめ全体で読む配列の要素数の上限(大きさ0の要素の入れ子に備える)
+const MAX_ELEMENTS: usize = 1 << 20;
+// 値として表示するバイト列の長さ
+const PREVIEW_LEN: usize = 16;
+
+// 組み込みの型
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Prim {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+    Char,
+}
+
+impl Prim {
+    fn from_name(name: &str) -> Option<Self> {
+        let prim = match name {
+            "u8" | "uchar" | "byte" => Prim::U8,
+            "i8" => Prim::I8,
+            "u16" | "ushort" => Prim::U16,
+            "i16" | "short" => Prim::I16,
+            "u32" | "uint" => Prim::U32,
+            "i32" | "int" => Prim::I32,
+            "u64" => Prim::U64,
+            "i64" => Prim::I64,
+            "f32" | "float" => Prim::F32,
+            "f64" | "double" => Prim::F64,
+            "char" => Prim::Char,
+            _ => return None,
+        };
+        Some(prim)
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Prim::U8 | Prim::I8 | Prim::Char => 1,
+            Prim::U16 | Prim::I16 => 2,
+            Prim::U32 | Prim::I32 | Prim::F32 => 4,
+            Prim::U64 | Prim::I64 | Prim::F64 => 8,
+        }
+    }
+
+    // 値(式で使う整数)と表示用の文字列
+    fn read(self, bytes: &[u8], endian: Endian) -> (i128, String) {
+        let raw = bytes.iter().enumerate().fold(0u64, |acc, (i, &x)| {
+            let shift = match endian {
+                Endian::Little => i * 8,
+                Endian::Big => (bytes.len() - 1 - i) * 8,
+            };
+            acc | (x as u64) << shift
+        });
+        let value = match self {
+            Prim::I8 => raw as u8 as i8 as i128,
+            Prim::I16 => raw as u16 as i16 as i128,
+            Prim::I32 => raw as u32 as i32 as i128,
+            Prim::I64 => raw as i64 as i128,
+            _ => raw as i128,
+        };
+        let text = match self {
+            Prim::F32 => f32::from_bits(raw as u32).to_string(),
+            Prim::F64 => f64::from_bits(raw).to_string(),
+            Prim::Char => format!("{value} '{}'", escape(&[raw as u8])),
+            Prim::I8 | Prim::I16 | Prim::I32 | Prim::I64 => value.to_string(),
+            _ if value > 9 => format!("{value} (0x{value:X})"),
+            _ => value.to_string(),
+        };
+        (value, text)
+    }
+}
+
+// 列挙型
+struct EnumDef {
+    base: Prim,
+    names: HashMap<i128, String>,
+    values: HashMap<String, i128>,
+}
+
+// 式
+#[derive(Debug)]
+enum Expr {
+    Num(i128),
+    // "a.b.c" のような参照
+    Var(Vec<String>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(String, Box<Expr>, Box<Expr>),
+}
+
+// 文
+enum Stmt {
+    Endian(Endian),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    Field {
+        endian: Option<Endian>,
+        type_name: String,
+        name: String,
+        count: Option<Expr>,
+        line: usize,
+    },
+}
+
+// 解釈済みのテンプレート
+pub(crate) struct Template {
+    structs: HashMap<String, Vec<Stmt>>,
+    enums: HashMap<String, EnumDef>,
+    main: String,
+}
+
+// 字句
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Ident(String),
+    Num(i128),
+    Punct(&'static str),
+}
+
+// 記号(長いものから照合する)
+const PUNCTS: [&str; 30] = [
+    "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "{", "}", "(", ")", "[", "]", ";", ":", ",",
+    "=", "<", ">", "+", "-", "*", "/", "%", "&", "|", "^", "!", ".",
+];
+
+// 字句に分ける(行番号付き)
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        // "//" と "#" 以降は注釈
+        let line = line.split("//").next().unwrap_or_default();
+        let line = line.split('#').next().unwrap_or_default();
+        let mut rest = line.trim_start();
+        while !rest.is_empty() {
+            let c = rest.chars().next().unwrap_or_default();
+            if c.is_ascii_alphabetic() || c == '_' {
+                let len = rest
+                    .find(|x: char| !(x.is_ascii_alphanumeric() || x == '_'))
+                    .unwrap_or(rest.len());
+                tokens.push((Token::Ident(rest[..len].to_string()), line_no));
+                rest = &rest[len..];
+            } else if c.is_ascii_digit() {
+                let len = rest
+                    .find(|x: char| !(x.is_ascii_alphanumeric() || x == '_'))
+                    .unwrap_or(rest.len());
+                let word = rest[..len].replace('_', "");
+                let value = match word.strip_prefix("0x").or(word.strip_prefix("0X")) {
+                    Some(hex) => i128::from_str_radix(hex, 16),
+                    None => word.parse(),
+                }
+                .map_err(|_| format!("line {line_no}: bad number '{word}'"))?;
+                tokens.push((Token::Num(value), line_no));
+                rest = &rest[len..];
+            } else {
+                let punct = PUNCTS
+                    .iter()
+                    .find(|x| rest.starts_with(**x))
+                    .ok_or_else(|| format!("line {line_no}: unexpected '{c}'"))?;
+                tokens.push((Token::Punct(punct), line_no));
+                rest = &rest[punct.len()..];
+            }
+            rest = rest.trim_start();
+        }
+    }
+    Ok(tokens)
+}
+
+// 構文解析
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|x| &x.0)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos.min(self.tokens.len().saturating_sub(1)))
+            .map_or(0, |x| x.1)
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {message}", self.line())
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(x)) if *x == punct)
+    }
+
+    // 記号があれば読み進める
+    fn eat(&mut self, punct: &str) -> bool {
+        if self.is_punct(punct) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{punct}'")))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Ident(x)) => Ok(x),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected a name"))
+            }
+        }
+    }
+
+    // struct / enum の並び
+    fn template(&mut self) -> Result<Template, String> {
+        let mut template = Template {
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            main: String::new(),
+        };
+        while self.peek().is_some() {
+            match self.ident()?.as_str() {
+                "struct" => {
+                    let name = self.ident()?;
+                    let body = self.block()?;
+                    self.eat(";");
+                    template.main = name.clone();
+                    template.structs.insert(name, body);
+                }
+                "enum" => {
+                    let name = self.ident()?;
+                    let def = self.enum_body()?;
+                    self.eat(";");
+                    template.enums.insert(name, def);
+                }
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("expected 'struct' or 'enum'"));
+                }
+            }
+        }
+        if template.structs.contains_key("main") {
+            template.main = String::from("main");
+        }
+        if template.main.is_empty() {
+            return Err(String::from("No struct defined"));
+        }
+        Ok(template)
+    }
+
+    // enum Name : u16 { A = 1, B, C }
+    fn enum_body(&mut self) -> Result<EnumDef, String> {
+        let base = if self.eat(":") {
+            let name = self.ident()?;
+            Prim::from_name(&name).ok_or_else(|| self.error(&format!("unknown type '{name}'")))?
+        } else {
+            Prim::U32
+        };
+        let mut def = EnumDef {
+            base,
+            names: HashMap::new(),
+            values: HashMap::new(),
+        };
+        self.expect("{")?;
+        // 値を省略した場合は直前の値 + 1
+        let mut next = Some(0);
+        while !self.eat("}") {
+            let name = self.ident()?;
+            let value = if self.eat("=") {
+                self.expr()?
+                    .constant()
+                    .ok_or_else(|| self.error("expected a constant"))?
+            } else {
+                next.ok_or_else(|| self.error(&format!("value of '{name}' overflows")))?
+            };
+            def.names.entry(value).or_insert_with(|| name.clone());
+            def.values.insert(name, value);
+            next = value.checked_add(1);
+            if !self.eat(",") {
+                self.expect("}")?;
+                break;
+            }
+        }
+        Ok(def)
+    }
+
+    // { 文 ... }
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        self.expect("{")?;
+        let mut stmts = Vec::new();
+        while !self.eat("}") {
+            if self.peek().is_none() {
+                return Err(self.error("expected '}'"));
+            }
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, String> {
+        let line = self.line();
+        let word = self.ident()?;
+        match word.as_str() {
+            "endian" => {
+                let endian = self.endian_name()?;
+                self.expect(";")?;
+                Ok(Stmt::Endian(endian))
+            }
+            "if" => {
+                self.expect("(")?;
+                let cond = self.expr()?;
+                self.expect(")")?;
+                let then = self.block()?;
+                let other = if self.peek() == Some(&Token::Ident(String::from("else"))) {
+                    self.pos += 1;
+                    if self.peek() == Some(&Token::Ident(String::from("if"))) {
+                        vec![self.stmt()?]
+                    } else {
+                        self.block()?
+                    }
+                } else {
+                    Vec::new()
+                };
+                Ok(Stmt::If(cond, then, other))
+            }
+            _ => {
+                let (endian, type_name) = match word.as_str() {
+                    "le" => (Some(Endian::Little), self.ident()?),
+                    "be" => (Some(Endian::Big), self.ident()?),
+                    _ => (None, word),
+                };
+                let name = self.ident()?;
+                let count = if self.eat("[") {
+                    let count = self.expr()?;
+                    self.expect("]")?;
+                    Some(count)
+                } else {
+                    None
+                };
+                self.expect(";")?;
+                Ok(Stmt::Field {
+                    endian,
+                    type_name,
+                    name,
+                    count,
+                    line,
+                })
+            }
+        }
+    }
+
+    fn endian_name(&mut self) -> Result<Endian, String> {
+        match self.ident()?.as_str() {
+            "little" | "le" => Ok(Endian::Little),
+            "big" | "be" => Ok(Endian::Big),
+            _ => Err(self.error("expected 'little' or 'big'")),
+        }
+    }
+
+    // 二項演算子の優先順位(低い順)
+    fn expr(&mut self) -> Result<Expr, String> {
+        const LEVELS: [&[&str]; 10] = [
+            &["||"],
+            &["&&"],
+            &["|"],
+            &["^"],
+            &["&"],
+            &["==", "!="],
+            &["<", "<=", ">", ">="],
+            &["<<", ">>"],
+            &["+", "-"],
+            &["*", "/", "%"],
+        ];
+        self.binary(&LEVELS)
+    }
+
+    fn binary(&mut self, levels: &[&[&str]]) -> Result<Expr, String> {
+        let Some((ops, rest)) = levels.split_first() else {
+            return self.unary();
+        };
+        let mut lhs = self.binary(rest)?;
+        while let Some(op) = ops.iter().find(|x| self.is_punct(x)) {
+            self.pos += 1;
+            let rhs = self.binary(rest)?;
+            lhs = Expr::Binary(op.to_string(), Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let expr = self.expr()?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+        match self.next() {
+            Some(Token::Num(x)) => Ok(Expr::Num(x)),
+            Some(Token::Ident(x)) => {
+                let mut path = vec![x];
+                while self.eat(".") {
+                    path.push(self.ident()?);
+                }
+                Ok(Expr::Var(path))
+            }
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected an expression"))
+            }
+        }
+    }
+}
+
+impl Expr {
+    // 参照を含まない式の値
+    fn constant(&self) -> Option<i128> {
+        eval_expr(self, &|_| None).ok()
+    }
+}
+
+// 式の値
+// lookup は参照の値を返す
+fn eval_expr(expr: &Expr, lookup: &dyn Fn(&[String]) -> Option<i128>) -> Result<i128, String> {
+    let value = match expr {
+        Expr::Num(x) => *x,
+        Expr::Var(path) => {
+            lookup(path).ok_or_else(|| format!("unknown name '{}'", path.join(".")))?
+        }
+        Expr::Not(x) => (eval_expr(x, lookup)? == 0) as i128,
+        Expr::Neg(x) => eval_expr(x, lookup)?.wrapping_neg(),
+        Expr::Binary(op, lhs, rhs) => {
+            let lhs = eval_expr(lhs, lookup)?;
+            let rhs = eval_expr(rhs, lookup)?;
+            match op.as_str() {
+                "||" => (lhs != 0 || rhs != 0) as i128,
+                "&&" => (lhs != 0 && rhs != 0) as i128,
+                "|" => lhs | rhs,
+                "^" => lhs ^ rhs,
+                "&" => lhs & rhs,
+                "==" => (lhs == rhs) as i128,
+                "!=" => (lhs != rhs) as i128,
+                "<" => (lhs < rhs) as i128,
+                "<=" => (lhs <= rhs) as i128,
+                ">" => (lhs > rhs) as i128,
+                ">=" => (lhs >= rhs) as i128,
+                "<<" => lhs.checked_shl(rhs as u32).unwrap_or_default(),
+                ">>" => lhs.checked_shr(rhs as u32).unwrap_or_default(),
+                "+" => lhs.wrapping_add(rhs),
+                "-" => lhs.wrapping_sub(rhs),
+                "*" => lhs.wrapping_mul(rhs),
+                "/" => lhs.checked_div(rhs).ok_or("division by zero")?,
+                _ => lhs.checked_rem(rhs).ok_or("division by zero")?,
+            }
+        }
+    };
+    Ok(value)
+}
+
+// 参照できる値
+enum Value {
+    Num(i128),
+    Struct(HashMap<String, Value>),
+}
+
+// テンプレートの当てはめ
+struct Evaluator<'a> {
+    template: &'a Template,
+    buf: &'a [u8],
+    pos: usize,
+    endian: Endian,
+    // 外側から順の構造体ごとの値
+    scopes: Vec<HashMap<String, Value>>,
+    // これまでに読んだ配列の要素数
+    elements: usize,
+}
+
+impl Evaluator<'_> {
+    fn lookup(&self, path: &[String]) -> Option<i128> {
+        let (head, rest) = path.split_first()?;
+        // 列挙型の名前
+        if rest.is_empty() {
+            if let Some(x) = self
+                .template
+                .enums
+                .values()
+                .find_map(|x| x.values.get(head))
+            {
+                if !self.scopes.iter().any(|scope| scope.contains_key(head)) {
+                    return Some(*x);
+                }
+            }
+        }
+        let mut value = self.scopes.iter().rev().find_map(|x| x.get(head))?;
+        for name in rest {
+            match value {
+                Value::Struct(x) => value = x.get(name)?,
+                Value::Num(_) => return None,
+            }
+        }
+        match value {
+            Value::Num(x) => Some(*x),
+            Value::Struct(_) => None,
+        }
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<i128, String> {
+        eval_expr(expr, &|path| self.lookup(path))
+    }
+
+    // 構造体1つを読む
+    fn read_struct(
+        &mut self,
+        name: &str,
+        body: &[Stmt],
+        label: String,
+    ) -> (Field, Value, Result<(), String>) {
+        let start = self.pos;
+        let endian = self.endian;
+        self.scopes.push(HashMap::new());
+        let mut children = Vec::new();
+        let result = if self.scopes.len() > MAX_DEPTH {
+            Err(String::from("structs nested too deeply"))
+        } else {
+            self.stmts(body, &mut children)
+        };
+        let scope = self.scopes.pop().unwrap_or_default();
+        self.endian = endian;
+        let field = Field {
+            name: label,
+            range: start..self.pos,
+            value: name.to_string(),
+            children,
+            link: None,
+            error: None,
+        };
+        (field, Value::Struct(scope), result)
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt], fields: &mut Vec<Field>) -> Result<(), String> {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Endian(endian) => self.endian = *endian,
+                Stmt::If(cond, then, other) => {
+                    let body = if self.eval(cond)? != 0 { then } else { other };
+                    self.stmts(body, fields)?;
+                }
+                Stmt::Field {
+                    endian,
+                    type_name,
+                    name,
+                    count,
+                    line,
+                } => {
+                    let endian = endian.unwrap_or(self.endian);
+                    self.field(type_name, name, count.as_ref(), endian, fields)
+                        .map_err(|e| format!("line {line}: {e}"))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // フィールド1つ(配列を含む)を読む
+    fn field(
+        &mut self,
+        type_name: &str,
+        name: &str,
+        count: Option<&Expr>,
+        endian: Endian,
+        fields: &mut Vec<Field>,
+    ) -> Result<(), String> {
+        let Some(count) = count else {
+            return self.element(type_name, name.to_string(), endian, fields);
+        };
+
+        let count = self.eval(count)?;
+        if !(0..=MAX_COUNT as i128).contains(&count) {
+            return Err(format!("bad array length {count} for '{name}'"));
+        }
+        let count = count as usize;
+        let start = self.pos;
+
+        // char と u8 の配列は1項目にまとめる
+        if let Some(prim @ (Prim::Char | Prim::U8)) = Prim::from_name(type_name) {
+            let bytes = self.take(count)?;
+            let value = match prim {
+                Prim::Char => format!(
+                    "\"{}\"",
+                    escape(bytes.split(|&x| x == 0).next().unwrap_or_default())
+                ),
+                _ => preview(bytes),
+            };
+            fields.push(Field::new(
+                format!("{name}[{count}]"),
+                start..self.pos,
+                value,
+            ));
+            return Ok(());
+        }
+
+        let mut children = Vec::new();
+        let mut result = Ok(());
+        for i in 0..count {
+            self.elements += 1;
+            if self.elements > MAX_ELEMENTS {
+                result = Err(String::from("too many array elements"));
+                break;
+            }
+            result = self.element(type_name, format!("[{i}]"), endian, &mut children);
+            if result.is_err() {
+                break;
+            }
+        }
+        fields.push(Field {
+            name: format!("{name}[{count}]"),
+            range: start..self.pos,
+            value: type_name.to_string(),
+            children,
+            link: None,
+            error: None,
+        });
+        result
+    }
+
+    // 配列でない値1つを読む
+    fn element(
+        &mut self,
+        type_name: &str,
+        label: String,
+        endian: Endian,
+        fields: &mut Vec<Field>,
+    ) -> Result<(), String> {
+        let start = self.pos;
+        if let Some(prim) = Prim::from_name(type_name) {
+            let bytes = self.take(prim.size())?;
+            let (value, text) = prim.read(bytes, endian);
+            self.define(&label, Value::Num(value));
+            fields.push(Field::new(label, start..self.pos, text));
+        } else if let Some(def) = self.template.enums.get(type_name) {
+            let bytes = self.take(def.base.size())?;
+            let (value, _) = def.base.read(bytes, endian);
+            let text = match def.names.get(&value) {
+                Some(name) => format!("{name} ({value})"),
+                None => format!("{value} (?)"),
+            };
+            self.define(&label, Value::Num(value));
+            fields.push(Field::new(label, start..self.pos, text));
+        } else if let Some(body) = self.template.structs.get(type_name) {
+            self.endian = endian;
+            let (field, value, result) = self.read_struct(type_name, body, label.clone());
+            self.define(&label, value);
+            fields.push(field);
+            result?;
+        } else {
+            return Err(format!("unknown type '{type_name}'"));
+        }
+        Ok(())
+    }
+
+    // 現在の構造体に名前を登録(配列の要素は登録しない)
+    fn define(&mut self, name: &str, value: Value) {
+        if !name.starts_with('[') {
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.insert(name.to_string(), value);
+            }
+        }
+    }
+
+    // len バイトを読み進める
+    fn take(&mut self, len: usize) -> Result<&[u8], String> {
+        let start = self.pos;
+        let bytes = self
+            .buf
+            .get(start..start.saturating_add(len))
+            .ok_or_else(|| format!("unexpected end of data at 0x{start:X}"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+}
+
+// 表示用に制御文字を置き換える
+fn escape(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&x| match x {
+            0x20..=0x7e => x as char,
+            _ => '.',
+        })
+        .collect()
+}
+
+// バイト列の先頭を16進数で
+fn preview(bytes: &[u8]) -> String {
+    let hex: Vec<String> = bytes
+        .iter()
+        .take(PREVIEW_LEN)
+        .map(|x| format!("{x:02X}"))
+        .collect();
+    let more = if bytes.len() > PREVIEW_LEN {
+        " …"
+    } else {
+        ""
+    };
+    format!("{}{more}", hex.join(" "))
+}
+
+impl Template {
+    // 文字列から読む
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        Parser { tokens, pos: 0 }.template()
+    }
+
+    // ファイルから読む
+    pub(crate) fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        Self::parse(&text).map_err(|e| format!("{path}: {e}"))
+    }
+
+    // buf の offset から当てはめる
+    // 途中で失敗した場合も読めたところまでの木とエラーを返す
+    pub(crate) fn apply(
+        &self,
+        buf: &[u8],
+        offset: usize,
+        endian: Endian,
+    ) -> (Field, Option<String>) {
+        let mut evaluator = Evaluator {
+            template: self,
+            buf,
+            pos: offset,
+            endian,
+            scopes: Vec::new(),
+            elements: 0,
+        };
+        let body = &self.structs[&self.main];
+        let (mut field, _, result) = evaluator.read_struct(&self.main, body, self.main.clone());
+        field.value = format!("{} bytes @ 0x{offset:X}", field.range.len());
+        (field, result.err())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEMPLATE: &str = "
+        # 例
+        enum Kind : u8 { Text = 1, Image, Sound = 5 }
+        struct Entry {
+            u16 id;
+            u8 len;
+            char name[len];
+        }
+        struct main {
+            char magic[4];
+            be u16 version;
+            u8 count;
+            Kind kind;
+            Entry entries[count];
+            if (version >= 2 && kind == Image) { u32 extra; } else { u8 extra; }
+        }";
+
+    #[test]
+    fn test_template() {
+        let template = Template::parse(TEMPLATE).unwrap();
+        let buf = [
+            b'D', b'A', b'T', b'A', 0x00, 0x02, 0x02, 0x02, // ヘッダー
+            0x01, 0x00, 0x02, b'h', b'i', // 要素0
+            0x02, 0x00, 0x00, // 要素1
+            0x78, 0x56, 0x34, 0x12,
+        ];
+        let (root, error) = template.apply(&buf, 0, Endian::Little);
+        assert_eq!(error, None);
+        assert_eq!(root.range, 0..20);
+        let values: Vec<_> = root
+            .children
+            .iter()
+            .map(|x| (x.name.as_str(), x.value.as_str()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("magic[4]", "\"DATA\""),
+                ("version", "2"),
+                ("count", "2"),
+                ("kind", "Image (2)"),
+                ("entries[2]", "Entry"),
+                ("extra", "305419896 (0x12345678)"),
+            ]
+        );
+        let name = root.get(&[4, 0, 2]).unwrap();
+        assert_eq!(
+            (name.value.as_str(), name.range.clone()),
+            ("\"hi\"", 11..13)
+        );
+        assert_eq!(root.get(&[4, 1]).unwrap().range, 13..16);
+
+        // データが足りない場合は途中まで
+        let (root, error) = template.apply(&buf[..10], 0, Endian::Little);
+        assert!(error.unwrap().contains("end of data"));
+        assert_eq!(root.children.len(), 5);
+
+        assert!(Template::parse("struct a { u8 x }").is_err());
+        assert!(Template::parse("struct a { u9 x; }")
+            .unwrap()
+            .apply(&buf, 0, Endian::Little)
+            .1
+            .is_some());
+    }
+
+    #[test]
+    fn test_limits() {
+        let max = "0x7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF";
+        let template = Template::parse(&format!("enum E {{ A = {max} }} struct main {{ E a; }}"));
+        assert!(template.is_ok());
+        assert!(Template::parse(&format!("enum E {{ A = {max}, B }}")).is_err());
+
+        // 大きさ0の要素の配列の入れ子は要素数の上限で止まる
+        let template = Template::parse(
+            "struct empty { }
+            struct row { empty cells[1048576]; }
+            struct main { row rows[1048576]; }",
+        )
+        .unwrap();
+        let (_, error) = template.apply(&[], 0, Endian::Little);
+        assert!(error.unwrap().contains("too many"));
+    }
+}
@@ -0,0 +1,267 @@
+// 配色テーマ
+
+// 標準ライブラリ
+use std::fmt;
+use std::str::FromStr;
+// ratatuiクレート
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::symbols::border;
+use ratatui::widgets::{Block, Borders};
+// 解析
+use crate::analysis::Region;
+
+// テーマ名
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ThemeName {
+    Dark,
+    Light,
+    HighContrast,
+    Monochrome,
+}
+
+impl ThemeName {
+    const ALL: [ThemeName; 4] = [
+        ThemeName::Dark,
+        ThemeName::Light,
+        ThemeName::HighContrast,
+        ThemeName::Monochrome,
+    ];
+
+    // 次のテーマ
+    pub(crate) fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|&x| x == self)
+            .unwrap_or_default();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    // 設定ファイル上の名前
+    fn name(self) -> &'static str {
+        match self {
+            ThemeName::Dark => "dark",
+            ThemeName::Light => "light",
+            ThemeName::HighContrast => "high-contrast",
+            ThemeName::Monochrome => "monochrome",
+        }
+    }
+
+    // 設定ファイル上の名前から変換
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.name() == name)
+    }
+}
+
+impl fmt::Display for ThemeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// 画面要素ごとの装飾
+pub(crate) struct Theme {
+    monochrome: bool,
+    border_set: border::Set,
+    pub(crate) border: Style,
+    pub(crate) title: Style,
+    pub(crate) header: Style,
+    pub(crate) selection: Style,
+    pub(crate) mode: Style,
+    pub(crate) modified: Style,
+    pub(crate) notice: Style,
+    pub(crate) key: Style,
+    pub(crate) separator: Style,
+    pub(crate) category: Style,
+    pub(crate) fill: Style,
+    pub(crate) text: Style,
+    pub(crate) entropy: Style,
+    pub(crate) binary: Style,
+    pub(crate) control: Style,
+    // 構造の項目の色分け(交互)
+    pub(crate) field: Style,
+    pub(crate) field_alt: Style,
+}
+
+impl Theme {
+    // 名前からテーマを作る
+    pub(crate) fn new(name: ThemeName) -> Self {
+        let base = Self {
+            monochrome: false,
+            border_set: border::THICK,
+            border: Style::new(),
+            title: Style::new().bold(),
+            header: Style::new().magenta(),
+            selection: Style::new().reversed(),
+            mode: Style::new().green().bold(),
+            modified: Style::new().red().bold(),
+            notice: Style::new(),
+            key: Style::new().blue().bold(),
+            separator: Style::new().dark_gray(),
+            category: Style::new().magenta().bold(),
+            fill: Style::new().dark_gray(),
+            text: Style::new().green(),
+            entropy: Style::new().red(),
+            binary: Style::new().blue(),
+            control: Style::new().cyan(),
+            field: Style::new().on_blue(),
+            field_alt: Style::new().on_magenta(),
+        };
+
+        match name {
+            ThemeName::Dark => base,
+            ThemeName::Light => Self {
+                header: Style::new().fg(Color::Rgb(0x80, 0x00, 0x80)),
+                mode: Style::new().fg(Color::Rgb(0x00, 0x64, 0x00)).bold(),
+                key: Style::new().fg(Color::Rgb(0x00, 0x00, 0x8b)).bold(),
+                separator: Style::new().gray(),
+                category: Style::new().fg(Color::Rgb(0x80, 0x00, 0x80)).bold(),
+                fill: Style::new().gray(),
+                control: Style::new().fg(Color::Rgb(0x00, 0x80, 0x80)),
+                field: Style::new().bg(Color::Rgb(0xcc, 0xdd, 0xff)),
+                field_alt: Style::new().bg(Color::Rgb(0xff, 0xdd, 0xcc)),
+                ..base
+            },
+            ThemeName::HighContrast => Self {
+                border: Style::new().white().bold(),
+                title: Style::new().black().on_white().bold(),
+                header: Style::new().light_yellow().bold(),
+                selection: Style::new().black().on_light_yellow(),
+                mode: Style::new().black().on_light_green().bold(),
+                modified: Style::new().white().on_red().bold(),
+                notice: Style::new().light_cyan().bold(),
+                key: Style::new().light_cyan().bold(),
+                separator: Style::new().white(),
+                category: Style::new().light_yellow().bold().underlined(),
+                fill: Style::new().white(),
+                text: Style::new().light_green(),
+                entropy: Style::new().light_red(),
+                binary: Style::new().light_cyan(),
+                control: Style::new().light_magenta().bold(),
+                field: Style::new().black().on_light_cyan(),
+                field_alt: Style::new().black().on_light_green(),
+                ..base
+            },
+            ThemeName::Monochrome => Self::monochrome(),
+        }
+    }
+
+    // 色を使わないテーマ
+    fn monochrome() -> Self {
+        Self {
+            monochrome: true,
+            border_set: border::PLAIN,
+            border: Style::new(),
+            title: Style::new().bold(),
+            header: Style::new().underlined(),
+            selection: Style::new().reversed(),
+            mode: Style::new().bold(),
+            modified: Style::new().bold(),
+            notice: Style::new(),
+            key: Style::new().bold(),
+            separator: Style::new(),
+            category: Style::new().bold().underlined(),
+            fill: Style::new(),
+            text: Style::new(),
+            entropy: Style::new(),
+            binary: Style::new(),
+            control: Style::new().underlined(),
+            field: Style::new().underlined(),
+            field_alt: Style::new().italic(),
+        }
+    }
+
+    // NO_COLOR が設定されているか
+    pub(crate) fn no_color() -> bool {
+        std::env::var_os("NO_COLOR").is_some_and(|x| !x.is_empty())
+    }
+
+    // 設定から作る
+    // NO_COLOR が設定されている場合は常に色を使わない
+    pub(crate) fn load(name: ThemeName, overrides: &[(String, String)]) -> Self {
+        if Self::no_color() {
+            return Self::monochrome();
+        }
+
+        let mut theme = Self::new(name);
+        for (key, value) in overrides {
+            if let (Some(style), Ok(parsed)) = (theme.style_mut(key), parse_style(value)) {
+                *style = parsed;
+            }
+        }
+        theme
+    }
+
+    // 設定ファイル上の要素名から装飾を探す
+    fn style_mut(&mut self, key: &str) -> Option<&mut Style> {
+        let style = match key {
+            "border" => &mut self.border,
+            "title" => &mut self.title,
+            "header" => &mut self.header,
+            "selection" => &mut self.selection,
+            "mode" => &mut self.mode,
+            "modified" => &mut self.modified,
+            "notice" => &mut self.notice,
+            "key" => &mut self.key,
+            "separator" => &mut self.separator,
+            "category" => &mut self.category,
+            "fill" => &mut self.fill,
+            "text" => &mut self.text,
+            "entropy" => &mut self.entropy,
+            "binary" => &mut self.binary,
+            "control" => &mut self.control,
+            "field" => &mut self.field,
+            "field_alt" => &mut self.field_alt,
+            _ => return None,
+        };
+        Some(style)
+    }
+
+    // 枠付きのブロック
+    pub(crate) fn block(&self) -> Block<'static> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_set(self.border_set)
+            .border_style(self.border)
+    }
+
+    // 色を使わないテーマか
+    pub(crate) fn is_monochrome(&self) -> bool {
+        self.monochrome
+    }
+
+    // 全体俯瞰ストリップの表示
+    // 色を使わない場合は記号で区別する
+    pub(crate) fn region(&self, region: Region) -> (&'static str, Style) {
+        match (region, self.monochrome) {
+            (Region::Fill, false) => ("█", self.fill),
+            (Region::Text, false) => ("█", self.text),
+            (Region::HighEntropy, false) => ("█", self.entropy),
+            (Region::Binary, false) => ("█", self.binary),
+            (Region::Fill, true) => (" ", self.fill),
+            (Region::Text, true) => ("░", self.text),
+            (Region::HighEntropy, true) => ("█", self.entropy),
+            (Region::Binary, true) => ("▒", self.binary),
+        }
+    }
+}
+
+// "red on black bold" のような装飾指定を解釈する
+fn parse_style(value: &str) -> Result<Style, String> {
+    let mut style = Style::new();
+    let mut words = value.split_whitespace();
+    while let Some(word) = words.next() {
+        style = match word {
+            "on" => {
+                let color = words.next().ok_or("Missing background color")?;
+                style.bg(Color::from_str(color).map_err(|e| e.to_string())?)
+            }
+            "bold" => style.add_modifier(Modifier::BOLD),
+            "dim" => style.add_modifier(Modifier::DIM),
+            "italic" => style.add_modifier(Modifier::ITALIC),
+            "underlined" => style.add_modifier(Modifier::UNDERLINED),
+            "reversed" => style.add_modifier(Modifier::REVERSED),
+            color => style.fg(Color::from_str(color).map_err(|e| e.to_string())?),
+        };
+    }
+    Ok(style)
+}
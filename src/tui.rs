@@ -1,19 +1,52 @@
 // TUI関連
 
 // モジュールファイルの読み込み
+mod bitmap;
 mod converter;
+mod entropy;
+mod help;
+mod inspector;
+mod plot;
+mod status;
+mod strings;
+mod structure;
 
 // 標準ライブラリ
 use std::io::{self};
 // ratatuiクレート
 use ratatui::prelude::*;
-use ratatui::symbols::border;
-use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::widgets::{Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState};
 use ratatui::DefaultTerminal;
+// 解析
+use crate::analysis;
+// キー割り当て
+use crate::keymap::{self, Action};
 // 状態管理
-use crate::message::{Message, Scroll};
+use crate::message::{MainView, Message, Pane, ScreenLayout, Scroll, TextEncoding};
+// 設定
+use crate::settings::LayoutPreset;
+// テーマ
+use crate::theme::Theme;
+// 画素表示
+use crate::tui::bitmap::bitmap_lines;
 // 変換処理系
-use crate::tui::converter::{Converter, ForAscii, ForHex};
+pub(crate) use crate::tui::converter::encode_text;
+use crate::tui::converter::{Converter, ForHex};
+// 解析表示
+pub(crate) use crate::tui::entropy::entropy_index;
+use crate::tui::entropy::{analysis_areas, entropy_header, render_analysis};
+// ヘルプ
+use crate::tui::help::{help_max_scroll, render_help};
+// インスペクタ
+use crate::tui::inspector::inspect_lines;
+// 波形表示
+use crate::tui::plot::{plot_header, render_plot};
+// ステータスライン
+use crate::tui::status::status_line;
+// 文字列一覧
+use crate::tui::strings::render_strings;
+// 構造パネル
+use crate::tui::structure::structure_lines;
 // 定数
 use crate::constants;
 
@@ -22,184 +55,571 @@ pub(crate) fn render_main(terminal: &mut DefaultTerminal, message: &Message) ->
     let bin_data = message.bin_data();
     let cursor = message.cursor();
     let layout = message.layout();
+    let theme = message.theme();
+    let encoding = *message.text_encoding();
+    let table = message.char_table();
+    let xxd = layout.preset == Some(LayoutPreset::Xxd);
 
     // メインパネル
     // 上タイトル
 
     // ファイル名のみを取得してタイトルとする
     let file_name = message.current_file().file_name();
-    let title = Line::from(format!(" {file_name} ").bold()).centered();
+    let title = Line::from(Span::styled(format!(" {file_name} "), theme.title)).centered();
 
-    // 下タイトル(ステータスバー)
-
-    // モード取得
-    let mode = message.write_mode().to_string();
-
-    // メッセージ取り出し
+    // ステータスライン(入力欄の表示中は入力欄)
     let notice = message.notice().pop_front();
-
-    let status_bar_left = Line::from(vec![" Mode:".into(), mode.green().bold()]).left_aligned();
-    let status_bar_mid = Line::from(notice).centered();
-    let status_bar_right =
-        Line::from(vec![" Quit ".into(), "<Ctrl+Q> ".blue().bold()]).right_aligned();
+    let status = match message.prompt() {
+        Some(prompt) => Line::from(vec![
+            Span::styled(prompt.kind().symbol(), theme.key),
+            Span::raw(prompt.input().to_string()),
+        ]),
+        None => status_line(message, notice, layout.status.width),
+    };
 
     // パネルブロック
-    let block = Block::default()
-        .title(title)
-        .title_bottom(status_bar_left)
-        .title_bottom(status_bar_mid)
-        .title_bottom(status_bar_right)
-        .borders(Borders::ALL)
-        .border_set(border::THICK);
+    let block = theme.block().title(title);
+
+    // 選択範囲の強調
+    // 構造パネルの表示中は選択中の項目を強調し、その他の項目を交互に色分けする
+    let selected = message.selected_range();
+    let structure = message.structure();
+    let field_range = structure
+        .is_visible()
+        .then(|| structure.selected_field().map(|x| x.range.clone()))
+        .flatten();
+    // セクタ単位の表示中は OOB 領域を薄くし、境界の直前の行に下線を引く
+    let sectors = message.sectors();
+    let style = |index: usize| {
+        let style = match &selected {
+            Some(range) if range.contains(&index) => theme.selection,
+            _ if field_range.as_ref().is_some_and(|x| x.contains(&index)) => theme.selection,
+            _ if structure.is_visible() => match structure.leaf_at(index) {
+                Some(i) if i.is_multiple_of(2) => theme.field,
+                Some(_) => theme.field_alt,
+                None => Style::new(),
+            },
+            _ if sectors.is_some_and(|x| x.is_spare(index)) => theme.fill,
+            _ => Style::new(),
+        };
+        match sectors {
+            Some(x) if x.is_before_boundary(index) => style.underlined(),
+            _ => style,
+        }
+    };
+
+    // テキストの装飾(制御文字を区別する)
+    let control = *message.control_display();
+    let text_style = |index: usize| {
+        if Converter::is_control_at(bin_data.buf(), index, encoding, control) {
+            style(index).patch(theme.control)
+        } else {
+            style(index)
+        }
+    };
 
     // メインパネル
 
-    // 16進数ヘッダー
-    let hex_header = Line::from(format!(
-        "{:width$} +0 +1 +2 +3 +4 +5 +6 +7 +8 +9 +A +B +C +D +E +F",
-        " ",
-        width = 8
-    ))
-    .magenta();
+    // 16進数ヘッダー(型付き表示では要素ごと)
+    let typed = *message.main_view() == MainView::Typed;
+    let value_type = *message.value_type();
+    let bitmap = *message.main_view() == MainView::Bitmap;
+    let plot = *message.main_view() == MainView::Plot;
+    let analysis = *message.main_view() == MainView::Analysis;
+    let mut hex_header = if analysis {
+        let header = entropy_header(bin_data.buf(), cursor.index(), message.entropy_window());
+        format!(" {header}")
+    } else if bitmap {
+        format!("{:width$} {}", " ", message.bitmap(), width = 8)
+    } else if plot {
+        let columns = layout.main.area.width as usize;
+        let header = plot_header(
+            bin_data.buf(),
+            cursor.index(),
+            message.plot(),
+            *message.endian(),
+            columns,
+        );
+        format!(" {header}")
+    } else if typed {
+        let header = Converter::typed_header(value_type, constants::LINE_LEN);
+        format!("{:width$} {header}", " ", width = 8)
+    } else {
+        format!(
+            "{:width$} +0 +1 +2 +3 +4 +5 +6 +7 +8 +9 +A +B +C +D +E +F",
+            " ",
+            width = 8
+        )
+    };
+    if xxd && !bitmap && !plot && !analysis {
+        let width = 9 + main_row_width(message);
+        hex_header = format!("{hex_header:width$}  0123456789ABCDEF");
+    }
+    let hex_header = Line::styled(hex_header, theme.header);
+
+    // スクロール量から表示範囲の編集データを切り出す
+    let scroll_y = message.scroll().scroll_y();
+    let (main_buf, main_offset) = visible_buf(bin_data.buf(), scroll_y[0], layout.main.area);
 
     // 編集データ
     let mut main_panel_data = Vec::new();
-    main_panel_data.append(&mut Converter::convert_to_lines::<ForHex>(
-        bin_data.buf(),
-        constants::LINE_LEN,
-    ));
-
-    // スクロール量で編集データの表示調整
-    let main_contents =
-        Paragraph::new(Text::from(main_panel_data)).scroll((message.scroll().scroll_y()[0], 0));
+    if plot || analysis {
+        // グラフは描画時に作る
+    } else if bitmap {
+        main_panel_data.append(&mut bitmap_lines(
+            bin_data.buf(),
+            cursor.index(),
+            message.bitmap(),
+            *message.endian(),
+            layout.main.area,
+            theme,
+        ));
+    } else if typed {
+        let range = main_offset..main_offset + main_buf.len();
+        let text = xxd.then(|| {
+            Converter::convert_to_text_cells(bin_data.buf(), range, encoding, table, control)
+        });
+        main_panel_data.append(&mut Converter::convert_to_typed_lines(
+            main_buf,
+            text.as_deref(),
+            main_offset,
+            value_type,
+            *message.endian(),
+            style,
+            text_style,
+        ));
+    } else if xxd {
+        let range = main_offset..main_offset + main_buf.len();
+        let text =
+            Converter::convert_to_text_cells(bin_data.buf(), range, encoding, table, control);
+        main_panel_data.append(&mut Converter::convert_to_xxd_lines(
+            main_buf,
+            &text,
+            constants::LINE_LEN,
+            main_offset,
+            style,
+            text_style,
+        ));
+    } else {
+        main_panel_data.append(&mut Converter::convert_to_lines::<ForHex>(
+            main_buf,
+            constants::LINE_LEN,
+            main_offset,
+            style,
+        ));
+    }
+    // セクタ単位の表示中はオフセット欄をセクタ番号にする
+    if let Some(sectors) = sectors.filter(|_| !(bitmap || plot || analysis)) {
+        for (i, line) in main_panel_data.iter_mut().enumerate() {
+            let label = sectors.label(main_offset + i * constants::LINE_LEN);
+            let style = match label.starts_with('#') {
+                true => theme.header,
+                false => Style::new(),
+            };
+            if let Some(span) = line.spans.first_mut() {
+                *span = Span::styled(format!("{label} "), style);
+            }
+        }
+    }
+    let main_contents = Paragraph::new(Text::from(main_panel_data));
+
+    // スクロールバー
+    let total_rows = bin_data.buf().len().div_ceil(constants::LINE_LEN);
+    let mut scrollbar_state = ScrollbarState::new(total_rows)
+        .position(scroll_y[0])
+        .viewport_content_length(layout.main.area.height as usize);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .style(theme.border);
 
     // サブパネル0
 
-    // Asciiヘッダー
-    let ascii_header = Line::from(format!("{:width$}+0123456789ABCDEF", " ", width = 8)).magenta();
+    // テキストヘッダー
+    let ascii_header = Line::styled(
+        format!("{:width$}+0123456789ABCDEF", " ", width = 8),
+        theme.header,
+    );
 
-    // Asciiデコーデッドデータ
+    // 文字コードに従ってデコードしたデータ
     let mut sub0_panel_data = Vec::new();
-    sub0_panel_data.append(&mut Converter::convert_to_lines::<ForAscii>(
-        bin_data.buf(),
-        constants::LINE_LEN,
-    ));
-
-    // スクロール量でAsciiデコーデッドデータの表示調整
-    let sub0_contents =
-        Paragraph::new(Text::from(sub0_panel_data)).scroll((message.scroll().scroll_y()[1], 0));
-
-    // パネルブロック
-    let sub0_block = Block::default()
-        .title(Line::from(" ASCII ").centered())
-        .borders(Borders::ALL)
-        .border_set(border::THICK);
+    if let Some(text) = layout.text {
+        let (sub0_buf, sub0_offset) = visible_buf(bin_data.buf(), scroll_y[1], text.area);
+        let range = sub0_offset..sub0_offset + sub0_buf.len();
+        let cells =
+            Converter::convert_to_text_cells(bin_data.buf(), range, encoding, table, control);
+        sub0_panel_data.append(&mut Converter::convert_to_text_lines(
+            &cells,
+            constants::LINE_LEN,
+            sub0_offset,
+            text_style,
+        ));
+    }
+    let sub0_contents = Paragraph::new(Text::from(sub0_panel_data));
+
+    // パネルブロック(文字テーブルの場合はファイル名)
+    let encoding_name = match table {
+        Some(table) if encoding == TextEncoding::Table => table.name().to_string(),
+        _ => encoding.to_string(),
+    };
+    let sub0_block = theme
+        .block()
+        .title(Line::from(format!(" {encoding_name} ")).centered());
+
+    // サブパネル1(インスペクタ)
+    let sub1_block = theme.block().title(Line::from(" Inspector ").centered());
+    let sub1_contents = Paragraph::new(inspect_lines(bin_data.buf(), cursor.index()));
+
+    // 構造パネル
+    let structure_block = theme
+        .block()
+        .title(Line::from(" Structure ").centered())
+        .title_bottom(
+            Line::from(format!(
+                " {}: focus  {}: keys ",
+                keymap::label_of(Action::FocusStructure),
+                keymap::label_of(Action::Help)
+            ))
+            .centered(),
+        );
+    let structure_contents = layout.structure.map(|panel| {
+        let height = structure_block.inner(panel).height;
+        Paragraph::new(structure_lines(structure, height, theme))
+    });
 
-    // サブパネル1
-    let sub1_block = Block::default()
-        .borders(Borders::ALL)
-        .border_set(border::THICK);
+    // 全体俯瞰ストリップ
+    let overview_block = theme.block();
+    let overview_contents = layout.overview.map(|panel| {
+        let viewport = main_offset..main_offset + main_buf.len();
+        let height = overview_block.inner(panel).height;
+        Paragraph::new(overview_lines(bin_data.buf(), height, viewport, theme))
+    });
 
     // 描画
+    let mut strings_position = None;
     let _ = terminal.draw(|frame| {
-        // メインパネル
-        let main_panel = layout[0][0];
-        let main_header = layout[2][0];
-        let main_area = layout[2][1];
-
-        // サブパネル0
-        let sub0_panel = layout[1][0];
-        let sub0_header = layout[3][0];
-        let sub0_area = layout[3][1];
-
-        // サブパネル1
-        let sub1_panel = layout[1][1];
-
         // パネルを描画
         frame.render_widget(Clear, frame.area());
 
         // メインパネル
-        frame.render_widget(&block, main_panel);
-        frame.render_widget(hex_header, main_header);
-        frame.render_widget(main_contents, main_area);
+        frame.render_widget(&block, layout.main.panel);
+        frame.render_widget(hex_header, layout.main.header);
+        if analysis {
+            render_analysis(
+                frame,
+                layout.main.area,
+                bin_data.buf(),
+                cursor.index(),
+                message.entropy_window(),
+                message.selected_range(),
+                theme,
+            );
+        } else if plot {
+            render_plot(
+                frame,
+                layout.main.area,
+                bin_data.buf(),
+                cursor.index(),
+                message.plot(),
+                *message.endian(),
+                theme,
+            );
+        } else {
+            frame.render_widget(main_contents, layout.main.area);
+        }
+        frame.render_stateful_widget(
+            scrollbar,
+            layout.main.panel.inner(Margin::new(0, 1)),
+            &mut scrollbar_state,
+        );
+
+        // ステータスライン
+        frame.render_widget(status, layout.status);
 
         // サブパネル0
-        frame.render_widget(&sub0_block, sub0_panel);
-        frame.render_widget(ascii_header, sub0_header);
-        frame.render_widget(sub0_contents, sub0_area);
+        if let Some(text) = layout.text {
+            frame.render_widget(&sub0_block, text.panel);
+            frame.render_widget(ascii_header, text.header);
+            frame.render_widget(sub0_contents, text.area);
+        }
 
         // サブパネル1
-        frame.render_widget(&sub1_block, sub1_panel);
+        if let Some(inspector) = layout.inspector {
+            frame.render_widget(sub1_contents.block(sub1_block), inspector);
+        }
+
+        // 全体俯瞰ストリップ
+        if let (Some(panel), Some(contents)) = (layout.overview, overview_contents) {
+            frame.render_widget(contents.block(overview_block), panel);
+        }
+
+        // 構造パネル
+        if let (Some(panel), Some(contents)) = (layout.structure, structure_contents) {
+            frame.render_widget(contents.block(structure_block), panel);
+        }
+
+        // 文字列一覧
+        if message.strings().is_visible() {
+            strings_position = Some(render_strings(frame, message.strings(), theme));
+        }
+
+        // ヘルプ
+        if message.help().is_visible() {
+            render_help(frame, message.help().scroll(), theme);
+        }
     });
 
-    // カーソル表示
-    let _ = terminal.set_cursor_position(*cursor.position());
+    // カーソル表示(入力欄や文字列一覧の表示中は入力欄の末尾)
+    let position = match message.prompt() {
+        Some(prompt) => {
+            let width = Line::raw(prompt.input()).width() as u16 + 1;
+            Position::new(layout.status.x + width, layout.status.y)
+        }
+        None => strings_position.unwrap_or(*cursor.position()),
+    };
+    let _ = terminal.set_cursor_position(position);
     let _ = terminal.show_cursor();
 
     Ok(())
 }
 
+// スクロール量と表示領域の高さから、表示する範囲のデータと先頭オフセットを返す
+fn visible_buf(buf: &[u8], scroll: usize, area: Rect) -> (&[u8], usize) {
+    let start = scroll.saturating_mul(constants::LINE_LEN).min(buf.len());
+    let end = (start + area.height as usize * constants::LINE_LEN).min(buf.len());
+    (&buf[start..end], start)
+}
+
+// 全体俯瞰ストリップの行
+// 表示中の範囲に掛かる行には印を付ける
+fn overview_lines(
+    buf: &[u8],
+    height: u16,
+    viewport: std::ops::Range<usize>,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let count = height as usize;
+    analysis::overview(buf, count)
+        .into_iter()
+        .enumerate()
+        .map(|(i, region)| {
+            let start = buf.len() * i / count;
+            let end = buf.len() * (i + 1) / count;
+            let marker = if start < viewport.end && viewport.start <= end {
+                "▸"
+            } else {
+                " "
+            };
+            let (glyph, style) = theme.region(region);
+            Line::from(vec![marker.into(), Span::styled(glyph, style)])
+        })
+        .collect()
+}
+
 // ratatuiレンダリング準備
 pub(crate) fn render_prep(terminal: &mut DefaultTerminal, message: &mut Message) -> io::Result<()> {
     let frame = terminal.get_frame();
 
-    // 左右に50%分割
-    let main_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(frame.area());
+    // レイアウト計算
+    let screen_layout = calc_layout(frame.area(), message);
 
-    // 右側を上下に50%分割
-    let sub_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(main_layout[1]);
+    // ヘルプのスクロール量を表示できる範囲に収める
+    if message.help().is_visible() {
+        let max = help_max_scroll(frame.area(), message.theme());
+        message.help_mut().clamp_scroll(max);
+    }
 
-    // 左側をヘッダーとコンテンツに分割
-    let inner_main = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(1), Constraint::Min(1)])
-        .margin(1)
-        .split(main_layout[0]);
+    // カーソル行からスクロール量を計算
+    let row = message.cursor().row();
+    let scroll_y = message.scroll_mut().scroll_y_mut();
+    scroll_y[0] = Scroll::calc_scroll(row, screen_layout.main.area.height);
+    if let Some(text) = screen_layout.text {
+        scroll_y[1] = Scroll::calc_scroll(row, text.area.height);
+    }
+
+    // カーソル位置の算出
+    let origin = screen_layout.main.area.as_position();
+    let scroll = scroll_y[0];
+    let value_type = *message.value_type();
+    match message.main_view() {
+        MainView::Hex => message.cursor_mut().calc_position(origin, scroll),
+        MainView::Typed => message
+            .cursor_mut()
+            .calc_typed_position(origin, scroll, value_type),
+        MainView::Bitmap => message.cursor_mut().calc_bitmap_position(origin),
+        MainView::Plot => {
+            let area = screen_layout.main.area;
+            let plot = message.plot();
+            let frame = plot.frame_at(message.cursor().index());
+            let start = plot.window_start(frame, area.width as usize);
+            let column = ((frame - start) / plot.zoom()) as u16;
+            let position = Position::new(area.x + column, area.bottom().saturating_sub(1));
+            message.cursor_mut().set_position(position);
+        }
+        MainView::Analysis => {
+            // エントロピーのグラフの該当する桁
+            let area = screen_layout.entropy.unwrap_or_default();
+            let len = message.bin_data().buf().len().max(1);
+            let column = (message.cursor().index() * area.width as usize / len) as u16;
+            let position = Position::new(area.x + column, area.bottom().saturating_sub(1));
+            message.cursor_mut().set_position(position);
+        }
+    }
+
+    // レイアウトの保存
+    *message.layout_mut() = screen_layout;
 
-    // 右上側をヘッダーとコンテンツに分割
-    let inner_sub0 = Layout::default()
+    Ok(())
+}
+
+// 設定に応じたレイアウト計算
+fn calc_layout(area: Rect, message: &Message) -> ScreenLayout {
+    // 2ペイン以上を並べられない幅
+    const COMPACT_WIDTH: u16 = 100;
+    // 16進数パネル、xxd風パネルに必要な幅
+    // 1行の幅に枠・オフセット・スクロールバー、xxd風はテキストを加える
+    let hex_width = main_row_width(message) as u16 + 12;
+    let xxd_width = hex_width + 19;
+
+    // 全体俯瞰ストリップの幅(枠を含む)
+    const OVERVIEW_WIDTH: u16 = 4;
+    // 構造パネルの幅(枠を含む)
+    const STRUCTURE_WIDTH: u16 = 44;
+
+    let settings = message.settings();
+    let ratio = settings.main_ratio();
+
+    // 最下行をステータスラインとして確保
+    let [area, status] = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(1), Constraint::Min(1)])
-        .margin(1)
-        .split(sub_layout[0]);
+        .constraints([Constraint::Fill(1), Constraint::Length(1)])
+        .areas(area);
+
+    // 全体俯瞰ストリップを右端に確保
+    let (area, overview) = if settings.overview() && area.width > OVERVIEW_WIDTH + xxd_width {
+        let [area, overview] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Fill(1), Constraint::Length(OVERVIEW_WIDTH)])
+            .areas(area);
+        (area, Some(overview))
+    } else {
+        (area, None)
+    };
 
-    // カーソルY座標の算出
-    let pos_y = {
-        let cursor = message.cursor_mut();
-        cursor.calc_position();
-        cursor.position().y
+    // 構造パネルを右端に確保
+    let (area, structure) = if message.structure().is_visible() && area.width > STRUCTURE_WIDTH {
+        let [area, structure] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Fill(1), Constraint::Length(STRUCTURE_WIDTH)])
+            .areas(area);
+        (area, Some(structure))
+    } else {
+        (area, None)
     };
-    // 画面の下限
-    let main_bottom = main_layout[0].bottom();
-    // メインパネルのスクロール開始ラインの算出
-    let main_border = Scroll::calc_border(main_bottom);
-    // メインパネルのスクロール量計算
-    let scroll_y = message.scroll_mut().scroll_y_mut();
-    scroll_y[0] = Scroll::calc_scroll(pos_y, main_border);
 
-    // サブパネルの下限
-    let sub0_bottom = sub_layout[0].bottom();
-    // サブ0パネルのスクロール開始ラインの算出
-    let sub0_border = Scroll::calc_border(sub0_bottom);
-    // サブ0パネルのスクロール量計算
-    scroll_y[1] = Scroll::calc_scroll(pos_y, sub0_border);
+    // 狭い端末では1ペインに縮退
+    let (preset, inspector) = if area.width < COMPACT_WIDTH {
+        let preset = if area.width < xxd_width {
+            LayoutPreset::HexOnly
+        } else {
+            LayoutPreset::Xxd
+        };
+        (preset, false)
+    } else {
+        (settings.layout(), settings.inspector())
+    };
 
-    // カーソルY座標の調整
-    let cursor = message.cursor_mut();
-    cursor.adjust_y(main_border);
+    let mut screen_layout = ScreenLayout {
+        preset: Some(preset),
+        status,
+        overview,
+        structure,
+        ..Default::default()
+    };
 
-    // レイアウトの保存
-    let screen_layout = message.layout_mut();
-    screen_layout[0] = main_layout;
-    screen_layout[1] = sub_layout;
-    screen_layout[2] = inner_main;
-    screen_layout[3] = inner_sub0;
+    match preset {
+        // 左に16進数、右にテキストとインスペクタ
+        LayoutPreset::SideBySide => {
+            let [left, right] = split_ratio(area, Direction::Horizontal, ratio);
+            screen_layout.main = split_pane(left);
+            if inspector {
+                let [top, bottom] = split_ratio(right, Direction::Vertical, 50);
+                screen_layout.text = Some(split_pane(top));
+                screen_layout.inspector = Some(bottom);
+            } else {
+                screen_layout.text = Some(split_pane(right));
+            }
+        }
+        // 上に16進数、下にテキストとインスペクタ
+        LayoutPreset::Stacked => {
+            let [top, bottom] = split_ratio(area, Direction::Vertical, ratio);
+            screen_layout.main = split_pane(top);
+            if inspector {
+                let [left, right] = split_ratio(bottom, Direction::Horizontal, 50);
+                screen_layout.text = Some(split_pane(left));
+                screen_layout.inspector = Some(right);
+            } else {
+                screen_layout.text = Some(split_pane(bottom));
+            }
+        }
+        // 1ペイン + インスペクタ
+        LayoutPreset::Xxd | LayoutPreset::HexOnly => {
+            if inspector {
+                // パネルに必要な幅は確保する
+                let width = if preset == LayoutPreset::Xxd {
+                    xxd_width
+                } else {
+                    hex_width
+                };
+                let [left, right] = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Min(width), Constraint::Percentage(100 - ratio)])
+                    .areas(area);
+                screen_layout.main = split_pane(left);
+                screen_layout.inspector = Some(right);
+            } else {
+                screen_layout.main = split_pane(area);
+            }
+        }
+    }
+
+    // 解析表示のエントロピーのグラフ(クリック位置の判定に使う)
+    if *message.main_view() == MainView::Analysis {
+        let [entropy, ..] = analysis_areas(screen_layout.main.area);
+        screen_layout.entropy = Some(entropy);
+    }
+
+    screen_layout
+}
 
-    Ok(())
+// メインパネルの1行の幅(オフセットを除く)
+fn main_row_width(message: &Message) -> usize {
+    match message.main_view() {
+        MainView::Hex | MainView::Bitmap | MainView::Plot | MainView::Analysis => {
+            constants::LINE_LEN * 3 - 1
+        }
+        MainView::Typed => Converter::typed_row_width(*message.value_type(), constants::LINE_LEN),
+    }
+}
+
+// 比率(%)で2分割
+fn split_ratio(area: Rect, direction: Direction, ratio: u16) -> [Rect; 2] {
+    Layout::default()
+        .direction(direction)
+        .constraints([Constraint::Percentage(ratio), Constraint::Fill(1)])
+        .areas(area)
+}
+
+// パネルをヘッダーとコンテンツに分割
+fn split_pane(panel: Rect) -> Pane {
+    let [header, area] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .margin(1)
+        .areas(panel);
+    Pane {
+        panel,
+        header,
+        area,
+    }
 }
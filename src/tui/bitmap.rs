@@ -0,0 +1,233 @@
+// 画素表示
+
+// ratatuiクレート
+use ratatui::prelude::*;
+// 状態管理
+use crate::message::{Bitmap, Endian, Palette, PixelFormat};
+// テーマ
+use crate::theme::Theme;
+
+// 表示領域左端から画素までの幅
+const OFFSET_WIDTH: u16 = 9;
+
+// VGAの16色
+const VGA16: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0x00, 0x00, 0xaa),
+    (0x00, 0xaa, 0x00),
+    (0x00, 0xaa, 0xaa),
+    (0xaa, 0x00, 0x00),
+    (0xaa, 0x00, 0xaa),
+    (0xaa, 0x55, 0x00),
+    (0xaa, 0xaa, 0xaa),
+    (0x55, 0x55, 0x55),
+    (0x55, 0x55, 0xff),
+    (0x55, 0xff, 0x55),
+    (0x55, 0xff, 0xff),
+    (0xff, 0x55, 0x55),
+    (0xff, 0x55, 0xff),
+    (0xff, 0xff, 0x55),
+    (0xff, 0xff, 0xff),
+];
+
+// 色を使わない場合の濃淡
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+// 画素表示の行
+// start の画素を左上にして、1文字に上下2画素を半角ブロックで描く
+// 色を使わないテーマでは1文字1画素を濃淡の記号で描く
+pub(super) fn bitmap_lines(
+    buf: &[u8],
+    start: usize,
+    bitmap: &Bitmap,
+    endian: Endian,
+    area: Rect,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let columns = bitmap
+        .width
+        .min(area.width.saturating_sub(OFFSET_WIDTH) as usize);
+    let rows_per_line = if theme.is_monochrome() { 1 } else { 2 };
+    let pixel = |x: usize, y: usize| pixel_rgb(buf, start, bitmap, endian, x, y);
+
+    // データの終わりより後の行は描かない
+    (0..area.height as usize)
+        .take_while(|line| start + line * rows_per_line * bitmap.stride() < buf.len())
+        .map(|line| {
+            let y = line * rows_per_line;
+            let offset = start + y * bitmap.stride();
+            let mut spans = vec![Span::raw(format!("{offset:08X} "))];
+            for x in 0..columns {
+                let span = if theme.is_monochrome() {
+                    let shade = pixel(x, y).map_or(' ', |(r, g, b)| {
+                        // 輝度を濃淡の段階にする
+                        let luma = (r as usize * 299 + g as usize * 587 + b as usize * 114) / 1000;
+                        SHADES[luma * SHADES.len() / 256]
+                    });
+                    Span::raw(shade.to_string())
+                } else {
+                    half_block(pixel(x, y), pixel(x, y + 1))
+                };
+                spans.push(span);
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+// 上下2画素を1文字にする
+fn half_block(top: Option<(u8, u8, u8)>, bottom: Option<(u8, u8, u8)>) -> Span<'static> {
+    let rgb = |(r, g, b): (u8, u8, u8)| Color::Rgb(r, g, b);
+    match (top, bottom) {
+        (Some(top), Some(bottom)) => Span::styled("▀", Style::new().fg(rgb(top)).bg(rgb(bottom))),
+        (Some(top), None) => Span::styled("▀", Style::new().fg(rgb(top))),
+        (None, Some(bottom)) => Span::styled("▄", Style::new().fg(rgb(bottom))),
+        (None, None) => Span::raw(" "),
+    }
+}
+
+// (x, y) の画素の色
+// データの範囲外は None
+fn pixel_rgb(
+    buf: &[u8],
+    start: usize,
+    bitmap: &Bitmap,
+    endian: Endian,
+    x: usize,
+    y: usize,
+) -> Option<(u8, u8, u8)> {
+    if x >= bitmap.width {
+        return None;
+    }
+    let bits = bitmap.format.bits();
+    let bit = x * bits;
+    let index = start + y * bitmap.stride() + bit / 8;
+
+    match bitmap.format {
+        PixelFormat::Rgb565 => {
+            let bytes = buf.get(index..index + 2)?;
+            let value = match endian {
+                Endian::Little => u16::from_le_bytes([bytes[0], bytes[1]]),
+                Endian::Big => u16::from_be_bytes([bytes[0], bytes[1]]),
+            };
+            let scale = |value: u16, max: u16| (value as u32 * 255 / max as u32) as u8;
+            Some((
+                scale(value >> 11, 0x1f),
+                scale((value >> 5) & 0x3f, 0x3f),
+                scale(value & 0x1f, 0x1f),
+            ))
+        }
+        PixelFormat::Rgb888 => {
+            let bytes = buf.get(index..index + 3)?;
+            Some((bytes[0], bytes[1], bytes[2]))
+        }
+        _ => {
+            // 上位ビットが左の画素
+            let byte = *buf.get(index)?;
+            let shift = 8 - bits - bit % 8;
+            let mask = (1u16 << bits) - 1;
+            let value = (byte as u16 >> shift) & mask;
+            Some(palette_rgb(bitmap.palette, value as u8, mask as u8))
+        }
+    }
+}
+
+// パレット番号の色
+// max はパレット番号の最大値
+fn palette_rgb(palette: Palette, value: u8, max: u8) -> (u8, u8, u8) {
+    let level = |value: u8| (value as u32 * 255 / max as u32) as u8;
+    match palette {
+        Palette::Gray => {
+            let x = level(value);
+            (x, x, x)
+        }
+        Palette::Vga if max == 0xff => xterm256(value),
+        Palette::Vga => VGA16[value as usize & 0x0f],
+        Palette::Heat => {
+            // 青 → 水色 → 緑 → 黄 → 赤
+            let t = level(value) as u32 * 4;
+            let (segment, x) = ((t / 256).min(3), (t % 256) as u8);
+            match segment {
+                0 => (0, x, 0xff),
+                1 => (0, 0xff, 0xff - x),
+                2 => (x, 0xff, 0),
+                _ if value == max => (0xff, 0, 0),
+                _ => (0xff, 0xff - x, 0),
+            }
+        }
+    }
+}
+
+// 端末の256色
+fn xterm256(value: u8) -> (u8, u8, u8) {
+    const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    match value {
+        0..=15 => VGA16[value as usize],
+        16..=231 => {
+            let i = (value - 16) as usize;
+            (CUBE[i / 36], CUBE[(i / 6) % 6], CUBE[i % 6])
+        }
+        _ => {
+            let x = 8 + (value - 232) * 10;
+            (x, x, x)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pixel_rgb() {
+        let mut bitmap = Bitmap::new();
+        bitmap.apply("width=8 bpp=1").unwrap();
+        // 上位ビットが左の画素
+        let buf = [0b1000_0001, 0x00];
+        assert_eq!(
+            pixel_rgb(&buf, 0, &bitmap, Endian::Little, 0, 0),
+            Some((255, 255, 255))
+        );
+        assert_eq!(
+            pixel_rgb(&buf, 0, &bitmap, Endian::Little, 1, 0),
+            Some((0, 0, 0))
+        );
+        assert_eq!(
+            pixel_rgb(&buf, 0, &bitmap, Endian::Little, 7, 0),
+            Some((255, 255, 255))
+        );
+        assert_eq!(pixel_rgb(&buf, 0, &bitmap, Endian::Little, 0, 2), None);
+        assert_eq!(pixel_rgb(&buf, 0, &bitmap, Endian::Little, 8, 0), None);
+
+        bitmap.apply("width=1 bpp=rgb565").unwrap();
+        let buf = [0x1f, 0x00];
+        assert_eq!(
+            pixel_rgb(&buf, 0, &bitmap, Endian::Little, 0, 0),
+            Some((0, 0, 255))
+        );
+        assert_eq!(
+            pixel_rgb(&buf, 0, &bitmap, Endian::Big, 0, 0),
+            Some((24, 226, 0))
+        );
+
+        assert_eq!(palette_rgb(Palette::Vga, 12, 15), (0xff, 0x55, 0x55));
+        assert_eq!(palette_rgb(Palette::Vga, 232, 255), (8, 8, 8));
+        assert_eq!(palette_rgb(Palette::Heat, 0, 255), (0, 0, 255));
+        assert_eq!(palette_rgb(Palette::Heat, 255, 255), (255, 0, 0));
+    }
+
+    #[test]
+    fn test_bitmap_apply() {
+        let mut bitmap = Bitmap::new();
+        bitmap.apply("width=8 stride=16").unwrap();
+        assert_eq!(bitmap.stride(), 16);
+        assert!(bitmap.apply("width=0").is_err());
+        assert!(bitmap.apply("width=4097").is_err());
+        assert!(bitmap.apply("stride=16385").is_err());
+        assert!(bitmap.apply("stride=18446744073709551615").is_err());
+        // 失敗した指定は途中まで反映しない
+        assert!(bitmap.apply("bpp=1 stride=99999").is_err());
+        assert_eq!(bitmap.format, PixelFormat::Indexed8);
+        assert_eq!((bitmap.width, bitmap.stride()), (8, 16));
+    }
+}
@@ -1,27 +1,428 @@
 // 変換処理系
 
-// crosstermクレート
-use ratatui::text::Line;
+// ratatuiクレート
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+// 文字幅
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+// 状態管理
+use crate::message::{ControlDisplay, Endian, TextEncoding};
+// 型付きの値
+use crate::value::ValueType;
+// 文字テーブル
+use crate::table::{CharTable, EntryKind};
+// 定数
+use crate::constants;
+
+// 1バイト文字コードの変換表
+mod codepage;
 
 // コンバーター
 pub(super) struct Converter;
 
 impl Converter {
+    // 多バイト文字の2バイト目以降の表示
+    const CONTINUATION_CHAR: char = '·';
+    // 表示範囲より前から復号を始める量
+    const LOOKBACK: usize = 64;
+    // 文字テーブルの終端と改行の表示
+    const END_CHAR: char = '¶';
+    const NEWLINE_CHAR: char = '↵';
+    // 幅に収まらない文字列の省略
+    const ELLIPSIS_CHAR: char = '…';
+
     // ratatuiのLines向けに変換
-    pub(crate) fn convert_to_lines<F: ConverterTrait>(buf: &[u8], len: usize) -> Vec<Line> {
+    // offset は buf 先頭のオフセットで、行頭に表示する
+    // style はオフセットごとの装飾
+    pub(crate) fn convert_to_lines<F: ConverterTrait>(
+        buf: &[u8],
+        len: usize,
+        offset: usize,
+        style: impl Fn(usize) -> Style,
+    ) -> Vec<Line<'static>> {
+        let cells = Self::convert_to_cells::<F>(buf);
+        Self::cells_to_lines(&cells, F::SEP, len, offset, style)
+    }
+
+    // 16進数とテキストを1行に並べて変換(xxd風)
+    // text は convert_to_text_cells で作ったセルで、text_style で装飾する
+    pub(crate) fn convert_to_xxd_lines(
+        buf: &[u8],
+        text: &[String],
+        len: usize,
+        offset: usize,
+        style: impl Fn(usize) -> Style,
+        text_style: impl Fn(usize) -> Style,
+    ) -> Vec<Line<'static>> {
+        let hex = Self::convert_to_cells::<ForHex>(buf);
+        let mut vec = Vec::new();
+        hex.chunks(len)
+            .zip(text.chunks(len))
+            .enumerate()
+            .for_each(|(i, (hex, text))| {
+                let line_offset = offset + i * len;
+                let mut spans = vec![Self::offset_span(line_offset)];
+                spans.append(&mut Self::cells_to_spans(
+                    hex,
+                    ForHex::SEP,
+                    line_offset,
+                    &style,
+                ));
+                // 16進数の幅を揃えてからテキストを続ける
+                let pad = (len - hex.len()) * 3 + 2;
+                spans.push(Span::raw(" ".repeat(pad)));
+                spans.append(&mut Self::cells_to_spans(
+                    text,
+                    "",
+                    line_offset,
+                    &text_style,
+                ));
+                vec.push(Line::from(spans))
+            });
+        vec
+    }
+
+    // 型付きの値の配列として変換
+    // 1行のバイト数は16進数表示と同じで、text があれば xxd 風に後ろへ並べる
+    pub(crate) fn convert_to_typed_lines(
+        buf: &[u8],
+        text: Option<&[String]>,
+        offset: usize,
+        value_type: ValueType,
+        endian: Endian,
+        style: impl Fn(usize) -> Style,
+        text_style: impl Fn(usize) -> Style,
+    ) -> Vec<Line<'static>> {
+        let len = constants::LINE_LEN;
+        let size = value_type.size();
+        let width = value_type.width();
+        let row_width = Self::typed_row_width(value_type, len);
+
         let mut vec = Vec::new();
-        buf.chunks(len).for_each(|x| {
-            vec.push(Line::from(format!(
-                "{:width$} {}",
-                " ",
-                F::convert(x),
-                width = 8
-            )))
+        for (i, row) in buf.chunks(len).enumerate() {
+            let line_offset = offset + i * len;
+            let mut spans = vec![Self::offset_span(line_offset)];
+            let mut used = 0;
+            for (k, element) in row.chunks(size).enumerate() {
+                if k > 0 {
+                    spans.push(Span::raw(" "));
+                    used += 1;
+                }
+                // 要素に満たない末尾は16進数で表示する
+                let value = value_type
+                    .format(element, endian)
+                    .unwrap_or_else(|| ForHex::convert(element).replace(' ', ""));
+                spans.push(Span::styled(
+                    format!("{value:>width$}"),
+                    style(line_offset + k * size),
+                ));
+                used += width;
+            }
+            if let Some(text) = text {
+                let cells = &text[(i * len).min(text.len())..((i + 1) * len).min(text.len())];
+                spans.push(Span::raw(" ".repeat(row_width - used + 2)));
+                spans.append(&mut Self::cells_to_spans(
+                    cells,
+                    "",
+                    line_offset,
+                    &text_style,
+                ));
+            }
+            vec.push(Line::from(spans));
+        }
+        vec
+    }
+
+    // 型付き表示の1行の幅(オフセットを除く)
+    pub(crate) fn typed_row_width(value_type: ValueType, len: usize) -> usize {
+        let count = len.div_ceil(value_type.size());
+        count * (value_type.width() + 1) - 1
+    }
+
+    // 型付き表示のヘッダー(要素ごとの相対オフセット)
+    pub(crate) fn typed_header(value_type: ValueType, len: usize) -> String {
+        let width = value_type.width();
+        (0..len)
+            .step_by(value_type.size())
+            .map(|x| format!("{:>width$}", format!("+{x:X}")))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // テキストパネル向けに変換
+    // cells は convert_to_text_cells で作ったセル
+    pub(crate) fn convert_to_text_lines(
+        cells: &[String],
+        len: usize,
+        offset: usize,
+        style: impl Fn(usize) -> Style,
+    ) -> Vec<Line<'static>> {
+        Self::cells_to_lines(cells, "", len, offset, style)
+    }
+
+    // 1バイトずつ変換
+    fn convert_to_cells<F: ConverterTrait>(buf: &[u8]) -> Vec<String> {
+        buf.iter().map(|x| F::convert(&[*x])).collect()
+    }
+
+    // 文字コードに従って buf[range] の各バイトに対応するセルを作る
+    // 多バイト文字は先頭バイトのセルに文字を置き、残りは継続の印にする
+    // 全角文字は次のセルまで使う(行末では行の外にはみ出す)
+    // 制御文字は control の方法で表示する
+    pub(crate) fn convert_to_text_cells(
+        buf: &[u8],
+        range: std::ops::Range<usize>,
+        encoding: TextEncoding,
+        table: Option<&CharTable>,
+        control: ControlDisplay,
+    ) -> Vec<String> {
+        if let (TextEncoding::Table, Some(table)) = (encoding, table) {
+            return Self::convert_to_table_cells(buf, range, table);
+        }
+        if encoding.is_single_byte() {
+            let mut cells = Self::convert_to_single_byte_cells(&buf[range.clone()], encoding);
+            if control != ControlDisplay::Dot {
+                for (cell, index) in cells.iter_mut().zip(range) {
+                    if let Some(glyph) = Self::control_glyph_at(buf, index, encoding, control) {
+                        *cell = glyph;
+                    }
+                }
+            }
+            return cells;
+        }
+
+        let mut cells = Vec::with_capacity(range.len());
+        let mut pos = Self::sync_start(buf, range.start, encoding);
+        while pos < range.end {
+            let (len, ch) = decode_char(&buf[pos..], encoding);
+            let glyph = match ch {
+                Some(x) => control_glyph(x, control).unwrap_or(Self::to_printable(x).to_string()),
+                None => Self::to_printable_char(0).to_string(),
+            };
+            let wide = glyph.width() > 1;
+            // 行末から始まる全角文字は行の外にはみ出す
+            let last_column = pos % constants::LINE_LEN == constants::LINE_LEN - 1;
+
+            for k in 0..len {
+                let index = pos + k;
+                if !range.contains(&index) {
+                    continue;
+                }
+                let cell = match k {
+                    0 => glyph.clone(),
+                    // 全角文字に覆われるセル
+                    1 if wide && !last_column && pos >= range.start => String::new(),
+                    _ => Self::CONTINUATION_CHAR.to_string(),
+                };
+                cells.push(cell);
+            }
+            pos += len;
+        }
+        cells
+    }
+
+    // buf[index] が制御文字(ASCIIでは0x80以上も含む)の場合の表示
+    // UTF-16 の2バイト目や文字テーブルでは None
+    fn control_glyph_at(
+        buf: &[u8],
+        index: usize,
+        encoding: TextEncoding,
+        control: ControlDisplay,
+    ) -> Option<String> {
+        let byte = *buf.get(index)?;
+        let ch = match encoding {
+            TextEncoding::Table => return None,
+            TextEncoding::Ascii if byte >= 0x80 => return high_glyph(control),
+            // 2バイト単位の先頭のみ
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be if !index.is_multiple_of(2) => {
+                return None
+            }
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+                decode_char(&buf[index..], encoding).1?
+            }
+            _ if encoding.is_single_byte() => decode_char(&[byte], encoding).1?,
+            // 多バイト文字コードはそのバイトを含む文字で決める
+            _ => Self::char_at(buf, index, encoding)?,
+        };
+        control_glyph(ch, control)
+    }
+
+    // buf[index] を含む文字(文字にならないバイトでは None)
+    fn char_at(buf: &[u8], index: usize, encoding: TextEncoding) -> Option<char> {
+        let mut pos = Self::sync_start(buf, index, encoding);
+        while pos <= index {
+            let (len, ch) = decode_char(&buf[pos..], encoding);
+            if index < pos + len {
+                return ch;
+            }
+            pos += len;
+        }
+        None
+    }
+
+    // buf[index] が制御文字として表示されるか
+    pub(crate) fn is_control_at(
+        buf: &[u8],
+        index: usize,
+        encoding: TextEncoding,
+        control: ControlDisplay,
+    ) -> bool {
+        control != ControlDisplay::Dot
+            && Self::control_glyph_at(buf, index, encoding, control).is_some()
+    }
+
+    // 文字テーブルに従って buf[range] の各バイトに対応するセルを作る
+    // 項目の文字列はバイト数分の幅に収め、収まらない場合は末尾を省略する
+    fn convert_to_table_cells(
+        buf: &[u8],
+        range: std::ops::Range<usize>,
+        table: &CharTable,
+    ) -> Vec<String> {
+        let mut cells = Vec::with_capacity(range.len());
+        let mut pos = range.start.saturating_sub(Self::LOOKBACK);
+        while pos < range.end {
+            let (len, text) = match table.decode(&buf[pos..]) {
+                Some((len, entry)) => (len, Self::entry_text(&entry.text, entry.kind)),
+                None => (1, Self::to_printable_char(0).to_string()),
+            };
+
+            for (k, cell) in Self::fit_cells(&text, len).into_iter().enumerate() {
+                if range.contains(&(pos + k)) {
+                    cells.push(cell);
+                }
+            }
+            pos += len;
+        }
+        cells
+    }
+
+    // 項目の表示用文字列
+    fn entry_text(text: &str, kind: EntryKind) -> String {
+        match kind {
+            EntryKind::Normal => text.chars().map(Self::to_printable).collect(),
+            EntryKind::End => String::from(Self::END_CHAR),
+            EntryKind::Newline => String::from(Self::NEWLINE_CHAR),
+        }
+    }
+
+    // 文字列を len 個のセルへ割り当てる
+    // 先頭のセルに文字列を置き、覆われるセルは空、余ったセルは継続の印にする
+    // 1バイトの全角文字だけは幅を超えてもそのまま表示する
+    fn fit_cells(text: &str, len: usize) -> Vec<String> {
+        let mut text = text.to_string();
+        let mut width: usize = text.chars().map(|x| x.width().unwrap_or(1)).sum();
+        if width > len && text.chars().count() > 1 {
+            let mut fitted = String::new();
+            width = 1;
+            for ch in text.chars() {
+                let w = ch.width().unwrap_or(1);
+                if width + w > len {
+                    break;
+                }
+                fitted.push(ch);
+                width += w;
+            }
+            fitted.push(Self::ELLIPSIS_CHAR);
+            text = fitted;
+        }
+
+        let mut cells = vec![text];
+        for k in 1..len {
+            if k < width {
+                cells.push(String::new());
+            } else {
+                cells.push(Self::CONTINUATION_CHAR.to_string());
+            }
+        }
+        cells
+    }
+
+    // 1バイト文字コードで変換
+    fn convert_to_single_byte_cells(buf: &[u8], encoding: TextEncoding) -> Vec<String> {
+        // ISO-8859の部番号ごとに変換を選ぶ
+        macro_rules! iso8859 {
+            ($part:expr, $($x:literal)*) => {
+                match $part {
+                    $($x => Self::convert_to_cells::<ForIso8859<$x>>(buf),)*
+                    _ => Self::convert_to_cells::<ForLatin1>(buf),
+                }
+            };
+        }
+
+        match encoding {
+            TextEncoding::Ebcdic037 => Self::convert_to_cells::<ForEbcdic037>(buf),
+            TextEncoding::Ebcdic1047 => Self::convert_to_cells::<ForEbcdic1047>(buf),
+            TextEncoding::Cp437 => Self::convert_to_cells::<ForCp437>(buf),
+            TextEncoding::Petscii => Self::convert_to_cells::<ForPetscii>(buf),
+            TextEncoding::Iso8859(part) => iso8859!(part, 2 3 4 5 6 7 8 9 10 11 13 14 15 16),
+            TextEncoding::Latin1 => Self::convert_to_cells::<ForLatin1>(buf),
+            _ => Self::convert_to_cells::<ForAscii>(buf),
+        }
+    }
+
+    // 復号を始める位置
+    // 表示範囲の少し前から始めて文字の区切りを合わせる
+    fn sync_start(buf: &[u8], start: usize, encoding: TextEncoding) -> usize {
+        let pos = start.saturating_sub(Self::LOOKBACK);
+        match encoding {
+            // 2バイト単位に揃える
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => pos & !1,
+            // 継続バイトでない位置まで進める
+            TextEncoding::Utf8 => (pos..start)
+                .find(|&i| buf[i] & 0xc0 != 0x80)
+                .unwrap_or(start),
+            _ => pos,
+        }
+    }
+
+    // 行頭のオフセット表示
+    fn offset_span(offset: usize) -> Span<'static> {
+        Span::raw(format!("{:0width$X} ", offset, width = 8))
+    }
+
+    // セルを行に分けて変換
+    fn cells_to_lines(
+        cells: &[String],
+        sep: &'static str,
+        len: usize,
+        offset: usize,
+        style: impl Fn(usize) -> Style,
+    ) -> Vec<Line<'static>> {
+        let mut vec = Vec::new();
+        cells.chunks(len).enumerate().for_each(|(i, x)| {
+            let line_offset = offset + i * len;
+            let mut spans = vec![Self::offset_span(line_offset)];
+            spans.append(&mut Self::cells_to_spans(x, sep, line_offset, &style));
+            vec.push(Line::from(spans))
         });
         // dbg!(&vec);
         vec
     }
 
+    // セルに装飾を付ける
+    // 区切り文字は前後が同じ装飾の場合のみ装飾する
+    fn cells_to_spans(
+        cells: &[String],
+        sep: &'static str,
+        offset: usize,
+        style: &impl Fn(usize) -> Style,
+    ) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        for (i, x) in cells.iter().enumerate() {
+            let current = style(offset + i);
+            if i > 0 && !sep.is_empty() {
+                let sep_style = if style(offset + i - 1) == current {
+                    current
+                } else {
+                    Style::default()
+                };
+                spans.push(Span::styled(sep, sep_style));
+            }
+            spans.push(Span::styled(x.clone(), current));
+        }
+        spans
+    }
+
     // 制御文字等もDUMMY_CHARに変換して読めるようにする
     pub(crate) fn to_printable_char(num: u8) -> char {
         const DUMMY_CHAR: char = '.';
@@ -31,6 +432,199 @@ impl Converter {
             _ => char::from(num),
         }
     }
+
+    // 制御文字と幅の無い文字をDUMMY_CHARに変換する
+    fn to_printable(ch: char) -> char {
+        match ch.width() {
+            Some(width) if width > 0 && !ch.is_control() => ch,
+            _ => Self::to_printable_char(0),
+        }
+    }
+}
+
+// 1文字分を復号し、使ったバイト数と文字を返す
+// 復号できない場合は1バイトだけ使って None を返す
+fn decode_char(buf: &[u8], encoding: TextEncoding) -> (usize, Option<char>) {
+    let len = char_len(buf, encoding);
+    let Some(bytes) = buf.get(..len) else {
+        return (1, None);
+    };
+
+    let ch = match encoding {
+        TextEncoding::Ascii | TextEncoding::Latin1 => Some(char::from(bytes[0])),
+        TextEncoding::Utf8 => std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|x| x.chars().next()),
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+            let units = bytes.chunks(2).map(|x| {
+                if encoding == TextEncoding::Utf16Le {
+                    u16::from_le_bytes([x[0], x[1]])
+                } else {
+                    u16::from_be_bytes([x[0], x[1]])
+                }
+            });
+            char::decode_utf16(units).next().and_then(|x| x.ok())
+        }
+        TextEncoding::ShiftJis => decode_with(encoding_rs::SHIFT_JIS, bytes),
+        TextEncoding::EucJp => decode_with(encoding_rs::EUC_JP, bytes),
+        TextEncoding::Ebcdic037 => Some(codepage::EBCDIC_037[bytes[0] as usize]),
+        TextEncoding::Ebcdic1047 => Some(codepage::EBCDIC_1047[bytes[0] as usize]),
+        TextEncoding::Cp437 => Some(codepage::CP437[bytes[0] as usize]),
+        TextEncoding::Petscii => Some(codepage::PETSCII[bytes[0] as usize]),
+        TextEncoding::Iso8859(part) => codepage::decode_iso8859(part, bytes[0]),
+        // 文字テーブルは convert_to_table_cells で扱う
+        TextEncoding::Table => None,
+    };
+
+    match ch {
+        Some(ch) => (len, Some(ch)),
+        None => (1, None),
+    }
+}
+
+// 制御文字の表示
+// 制御文字でない場合と "." で表示する場合は None
+fn control_glyph(ch: char, control: ControlDisplay) -> Option<String> {
+    if !ch.is_control() {
+        return None;
+    }
+    let code = ch as u32;
+    match (control, code) {
+        (ControlDisplay::Dot, _) => None,
+        // U+2400からの図記号
+        (ControlDisplay::Pictures, 0x00..=0x1f) => char::from_u32(0x2400 + code).map(String::from),
+        (ControlDisplay::Pictures, 0x7f) => Some(String::from('␡')),
+        // ^@ ^A ... ^_ と ^?
+        // 1セルに収めるため ^ の代わりに上線を文字に重ねる
+        // (^ を重ねると Latin-1 の Â などと区別できない。上線には合成済みの文字が無い)
+        (ControlDisplay::Caret, 0x00..=0x1f) => {
+            Some(format!("{}\u{305}", (0x40 + code) as u8 as char))
+        }
+        (ControlDisplay::Caret, 0x7f) => Some(String::from("?\u{305}")),
+        // 種類ごとの記号
+        (ControlDisplay::Glyphs, 0x00) => Some(String::from('∅')),
+        (ControlDisplay::Glyphs, 0x09..=0x0d) => Some(String::from('↲')),
+        (ControlDisplay::Glyphs, 0x01..=0x1f | 0x7f) => Some(String::from('◆')),
+        // C1制御文字
+        _ => high_glyph(control),
+    }
+}
+
+// 0x80以上の文字にならないバイトの表示
+fn high_glyph(control: ControlDisplay) -> Option<String> {
+    match control {
+        ControlDisplay::Dot => None,
+        _ => Some(String::from('▪')),
+    }
+}
+
+// 文字列を文字コード(または文字テーブル)でバイト列へ変換
+pub(crate) fn encode_text(
+    text: &str,
+    encoding: TextEncoding,
+    table: Option<&CharTable>,
+) -> Result<Vec<u8>, String> {
+    let unencodable = |ch: char| format!("Cannot encode '{ch}' in {encoding}");
+    match encoding {
+        TextEncoding::Table => match table {
+            Some(table) => table.encode(text),
+            None => Err(String::from("No table loaded")),
+        },
+        TextEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+        TextEncoding::Utf16Le => Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+        TextEncoding::Utf16Be => Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+        TextEncoding::ShiftJis | TextEncoding::EucJp => {
+            let target = if encoding == TextEncoding::ShiftJis {
+                encoding_rs::SHIFT_JIS
+            } else {
+                encoding_rs::EUC_JP
+            };
+            let (bytes, _, had_errors) = target.encode(text);
+            if had_errors {
+                Err(format!("Cannot encode text in {encoding}"))
+            } else {
+                Ok(bytes.into_owned())
+            }
+        }
+        // 1バイト文字コードは256通りから逆引きする
+        TextEncoding::Ascii => text
+            .chars()
+            .map(|ch| {
+                u8::try_from(ch)
+                    .ok()
+                    .filter(u8::is_ascii)
+                    .ok_or(unencodable(ch))
+            })
+            .collect(),
+        _ => text
+            .chars()
+            .map(|ch| {
+                (0..=u8::MAX)
+                    .find(|&x| decode_char(&[x], encoding).1 == Some(ch))
+                    .ok_or(unencodable(ch))
+            })
+            .collect(),
+    }
+}
+
+// encoding_rsで1文字分を復号
+fn decode_with(encoding: &'static encoding_rs::Encoding, bytes: &[u8]) -> Option<char> {
+    let text = encoding.decode_without_bom_handling_and_without_replacement(bytes)?;
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Some(ch),
+        _ => None,
+    }
+}
+
+// 先頭バイトから1文字のバイト数を求める
+fn char_len(buf: &[u8], encoding: TextEncoding) -> usize {
+    let lead = buf[0];
+    let trail = |i: usize, range: std::ops::RangeInclusive<u8>| {
+        buf.get(i).is_some_and(|x| range.contains(x))
+    };
+
+    match encoding {
+        TextEncoding::Utf8 => {
+            let len = match lead {
+                0xc2..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf4 => 4,
+                _ => 1,
+            };
+            if (1..len).all(|i| trail(i, 0x80..=0xbf)) {
+                len
+            } else {
+                1
+            }
+        }
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+            let unit = |i: usize| {
+                let x = buf.get(i..i + 2)?;
+                Some(if encoding == TextEncoding::Utf16Le {
+                    u16::from_le_bytes([x[0], x[1]])
+                } else {
+                    u16::from_be_bytes([x[0], x[1]])
+                })
+            };
+            match (unit(0), unit(2)) {
+                (Some(0xd800..=0xdbff), Some(0xdc00..=0xdfff)) => 4,
+                _ => 2,
+            }
+        }
+        TextEncoding::ShiftJis => match lead {
+            0x81..=0x9f | 0xe0..=0xfc if trail(1, 0x40..=0x7e) || trail(1, 0x80..=0xfc) => 2,
+            _ => 1,
+        },
+        TextEncoding::EucJp => match lead {
+            0x8e if trail(1, 0xa1..=0xdf) => 2,
+            0x8f if trail(1, 0xa1..=0xfe) && trail(2, 0xa1..=0xfe) => 3,
+            0xa1..=0xfe if trail(1, 0xa1..=0xfe) => 2,
+            _ => 1,
+        },
+        // 1バイト文字コード
+        _ => 1,
+    }
 }
 
 // 16進数変換
@@ -39,19 +633,41 @@ pub(super) struct ForHex;
 // Ascii変換
 pub(super) struct ForAscii;
 
+// Latin-1(ISO-8859-1)変換
+pub(super) struct ForLatin1;
+
+// EBCDIC(コードページ037)変換
+pub(super) struct ForEbcdic037;
+
+// EBCDIC(コードページ1047)変換
+pub(super) struct ForEbcdic1047;
+
+// CP437(IBM PC)変換
+pub(super) struct ForCp437;
+
+// PETSCII(Commodore)変換
+pub(super) struct ForPetscii;
+
+// ISO-8859-x変換
+pub(super) struct ForIso8859<const PART: u8>;
+
 pub(super) trait ConverterTrait {
+    // バイト間の区切り
+    const SEP: &'static str = "";
+
     fn convert(buf: &[u8]) -> String;
 }
 
 impl ConverterTrait for ForHex {
+    const SEP: &'static str = " ";
+
     // 16進数へ変換
     fn convert(buf: &[u8]) -> String {
-        let sep = String::from(" ");
         let hex = buf
             .iter()
             .map(|x| format!("{:02X}", x))
             .collect::<Vec<_>>()
-            .join(&sep);
+            .join(Self::SEP);
         hex
     }
 }
@@ -66,3 +682,227 @@ impl ConverterTrait for ForAscii {
         })
     }
 }
+
+impl ConverterTrait for ForLatin1 {
+    // Latin-1へ変換
+    fn convert(buf: &[u8]) -> String {
+        buf.iter()
+            .map(|&x| Converter::to_printable(char::from(x)))
+            .collect()
+    }
+}
+
+// 変換表で1バイトずつ変換
+fn convert_with_table(buf: &[u8], table: &[char; 256]) -> String {
+    buf.iter()
+        .map(|&x| Converter::to_printable(table[x as usize]))
+        .collect()
+}
+
+impl ConverterTrait for ForEbcdic037 {
+    // EBCDIC 037へ変換
+    fn convert(buf: &[u8]) -> String {
+        convert_with_table(buf, &codepage::EBCDIC_037)
+    }
+}
+
+impl ConverterTrait for ForEbcdic1047 {
+    // EBCDIC 1047へ変換
+    fn convert(buf: &[u8]) -> String {
+        convert_with_table(buf, &codepage::EBCDIC_1047)
+    }
+}
+
+impl ConverterTrait for ForCp437 {
+    // CP437へ変換
+    fn convert(buf: &[u8]) -> String {
+        convert_with_table(buf, &codepage::CP437)
+    }
+}
+
+impl ConverterTrait for ForPetscii {
+    // PETSCIIへ変換
+    fn convert(buf: &[u8]) -> String {
+        convert_with_table(buf, &codepage::PETSCII)
+    }
+}
+
+impl<const PART: u8> ConverterTrait for ForIso8859<PART> {
+    // ISO-8859-xへ変換
+    // 未定義のバイトは DUMMY_CHAR にする
+    fn convert(buf: &[u8]) -> String {
+        buf.iter()
+            .map(|&x| {
+                codepage::decode_iso8859(PART, x)
+                    .map(Converter::to_printable)
+                    .unwrap_or(Converter::to_printable_char(0))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // テキストセルを行ごとに連結
+    fn text_rows(buf: &[u8], encoding: TextEncoding) -> Vec<String> {
+        let cells = Converter::convert_to_text_cells(
+            buf,
+            0..buf.len(),
+            encoding,
+            None,
+            ControlDisplay::Dot,
+        );
+        assert_eq!(cells.len(), buf.len());
+        cells
+            .chunks(constants::LINE_LEN)
+            .map(|x| x.concat())
+            .collect()
+    }
+
+    #[test]
+    fn test_utf8_wide() {
+        // あ は3バイトで全角
+        let res = text_rows("aあb".as_bytes(), TextEncoding::Utf8);
+        assert_eq!(res, vec!["aあ·b"]);
+    }
+
+    #[test]
+    fn test_shift_jis_across_rows() {
+        // 行末から始まる2バイト文字は次の行の先頭を継続の印にする
+        let mut buf = vec![b'x'; constants::LINE_LEN - 1];
+        buf.extend_from_slice(&[0x82, 0xa0, b'y']);
+        let res = text_rows(&buf, TextEncoding::ShiftJis);
+        assert_eq!(res[0], format!("{}あ", "x".repeat(constants::LINE_LEN - 1)));
+        assert_eq!(res[1], "·y");
+    }
+
+    #[test]
+    fn test_euc_jp_and_invalid() {
+        // あ(A4 A2) と不正なバイト
+        let res = text_rows(&[0xa4, 0xa2, 0xff, b'z'], TextEncoding::EucJp);
+        assert_eq!(res, vec!["あ.z"]);
+    }
+
+    #[test]
+    fn test_utf16_surrogate() {
+        // a と 😀(D83D DE00)
+        let res = text_rows(&[0x61, 0x00, 0x3d, 0xd8, 0x00, 0xde], TextEncoding::Utf16Le);
+        assert_eq!(res, vec!["a·😀··"]);
+    }
+
+    #[test]
+    fn test_single_byte_code_pages() {
+        // "Hi" と 0x80台
+        let res = text_rows(&[0xc8, 0x89, 0x5f, 0x4a], TextEncoding::Ebcdic037);
+        assert_eq!(res, vec!["Hi¬¢"]);
+        let res = text_rows(&[0xc8, 0x89, 0x5f, 0xad], TextEncoding::Ebcdic1047);
+        assert_eq!(res, vec!["Hi^["]);
+        let res = text_rows(&[0x01, 0xc9, 0xcd, 0xbb, 0x00], TextEncoding::Cp437);
+        assert_eq!(res, vec!["☺╔═╗."]);
+        let res = text_rows(&[0x48, 0x49, 0x5c, 0x73], TextEncoding::Petscii);
+        assert_eq!(res, vec!["HI£♥"]);
+        let res = text_rows(&[0xc6, 0x85, 0xa4], TextEncoding::Iso8859(5));
+        assert_eq!(res, vec!["Ц.Є"]);
+        assert_eq!("iso-8859-5".parse(), Ok(TextEncoding::Iso8859(5)));
+        assert_eq!("IBM1047".parse(), Ok(TextEncoding::Ebcdic1047));
+        assert!("iso-8859-12".parse::<TextEncoding>().is_err());
+    }
+
+    #[test]
+    fn test_table_cells() {
+        let table = CharTable::parse(
+            String::from("test.tbl"),
+            "41=A\n0102=th\n03=the\n04=あ\n/FF\n",
+        )
+        .unwrap();
+        let buf = [0x41, 0x01, 0x02, 0x03, 0x04, 0xff, 0x99];
+        let cells = Converter::convert_to_text_cells(
+            &buf,
+            0..buf.len(),
+            TextEncoding::Table,
+            Some(&table),
+            ControlDisplay::Dot,
+        );
+        assert_eq!(cells, vec!["A", "th", "", "…", "あ", "¶", "."]);
+        // 多バイト項目の途中から表示する場合
+        let cells = Converter::convert_to_text_cells(
+            &buf,
+            2..4,
+            TextEncoding::Table,
+            Some(&table),
+            ControlDisplay::Dot,
+        );
+        assert_eq!(cells, vec!["", "…"]);
+    }
+
+    #[test]
+    fn test_control_display() {
+        let buf = [0x00, 0x0a, 0x1b, 0x7f, 0xff, b'a'];
+        let cells = |encoding, control| {
+            Converter::convert_to_text_cells(&buf, 0..buf.len(), encoding, None, control).concat()
+        };
+        assert_eq!(cells(TextEncoding::Ascii, ControlDisplay::Dot), ".....a");
+        assert_eq!(
+            cells(TextEncoding::Ascii, ControlDisplay::Pictures),
+            "␀␊␛␡▪a"
+        );
+        assert_eq!(
+            cells(TextEncoding::Ascii, ControlDisplay::Caret),
+            "@\u{305}J\u{305}[\u{305}?\u{305}▪a"
+        );
+        // Latin-1 の Â (0xC2) と ^A は別の表示
+        let latin1 = |buf: &[u8]| {
+            Converter::convert_to_text_cells(
+                buf,
+                0..buf.len(),
+                TextEncoding::Latin1,
+                None,
+                ControlDisplay::Caret,
+            )
+        };
+        assert_eq!(latin1(&[0x01, 0xc2]), vec!["A\u{305}", "Â"]);
+        assert!(!latin1(&[0x01])[0].contains('\u{302}'));
+        assert_eq!(cells(TextEncoding::Ascii, ControlDisplay::Glyphs), "∅↲◆◆▪a");
+        // 多バイト文字コードでも同じ
+        assert_eq!(
+            cells(TextEncoding::Utf8, ControlDisplay::Pictures),
+            "␀␊␛␡.a"
+        );
+        assert!(Converter::is_control_at(
+            &buf,
+            1,
+            TextEncoding::Utf8,
+            ControlDisplay::Caret
+        ));
+        assert!(!Converter::is_control_at(
+            &buf,
+            5,
+            TextEncoding::Utf8,
+            ControlDisplay::Caret
+        ));
+        // 文字の途中の 0x80-0x9F は制御文字ではない(U+0085 は制御文字)
+        let control = |buf: &[u8], index, encoding| {
+            Converter::is_control_at(buf, index, encoding, ControlDisplay::Pictures)
+        };
+        let text = "…\u{85}".as_bytes();
+        assert!(!control(text, 1, TextEncoding::Utf8));
+        assert!(control(text, 4, TextEncoding::Utf8));
+        assert!(!control(&[0x83, 0x5c], 0, TextEncoding::ShiftJis));
+        assert!(!control(&[0x8e, 0xb1], 0, TextEncoding::EucJp));
+    }
+
+    #[test]
+    fn test_encode_text() {
+        assert_eq!(
+            encode_text("Hi", TextEncoding::Ebcdic037, None),
+            Ok(vec![0xc8, 0x89])
+        );
+        assert_eq!(
+            encode_text("あ", TextEncoding::ShiftJis, None),
+            Ok(vec![0x82, 0xa0])
+        );
+        assert!(encode_text("é", TextEncoding::Ascii, None).is_err());
+    }
+}
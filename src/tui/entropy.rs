@@ -0,0 +1,119 @@
+// 解析表示(エントロピーとバイト値の分布)
+
+// 標準ライブラリ
+use std::ops::RangeInclusive;
+// ratatuiクレート
+use ratatui::prelude::*;
+use ratatui::widgets::{Axis, Chart, Dataset, GraphType, Paragraph, Sparkline};
+// データ解析
+use crate::analysis;
+// テーマ
+use crate::theme::Theme;
+
+// 上半分にエントロピー、下半分に見出し・分布・目盛り
+pub(super) fn analysis_areas(area: Rect) -> [Rect; 4] {
+    Layout::vertical([
+        Constraint::Percentage(50),
+        Constraint::Length(1),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .areas(area)
+}
+
+// エントロピーのグラフの桁からデータの位置を求める
+pub(crate) fn entropy_index(len: usize, column: u16, width: u16) -> usize {
+    len * column as usize / width.max(1) as usize
+}
+
+// ヘッダー(窓の大きさとカーソル位置のエントロピー)
+pub(super) fn entropy_header(buf: &[u8], cursor: usize, window: usize) -> String {
+    let end = (cursor + window).min(buf.len());
+    let entropy = analysis::entropy(&buf[cursor.min(end)..end]);
+    format!("Entropy  window {window}  cursor {entropy:.2} bit/byte")
+}
+
+// 解析表示を描画
+// 分布は選択範囲があればその範囲、なければファイル全体
+pub(super) fn render_analysis(
+    frame: &mut Frame,
+    area: Rect,
+    buf: &[u8],
+    cursor: usize,
+    window: usize,
+    selected: Option<RangeInclusive<usize>>,
+    theme: &Theme,
+) {
+    let [entropy_area, header_area, histogram_area, scale_area] = analysis_areas(area);
+
+    // エントロピー(点字で1桁に2点)
+    let count = entropy_area.width as usize * 2;
+    let points: Vec<(f64, f64)> = analysis::entropy_profile(buf, count, window)
+        .into_iter()
+        .enumerate()
+        .map(|(x, entropy)| (x as f64, entropy))
+        .collect();
+    let x = (cursor * count / buf.len().max(1)) as f64;
+    let marker = [(x, 0.0), (x, 8.0)];
+    let datasets = vec![
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(theme.key)
+            .data(&marker),
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(theme.entropy)
+            .data(&points),
+    ];
+    let chart = Chart::new(datasets)
+        .x_axis(Axis::default().bounds([0.0, count.saturating_sub(1) as f64]))
+        .y_axis(Axis::default().bounds([0.0, 8.0]));
+    frame.render_widget(chart, entropy_area);
+
+    // バイト値の分布
+    let (range, target) = match selected {
+        Some(range) => {
+            let target = format!("selection {} bytes", range.end() - range.start() + 1);
+            (range, target)
+        }
+        None => (0..=buf.len().saturating_sub(1), String::from("all")),
+    };
+    let slice = buf.get(range).unwrap_or_default();
+    let counts = analysis::histogram(slice);
+    let header = Line::styled(histogram_header(&counts, &target), theme.header);
+    frame.render_widget(header, header_area);
+
+    // 桁数に合わせてバイト値をまとめる
+    let group = 256usize.div_ceil(histogram_area.width.max(1) as usize);
+    let bars: Vec<u64> = counts
+        .chunks(group)
+        .map(|x| x.iter().sum::<usize>() as u64)
+        .collect();
+    let sparkline = Sparkline::default().data(&bars).style(theme.text);
+    frame.render_widget(sparkline, histogram_area);
+
+    // 目盛り(左端と右端のバイト値)
+    let width = bars.len().saturating_sub(2);
+    let scale = Paragraph::new(format!("00{:>width$}", "FF")).style(theme.separator);
+    frame.render_widget(scale, scale_area);
+}
+
+// 分布の見出し(対象、異なる値の数、多い順に3つ)
+fn histogram_header(counts: &[usize; 256], target: &str) -> String {
+    let total: usize = counts.iter().sum();
+    let distinct = counts.iter().filter(|&&x| x > 0).count();
+    let mut ranking: Vec<(usize, usize)> = counts.iter().copied().enumerate().collect();
+    ranking.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let top: Vec<String> = ranking
+        .iter()
+        .take(3)
+        .filter(|x| x.1 > 0)
+        .map(|(byte, count)| format!("{byte:02X} {:.1}%", *count as f64 * 100.0 / total as f64))
+        .collect();
+    format!(
+        "Histogram  {target}  distinct {distinct}  top {}",
+        top.join(", ")
+    )
+}
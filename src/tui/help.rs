@@ -0,0 +1,90 @@
+// ヘルプ表示
+
+// ratatuiクレート
+use ratatui::prelude::*;
+use ratatui::widgets::{Clear, Paragraph};
+// キー割り当て
+use crate::keymap::{Category, KEY_BINDINGS};
+// テーマ
+use crate::theme::Theme;
+
+// キー割り当て表から分類ごとの行を作る
+fn help_lines(theme: &Theme) -> Vec<Line<'static>> {
+    // キー名の表示幅
+    let width = KEY_BINDINGS
+        .iter()
+        .map(|x| x.label().chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = Vec::new();
+    for category in Category::ALL {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::styled(category.name(), theme.category));
+        KEY_BINDINGS
+            .iter()
+            .filter(|x| x.category == category)
+            .for_each(|x| {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {:width$}  ", x.label()), theme.key),
+                    x.description.into(),
+                ]))
+            });
+    }
+    lines
+}
+
+// 中央に配置したヘルプの範囲
+fn help_area(area: Rect, lines: &[Line]) -> Rect {
+    let width = lines.iter().map(|x| x.width()).max().unwrap_or_default() as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(layout::Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(layout::Flex::Center)
+        .areas(area);
+    area
+}
+
+// 最後の行が下端に来るまでのスクロール量
+pub(super) fn help_max_scroll(area: Rect, theme: &Theme) -> u16 {
+    let lines = help_lines(theme);
+    let height = help_area(area, &lines).height.saturating_sub(2);
+    (lines.len() as u16).saturating_sub(height)
+}
+
+// パネルの上にヘルプを重ねて描画
+pub(super) fn render_help(frame: &mut Frame, scroll: u16, theme: &Theme) {
+    let lines = help_lines(theme);
+    let area = help_area(frame.area(), &lines);
+
+    let block = theme
+        .block()
+        .title(Line::styled(" Help ", theme.title).centered())
+        .title_bottom(Line::from(" j/k: scroll  other: close ").centered());
+    let contents = Paragraph::new(lines).block(block).scroll((scroll, 0));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(contents, area);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_help_max_scroll() {
+        let theme = Theme::new(crate::theme::ThemeName::Dark);
+        let count = help_lines(&theme).len() as u16;
+        // 枠の2行を除いた高さだけ見える
+        assert_eq!(
+            help_max_scroll(Rect::new(0, 0, 200, 12), &theme),
+            count - 10
+        );
+        assert_eq!(help_max_scroll(Rect::new(0, 0, 200, count + 2), &theme), 0);
+        assert_eq!(help_max_scroll(Rect::new(0, 0, 200, 1), &theme), count);
+    }
+}
@@ -0,0 +1,33 @@
+// インスペクタ
+
+// ratatuiクレート
+use ratatui::text::Line;
+
+// カーソル位置のデータを複数の型で解釈した行を作る
+pub(super) fn inspect_lines(buf: &[u8], index: usize) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(format!(" Offset  0x{index:08X} ({index})"))];
+
+    let Some(&byte) = buf.get(index) else {
+        lines.push(Line::from(" (end of data)"));
+        return lines;
+    };
+
+    // カーソル位置から読める分だけ取り出す
+    let rest = &buf[index..];
+    let word = rest.get(..2).map(|x| [x[0], x[1]]);
+    let dword = rest.get(..4).map(|x| [x[0], x[1], x[2], x[3]]);
+
+    lines.push(Line::from(format!(" u8      {byte}")));
+    lines.push(Line::from(format!(" i8      {}", byte as i8)));
+    lines.push(Line::from(format!(" bin     {byte:08b}")));
+    if let Some(x) = word {
+        lines.push(Line::from(format!(" u16 LE  {}", u16::from_le_bytes(x))));
+        lines.push(Line::from(format!(" u16 BE  {}", u16::from_be_bytes(x))));
+    }
+    if let Some(x) = dword {
+        lines.push(Line::from(format!(" u32 LE  {}", u32::from_le_bytes(x))));
+        lines.push(Line::from(format!(" u32 BE  {}", u32::from_be_bytes(x))));
+    }
+
+    lines
+}
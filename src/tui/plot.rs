@@ -0,0 +1,153 @@
+// 波形表示
+
+// ratatuiクレート
+use ratatui::prelude::*;
+use ratatui::widgets::{Axis, Chart, Dataset, GraphType, Sparkline};
+// 状態管理
+use crate::message::{Endian, Plot, PlotStyle};
+// テーマ
+use crate::theme::Theme;
+
+// 表示する範囲の桁ごとの最小値と最大値
+// 範囲外の桁は None
+fn plot_columns(
+    buf: &[u8],
+    plot: &Plot,
+    endian: Endian,
+    start: usize,
+    columns: usize,
+) -> Vec<Option<(f64, f64)>> {
+    (0..columns)
+        .map(|column| {
+            let first = start + column * plot.zoom();
+            (first..first + plot.zoom())
+                .map_while(|frame| plot.sample_at(buf, endian, frame))
+                .filter(|x| x.is_finite())
+                .fold(None, |acc, x| match acc {
+                    None => Some((x, x)),
+                    Some((min, max)) => Some((x.min(min), x.max(max))),
+                })
+        })
+        .collect()
+}
+
+// 値の範囲(平坦な場合は幅を持たせる)
+fn value_bounds(columns: &[Option<(f64, f64)>]) -> [f64; 2] {
+    let (low, high) = columns
+        .iter()
+        .flatten()
+        .fold((f64::MAX, f64::MIN), |(low, high), &(min, max)| {
+            (low.min(min), high.max(max))
+        });
+    match (low, high) {
+        (f64::MAX, _) => [0.0, 1.0],
+        (low, high) if low == high => [low - 1.0, high + 1.0],
+        bounds => [bounds.0, bounds.1],
+    }
+}
+
+// ヘッダー(設定、カーソルの標本、値の範囲)
+pub(super) fn plot_header(
+    buf: &[u8],
+    cursor: usize,
+    plot: &Plot,
+    endian: Endian,
+    columns: usize,
+) -> String {
+    let frame = plot.frame_at(cursor);
+    let start = plot.window_start(frame, columns);
+    let [low, high] = value_bounds(&plot_columns(buf, plot, endian, start, columns));
+    let value = plot
+        .sample_at(buf, endian, frame)
+        .map_or(String::from("-"), |x| x.to_string());
+    format!("{plot}  #{frame} = {value}  [{low} .. {high}]")
+}
+
+// 波形を描画
+// カーソルの標本は縦線(棒の場合は端末のカーソル)で示す
+pub(super) fn render_plot(
+    frame: &mut Frame,
+    area: Rect,
+    buf: &[u8],
+    cursor: usize,
+    plot: &Plot,
+    endian: Endian,
+    theme: &Theme,
+) {
+    let columns = area.width as usize;
+    let current = plot.frame_at(cursor);
+    let start = plot.window_start(current, columns);
+    let values = plot_columns(buf, plot, endian, start, columns);
+    let [low, high] = value_bounds(&values);
+
+    match plot.style {
+        PlotStyle::Line => {
+            // 桁ごとに最小値と最大値を結ぶ
+            let points: Vec<(f64, f64)> = values
+                .iter()
+                .enumerate()
+                .filter_map(|(x, value)| value.map(|value| (x as f64, value)))
+                .flat_map(|(x, (min, max))| [(x, min), (x, max)])
+                .collect();
+            let x = ((current - start) / plot.zoom()) as f64;
+            let marker = [(x, low), (x, high)];
+            let datasets = vec![
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(theme.key)
+                    .data(&marker),
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(theme.text)
+                    .data(&points),
+            ];
+            let chart = Chart::new(datasets)
+                .x_axis(Axis::default().bounds([0.0, columns.saturating_sub(1) as f64]))
+                .y_axis(Axis::default().bounds([low, high]));
+            frame.render_widget(chart, area);
+        }
+        PlotStyle::Bar => {
+            // 最小値からの高さ(桁の最大値)
+            let scale = u16::MAX as f64 / (high - low);
+            let bars: Vec<u64> = values
+                .iter()
+                .map(|x| x.map_or(0, |(_, max)| ((max - low) * scale) as u64))
+                .collect();
+            let sparkline = Sparkline::default()
+                .data(&bars)
+                .max(u16::MAX as u64)
+                .style(theme.text);
+            frame.render_widget(sparkline, area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plot_columns() {
+        // 2チャンネルの i16 (LE) で2チャンネル目を表示
+        let mut plot = Plot::new();
+        plot.apply("type=i16 channels=2 channel=1 zoom=2").unwrap();
+        let buf = [0, 0, 0xff, 0xff, 0, 0, 5, 0, 0, 0, 3, 0];
+        let columns = plot_columns(&buf, &plot, Endian::Little, 0, 3);
+        assert_eq!(columns, vec![Some((-1.0, 5.0)), Some((3.0, 3.0)), None]);
+        assert_eq!(value_bounds(&columns), [-1.0, 5.0]);
+        assert_eq!(value_bounds(&columns[1..]), [2.0, 4.0]);
+        assert_eq!(plot.frame_at(9), 2);
+        assert_eq!(plot.window_start(5, 2), 4);
+        // channel は指定の順によらない
+        let mut plot = Plot::new();
+        plot.apply("channel=2 channels=4").unwrap();
+        assert_eq!(plot.to_string(), "u8 line ch 3/4 x1");
+        assert!(Plot::new().apply("channels=2 channel=2").is_err());
+        // 失敗した指定は途中まで反映しない
+        let mut plot = Plot::new();
+        assert!(plot.apply("type=i16 zoom=0").is_err());
+        assert_eq!(plot.to_string(), "u8 line x1");
+    }
+}
@@ -0,0 +1,159 @@
+// ステータスライン
+
+// ratatuiクレート
+use ratatui::prelude::*;
+// キー割り当て
+use crate::keymap::{self, Action};
+// 状態管理
+use crate::message::{MainView, Message};
+
+// ステータスラインの1区画
+struct Segment {
+    // 幅が足りない場合は小さい値から省く
+    priority: u8,
+    spans: Vec<Span<'static>>,
+}
+
+impl Segment {
+    fn new(priority: u8, spans: Vec<Span<'static>>) -> Self {
+        Self { priority, spans }
+    }
+
+    fn width(&self) -> usize {
+        self.spans.iter().map(|x| x.width()).sum()
+    }
+}
+
+// 区切り
+const SEP: &str = " │ ";
+
+// ステータスラインを作る
+// 左側は状態、右側は通知と終了キー
+pub(super) fn status_line(message: &Message, notice: String, width: u16) -> Line<'static> {
+    let bin_data = message.bin_data();
+    let theme = message.theme();
+    let buf = bin_data.buf();
+    let index = message.cursor().index();
+
+    let mut left = Vec::new();
+
+    // モード
+    let mode = message.write_mode().to_string();
+    left.push(Segment::new(9, vec![Span::styled(mode, theme.mode)]));
+
+    // カーソル位置
+    left.push(Segment::new(
+        8,
+        vec![format!("0x{index:08X} ({index})").into()],
+    ));
+
+    // 変更の有無
+    if bin_data.is_modified() {
+        left.push(Segment::new(7, vec![Span::styled("[+]", theme.modified)]));
+    }
+
+    // カーソル位置の値
+    if let Some(&x) = buf.get(index) {
+        left.push(Segment::new(
+            6,
+            vec![format!("0x{x:02X} {x} 0o{x:03o} 0b{x:08b}").into()],
+        ));
+    }
+
+    // ファイルサイズ
+    left.push(Segment::new(5, vec![format!("Size {}", buf.len()).into()]));
+
+    // 選択範囲
+    if let Some(range) = message.selected_range() {
+        let len = range.end() - range.start() + 1;
+        left.push(Segment::new(
+            4,
+            vec![Span::styled(format!("Sel {len}"), theme.selection)],
+        ));
+    }
+
+    // 文字コード
+    left.push(Segment::new(
+        3,
+        vec![message.text_encoding().to_string().into()],
+    ));
+
+    // エンディアン(型付き表示と波形表示では型も、画素表示では画素形式も)
+    let endian = match message.main_view() {
+        MainView::Typed => format!("{} {}", message.value_type(), message.endian()),
+        MainView::Bitmap => format!("{} {}", message.bitmap().format, message.endian()),
+        MainView::Plot => format!("{} {}", message.plot().sample, message.endian()),
+        MainView::Hex | MainView::Analysis => message.endian().to_string(),
+    };
+    left.push(Segment::new(2, vec![endian.into()]));
+
+    let mut right = Vec::new();
+
+    // 通知
+    if !notice.is_empty() {
+        right.push(Segment::new(8, vec![Span::styled(notice, theme.notice)]));
+    }
+
+    // ヘルプキー
+    right.push(Segment::new(
+        1,
+        vec![
+            "Help ".into(),
+            Span::styled(format!("<{}>", keymap::label_of(Action::Help)), theme.key),
+        ],
+    ));
+
+    // 終了キー
+    right.push(Segment::new(
+        1,
+        vec![
+            "Quit ".into(),
+            Span::styled(format!("<{}> ", keymap::label_of(Action::Quit)), theme.key),
+        ],
+    ));
+
+    // 幅に収まるまで優先度の低い区画を省く
+    let total = |left: &[Segment], right: &[Segment]| {
+        let sum = |segments: &[Segment]| {
+            segments.iter().map(|x| x.width()).sum::<usize>()
+                + segments.len().saturating_sub(1) * SEP.chars().count()
+        };
+        sum(left) + sum(right) + 2
+    };
+    while total(&left, &right) > width as usize {
+        let lowest = left
+            .iter()
+            .chain(right.iter())
+            .map(|x| x.priority)
+            .min()
+            .unwrap_or_default();
+        if let Some(i) = left.iter().position(|x| x.priority == lowest) {
+            left.remove(i);
+        } else if let Some(i) = right.iter().position(|x| x.priority == lowest) {
+            right.remove(i);
+        } else {
+            break;
+        }
+    }
+
+    // 左右の間を空白で埋める
+    let gap = (width as usize).saturating_sub(total(&left, &right));
+    let mut spans = vec![Span::raw(" ")];
+    spans.append(&mut join(left, theme.separator));
+    spans.push(Span::raw(" ".repeat(gap + 1)));
+    spans.append(&mut join(right, theme.separator));
+
+    Line::from(spans)
+}
+
+// 区画を区切りで連結
+fn join(segments: Vec<Segment>, separator: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    for (i, mut segment) in segments.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(SEP, separator));
+        }
+        spans.append(&mut segment.spans);
+    }
+    spans
+}
@@ -0,0 +1,70 @@
+// 文字列一覧
+
+// ratatuiクレート
+use ratatui::prelude::*;
+use ratatui::widgets::{Clear, Paragraph};
+// 状態管理
+use crate::message::StringsPanel;
+// テーマ
+use crate::theme::Theme;
+
+// パネルの上に文字列一覧を重ねて描画
+// 絞り込み欄の入力位置を返す
+pub(super) fn render_strings(frame: &mut Frame, panel: &StringsPanel, theme: &Theme) -> Position {
+    // 中央に画面の8割の大きさで配置
+    let [area] = Layout::horizontal([Constraint::Percentage(80)])
+        .flex(layout::Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::vertical([Constraint::Percentage(80)])
+        .flex(layout::Flex::Center)
+        .areas(area);
+
+    let items = panel.filtered();
+    let kinds: Vec<&str> = panel.kinds().iter().map(|x| x.name()).collect();
+    let title = format!(
+        " Strings {}/{}  min {}  {} ",
+        items.len(),
+        panel.total(),
+        panel.min_len(),
+        kinds.join(",")
+    );
+    let block = theme
+        .block()
+        .title(Line::styled(title, theme.title).centered())
+        // ? は絞り込みの文字になるため F1 のみ案内する
+        .title_bottom(Line::from(" type: filter  F1: keys ").centered());
+    let inner = block.inner(area);
+
+    // 1行目は絞り込み欄、残りに選択位置が見える範囲を表示
+    let height = inner.height.saturating_sub(1) as usize;
+    let start = panel.selected().saturating_sub(height.saturating_sub(1));
+    let mut lines = vec![Line::from(vec![
+        Span::styled("Filter: ", theme.key),
+        Span::raw(panel.filter().to_string()),
+    ])];
+    lines.extend(
+        items
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(height)
+            .map(|(i, x)| {
+                let style = if i == panel.selected() {
+                    theme.selection
+                } else {
+                    Style::new()
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:08X} ", x.offset), theme.header),
+                    Span::styled(format!("{:7} ", x.kind.name()), theme.separator),
+                    Span::styled(x.text.replace('\t', " "), style),
+                ])
+            }),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+
+    let width = Line::raw(panel.filter()).width() as u16;
+    Position::new(inner.x + 8 + width, inner.y)
+}
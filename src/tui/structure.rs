@@ -0,0 +1,66 @@
+// 構造パネル
+
+// ratatuiクレート
+use ratatui::prelude::*;
+// 状態管理
+use crate::message::StructurePanel;
+// テーマ
+use crate::theme::Theme;
+
+// 構造の木の表示行
+// 選択行が見える範囲を切り出す
+pub(super) fn structure_lines(
+    panel: &StructurePanel,
+    height: u16,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let height = height as usize;
+    let start = panel.selected().saturating_sub(height.saturating_sub(1));
+    panel
+        .rows()
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(height)
+        .map(|(i, row)| {
+            let marker = match (row.field.children.is_empty(), row.collapsed) {
+                (true, _) => "  ",
+                (false, true) => "▸ ",
+                (false, false) => "▾ ",
+            };
+            let name_style = if i == panel.selected() {
+                if panel.is_focused() {
+                    theme.selection
+                } else {
+                    Style::new().underlined()
+                }
+            } else {
+                Style::new()
+            };
+            Line::from(vec![
+                Span::raw(format!("{}{marker}", "  ".repeat(row.depth))),
+                Span::styled(row.field.name.clone(), name_style),
+                Span::styled(
+                    match row.field.value.as_str() {
+                        "" => String::new(),
+                        value => format!(" {value}"),
+                    },
+                    theme.header,
+                ),
+                Span::styled(
+                    row.field
+                        .link
+                        .map_or_else(String::new, |x| format!(" → {x:X}")),
+                    theme.key,
+                ),
+                Span::styled(
+                    row.field
+                        .error
+                        .as_ref()
+                        .map_or_else(String::new, |x| format!(" ! {x}")),
+                    theme.modified,
+                ),
+            ])
+        })
+        .collect()
+}
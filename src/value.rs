@@ -0,0 +1,233 @@
+// 型付きの値
+
+// 標準ライブラリ
+use std::fmt;
+// 状態管理
+use crate::message::Endian;
+
+// 型付き表示の要素の型
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ValueType {
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl ValueType {
+    const ALL: [ValueType; 8] = [
+        ValueType::U16,
+        ValueType::I16,
+        ValueType::U32,
+        ValueType::I32,
+        ValueType::U64,
+        ValueType::I64,
+        ValueType::F32,
+        ValueType::F64,
+    ];
+
+    // 次の型
+    pub(crate) fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|&x| x == self)
+            .unwrap_or_default();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    // 要素のバイト数
+    pub(crate) fn size(self) -> usize {
+        match self {
+            ValueType::U16 | ValueType::I16 => 2,
+            ValueType::U32 | ValueType::I32 | ValueType::F32 => 4,
+            ValueType::U64 | ValueType::I64 | ValueType::F64 => 8,
+        }
+    }
+
+    // 表示幅(最大の桁数)
+    pub(crate) fn width(self) -> usize {
+        match self {
+            ValueType::U16 => 5,
+            ValueType::I16 => 6,
+            ValueType::U32 => 10,
+            ValueType::I32 => 11,
+            ValueType::U64 | ValueType::I64 => 20,
+            ValueType::F32 => 11,
+            ValueType::F64 => 23,
+        }
+    }
+
+    // 1要素を表示用の文字列にする
+    // bytes が要素のバイト数に足りない場合は None
+    pub(crate) fn format(self, bytes: &[u8], endian: Endian) -> Option<String> {
+        let bytes = bytes.get(..self.size())?;
+        // エンディアンに従って u64 に詰める
+        let raw = bytes.iter().enumerate().fold(0u64, |acc, (i, &x)| {
+            let shift = match endian {
+                Endian::Little => i * 8,
+                Endian::Big => (self.size() - 1 - i) * 8,
+            };
+            acc | (x as u64) << shift
+        });
+
+        let text = match self {
+            ValueType::U16 | ValueType::U32 | ValueType::U64 => raw.to_string(),
+            ValueType::I16 => (raw as u16 as i16).to_string(),
+            ValueType::I32 => (raw as u32 as i32).to_string(),
+            ValueType::I64 => (raw as i64).to_string(),
+            ValueType::F32 => format_float(f32::from_bits(raw as u32), self.width()),
+            ValueType::F64 => format_float(f64::from_bits(raw), self.width()),
+        };
+        Some(text)
+    }
+
+    // 文字列を解釈してバイト列にする
+    // 整数は10進数または "0x" で始まる16進数
+    pub(crate) fn parse(self, text: &str, endian: Endian) -> Result<Vec<u8>, String> {
+        let text = text.trim();
+        let invalid = || format!("Invalid {self}: {text}");
+        let raw = match self {
+            ValueType::F32 => text.parse::<f32>().map_err(|_| invalid())?.to_bits() as u64,
+            ValueType::F64 => text.parse::<f64>().map_err(|_| invalid())?.to_bits(),
+            ValueType::U16 | ValueType::U32 | ValueType::U64 => {
+                let value = parse_int(text).ok_or_else(invalid)?;
+                let max = u64::MAX >> (64 - self.size() * 8);
+                if !(0..=max as i128).contains(&value) {
+                    return Err(invalid());
+                }
+                value as u64
+            }
+            ValueType::I16 | ValueType::I32 | ValueType::I64 => {
+                let value = parse_int(text).ok_or_else(invalid)?;
+                let bits = self.size() as u32 * 8;
+                let (min, max) = (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1);
+                if !(min..=max).contains(&value) {
+                    return Err(invalid());
+                }
+                value as i64 as u64
+            }
+        };
+
+        let bytes = raw.to_le_bytes()[..self.size()].to_vec();
+        Ok(match endian {
+            Endian::Little => bytes,
+            Endian::Big => bytes.into_iter().rev().collect(),
+        })
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ValueType::U16 => "u16",
+            ValueType::I16 => "i16",
+            ValueType::U32 => "u32",
+            ValueType::I32 => "i32",
+            ValueType::U64 => "u64",
+            ValueType::I64 => "i64",
+            ValueType::F32 => "f32",
+            ValueType::F64 => "f64",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// 浮動小数点数を幅に収まるように表示
+// 収まらない場合は指数表記にして精度を落とす
+fn format_float<T: fmt::Display + fmt::LowerExp>(value: T, width: usize) -> String {
+    let text = value.to_string();
+    if text.len() <= width {
+        return text;
+    }
+    let exp = format!("{value:e}");
+    if exp.len() <= width {
+        return exp;
+    }
+    (0..=16)
+        .rev()
+        .map(|precision| format!("{value:.precision$e}"))
+        .find(|x| x.len() <= width)
+        .unwrap_or(exp)
+}
+
+// 10進数または16進数の整数
+fn parse_int(text: &str) -> Option<i128> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(x) => (true, x),
+        None => (false, text),
+    };
+    let (radix, digits) = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        Some(hex) => (16, hex),
+        None => (10, digits),
+    };
+    // 符号は先頭の '-' だけ(from_str_radix は '+' や '-' も受け付ける)
+    if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let value = i128::from_str_radix(digits, radix).ok()?;
+    if negative {
+        value.checked_neg()
+    } else {
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_and_parse() {
+        let bytes = [0xfe, 0xff, 0x00, 0x00];
+        assert_eq!(
+            ValueType::I16.format(&bytes, Endian::Little),
+            Some(String::from("-2"))
+        );
+        assert_eq!(
+            ValueType::U16.format(&bytes, Endian::Big),
+            Some(String::from("65279"))
+        );
+        assert_eq!(ValueType::U32.format(&bytes[..3], Endian::Little), None);
+        let pi = ValueType::F64
+            .parse("3.141592653589793", Endian::Big)
+            .unwrap();
+        assert_eq!(
+            ValueType::F64.format(&pi, Endian::Big),
+            Some(String::from("3.141592653589793"))
+        );
+        // 幅に収まらない値は指数表記
+        let big = ValueType::F32.parse("-1e30", Endian::Little).unwrap();
+        assert_eq!(
+            ValueType::F32.format(&big, Endian::Little),
+            Some(String::from("-1e30"))
+        );
+        let small = ValueType::F32
+            .parse("1.2345678e-20", Endian::Little)
+            .unwrap();
+        assert_eq!(
+            ValueType::F32.format(&small, Endian::Little),
+            Some(String::from("1.23457e-20"))
+        );
+
+        assert_eq!(
+            ValueType::I16.parse("-0x8000", Endian::Little),
+            Ok(vec![0x00, 0x80])
+        );
+        assert_eq!(
+            ValueType::U32.parse("0x01020304", Endian::Big),
+            Ok(vec![1, 2, 3, 4])
+        );
+        assert!(ValueType::U16.parse("65536", Endian::Little).is_err());
+        for text in ["--5", "-+5", "+5", "0x-5", "-0x+5", "0x", ""] {
+            assert!(
+                ValueType::I32.parse(text, Endian::Little).is_err(),
+                "{text}"
+            );
+        }
+        assert!(ValueType::I32.parse("abc", Endian::Little).is_err());
+    }
+}